@@ -19,5 +19,63 @@ fn bench_render(c: &mut Criterion) {
     });
 }
 
+/// Scanline fill against the per-triangle fill it replaced, over every
+/// shape path of the corpus file scaled to 240x240.
+#[cfg(feature = "bench")]
+fn bench_fill(c: &mut Criterion) {
+    use rlottie_core::renderer::cpu::{draw_path, draw_path_triangles};
+    use rlottie_core::types::{Color, Layer, Paint, Vec2};
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/assets/corpus/1643-exploding-star.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let width = 240usize;
+    let height = 240usize;
+    let sx = width as f32 / comp.width.max(1) as f32;
+    let sy = height as f32 / comp.height.max(1) as f32;
+    let paths: Vec<_> = comp
+        .layers
+        .iter()
+        .filter_map(|l| match l {
+            Layer::Shape(shape) => Some(&shape.paths),
+            _ => None,
+        })
+        .flatten()
+        .map(|p| {
+            p.map_points(|v| Vec2 {
+                x: v.x * sx,
+                y: v.y * sy,
+            })
+        })
+        .collect();
+    let paint = Paint::Solid(Color {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+    });
+    let mut buf = vec![0u8; width * height * 4];
+    let mut group = c.benchmark_group("fill");
+    group.bench_function("scanline", |b| {
+        b.iter(|| {
+            for p in &paths {
+                draw_path(p, paint.clone(), &mut buf, width, height, width * 4);
+            }
+        });
+    });
+    group.bench_function("triangles", |b| {
+        b.iter(|| {
+            for p in &paths {
+                draw_path_triangles(p, paint.clone(), &mut buf, width, height, width * 4);
+            }
+        });
+    });
+    group.finish();
+}
+
+#[cfg(feature = "bench")]
+criterion_group!(benches, bench_render, bench_fill);
+#[cfg(not(feature = "bench"))]
 criterion_group!(benches, bench_render);
 criterion_main!(benches);
@@ -0,0 +1,9 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: frame exporters
+//! Mirrors: none (rlottie-rs extension)
+
+pub mod png;
+pub mod svg;
+pub use png::{encode_png, frame_to_png};
+pub use svg::frame_to_svg;
@@ -0,0 +1,146 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: PNG frame exporter
+//! Mirrors: none (rlottie-rs extension)
+
+use crate::types::Composition;
+use png::{BitDepth, ColorType, Encoder, ScaledFloat, SourceChromaticities, SrgbRenderingIntent};
+use std::io;
+
+/// Render a single frame of `comp` at `width`×`height` and encode it as PNG.
+pub fn frame_to_png(
+    comp: &Composition,
+    frame: u32,
+    width: usize,
+    height: usize,
+) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0u8; width * height * 4];
+    comp.render_sync(frame, &mut buf, width, height, width * 4);
+    encode_png(&buf, width, height, width * 4)
+}
+
+/// Encode a premultiplied RGBA8888 buffer, as the renderer fills it, as
+/// PNG. PNG stores straight alpha, so colors are divided by their alpha.
+///
+/// The file carries an `sRGB` chunk along with the `gAMA` and `cHRM` values
+/// the PNG specification lists as its fallback, so viewers without sRGB
+/// support still show the same colors.
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] if `stride` is shorter than a
+/// row or `buffer` is too small for `height` rows.
+pub fn encode_png(
+    buffer: &[u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) -> io::Result<Vec<u8>> {
+    let row = width
+        .checked_mul(4)
+        .filter(|&row| row <= stride)
+        .ok_or_else(|| invalid_input("stride is shorter than a row"))?;
+    let needed = match height {
+        0 => 0,
+        h => (h - 1)
+            .checked_mul(stride)
+            .and_then(|n| n.checked_add(row))
+            .ok_or_else(|| invalid_input("image is too large"))?,
+    };
+    if buffer.len() < needed {
+        return Err(invalid_input("buffer is too small for the image"));
+    }
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(&mut out, width as u32, height as u32);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(BitDepth::Eight);
+    encoder.set_source_srgb(SrgbRenderingIntent::Perceptual);
+    encoder.set_source_gamma(ScaledFloat::from_scaled(45455));
+    encoder.set_source_chromaticities(SourceChromaticities {
+        white: (
+            ScaledFloat::from_scaled(31270),
+            ScaledFloat::from_scaled(32900),
+        ),
+        red: (
+            ScaledFloat::from_scaled(64000),
+            ScaledFloat::from_scaled(33000),
+        ),
+        green: (
+            ScaledFloat::from_scaled(30000),
+            ScaledFloat::from_scaled(60000),
+        ),
+        blue: (
+            ScaledFloat::from_scaled(15000),
+            ScaledFloat::from_scaled(6000),
+        ),
+    });
+    let mut writer = encoder.write_header()?;
+    let mut rows: Vec<u8> = (0..height)
+        .flat_map(|y| &buffer[y * stride..y * stride + width * 4])
+        .copied()
+        .collect();
+    for px in rows.chunks_exact_mut(4) {
+        unpremultiply(px);
+    }
+    writer.write_image_data(&rows)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+fn invalid_input(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Convert one premultiplied RGBA pixel to straight alpha in place.
+fn unpremultiply(px: &mut [u8]) {
+    let a = px[3] as u32;
+    for c in &mut px[..3] {
+        *c = (*c as u32 * 255 + a / 2)
+            .checked_div(a)
+            .map_or(0, |v| v.min(255) as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::json;
+
+    #[test]
+    fn export_is_tagged_srgb() {
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+        let data = std::fs::read(path).unwrap();
+        let comp = json::from_slice(&data).unwrap();
+        let bytes = frame_to_png(&comp, 0, 16, 16).unwrap();
+
+        let decoder = png::Decoder::new(io::Cursor::new(bytes));
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (16, 16));
+        assert_eq!(info.srgb, Some(SrgbRenderingIntent::Perceptual));
+        assert_eq!(info.gama_chunk, Some(ScaledFloat::from_scaled(45455)));
+        assert!(info.chrm_chunk.is_some());
+    }
+
+    #[test]
+    fn half_transparent_pixel_round_trips_straight() {
+        // Premultiplied 50% red, and a fully transparent pixel.
+        let buffer = [128, 0, 0, 128, 9, 9, 9, 0];
+        let bytes = encode_png(&buffer, 2, 1, 8).unwrap();
+        let mut reader = png::Decoder::new(io::Cursor::new(bytes))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0u8; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, [255, 0, 0, 128, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn mismatched_buffer_is_rejected() {
+        let buffer = [0u8; 16];
+        for (width, height, stride) in [(2, 3, 8), (3, 1, 8), (2, 2, 4)] {
+            let err = encode_png(&buffer, width, height, stride).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(encode_png(&buffer, 2, 2, 8).is_ok());
+    }
+}
@@ -0,0 +1,505 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: SVG frame exporter
+//! Mirrors: none (rlottie-rs extension)
+
+use crate::geometry::{Path, PathSeg};
+use crate::renderer::cpu::{sample_opacity, sample_stops};
+use crate::types::{
+    parent_matrix, Affine, BlendMode, Color, ColorSpace, Composition, FillRule, GradientStop,
+    Layer, LineCap, LineJoin, MaskMode, OpacityStop, Paint, ShapeLayer, StrokeStyle, Vec2,
+};
+use std::fmt::Write;
+
+/// Half the side of the square, in layer units, standing in for "everywhere"
+/// in exported masks.
+const MASK_EXTENT: f32 = 1_000_000.0;
+
+/// Export a single frame of `comp` as a standalone SVG document.
+///
+/// Fills, strokes and gradients become `<path>` elements placed by their
+/// layer's `transform="matrix(...)"`, gradients are emitted into a `<defs>`
+/// section and layer masks map to luminance `<mask>` elements that follow
+/// each mask's mode, inversion and opacity.
+pub fn frame_to_svg(comp: &Composition, frame: u32) -> String {
+    let frame_no = comp.frame_at(frame).0 as f32;
+    let mut defs = String::new();
+    let mut body = String::new();
+    let mut next_id = 0usize;
+    write_layers(comp, frame_no, &mut defs, &mut body, &mut next_id);
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = comp.width,
+        h = comp.height
+    );
+    if !defs.is_empty() {
+        out.push_str("<defs>");
+        out.push_str(&defs);
+        out.push_str("</defs>");
+    }
+    if comp.opacity < 1.0 {
+        let _ = write!(out, r#"<g opacity="{}">"#, comp.opacity.max(0.0));
+        out.push_str(&body);
+        out.push_str("</g>");
+    } else {
+        out.push_str(&body);
+    }
+    out.push_str("</svg>");
+    out
+}
+
+/// Write the layers of `comp` at its absolute `frame`; each layer samples
+/// its own content at its local frame, as the raster path does.
+fn write_layers(
+    comp: &Composition,
+    frame: f32,
+    defs: &mut String,
+    body: &mut String,
+    next_id: &mut usize,
+) {
+    let layers = &comp.layers;
+    for layer in layers {
+        match layer {
+            Layer::Shape(shape) => {
+                let own = shape.local_frame(frame, comp.fps);
+                let local = shape.transform.at(own);
+                let matrix =
+                    parent_matrix(layers, shape.parent, frame) * Affine::from_transform(&local);
+                write_shape(shape, matrix, local.opacity, own, defs, body, next_id);
+            }
+            Layer::PreComp(pre) => {
+                let local = pre.transform.at(frame);
+                if local.opacity <= 0.0 {
+                    continue;
+                }
+                let matrix =
+                    parent_matrix(layers, pre.parent, frame) * Affine::from_transform(&local);
+                let _ = write!(body, "<g{}", transform_attr(matrix));
+                if local.opacity < 1.0 {
+                    let _ = write!(body, r#" opacity="{}""#, local.opacity);
+                }
+                body.push('>');
+                let inner = pre.local_frame(frame) + pre.comp.start_frame as f32;
+                write_layers(&pre.comp, inner, defs, body, next_id);
+                body.push_str("</g>");
+            }
+            Layer::Image(_) | Layer::ImageSequence(_) | Layer::Text(_) | Layer::Null(_) => {}
+        }
+    }
+}
+
+/// Write `shape` in its own layer space, placed by a `transform` attribute
+/// so stroke widths, dashes and gradients scale along with the paths.
+fn write_shape(
+    shape: &ShapeLayer,
+    matrix: Affine,
+    opacity: f32,
+    frame: f32,
+    defs: &mut String,
+    body: &mut String,
+    next_id: &mut usize,
+) {
+    // Track matte sources are not visible content on their own.
+    if shape.is_mask || opacity <= 0.0 {
+        return;
+    }
+    // Mask content in user space resolves under the referencing element's
+    // transform, so masks stay in layer space too.
+    let mut mask = String::new();
+    if let Some(mask_paths) = shape.mask.as_ref().filter(|_| shape.has_mask) {
+        let id = format!("mask{}", *next_id);
+        *next_id += 1;
+        let (lo, span) = (-MASK_EXTENT, 2.0 * MASK_EXTENT);
+        let _ = write!(
+            defs,
+            r#"<mask id="{id}" maskUnits="userSpaceOnUse" x="{lo}" y="{lo}" width="{span}" height="{span}">"#
+        );
+        // Painted in order like the raster coverage: white adds, black
+        // takes away, and a subtracting or intersecting first mask starts
+        // from full coverage.
+        let everywhere = format!("M{lo} {lo} H{MASK_EXTENT} V{MASK_EXTENT} H{lo} Z");
+        let mode = |i: usize| shape.mask_modes.get(i).copied().unwrap_or_default();
+        let first = (0..mask_paths.len())
+            .map(mode)
+            .find(|m| *m != MaskMode::None);
+        if matches!(first, Some(MaskMode::Subtract | MaskMode::Intersect)) {
+            let _ = write!(defs, r##"<path d="{everywhere}" fill="#fff"/>"##);
+        }
+        for (i, path) in mask_paths.iter().enumerate() {
+            let (color, complement) = match mode(i) {
+                MaskMode::None => continue,
+                MaskMode::Add => ("#fff", false),
+                MaskMode::Subtract => ("#000", false),
+                MaskMode::Intersect => ("#000", true),
+            };
+            let path = match shape.mask_expansion.get(i).filter(|x| **x != 0.0) {
+                Some(&x) => path.offset(x),
+                None => path.clone(),
+            };
+            let inverted = shape.mask_inverted.get(i).copied().unwrap_or(false);
+            let opacity = shape.mask_opacity.get(i).map_or(1.0, |o| o.clamp(0.0, 1.0));
+            let _ = if inverted != complement {
+                write!(
+                    defs,
+                    r#"<path d="{everywhere} {}" fill="{color}" fill-rule="evenodd""#,
+                    path_data(&path)
+                )
+            } else {
+                write!(defs, r#"<path d="{}" fill="{color}""#, path_data(&path))
+            };
+            if opacity < 1.0 {
+                let _ = write!(defs, r#" fill-opacity="{opacity}""#);
+            }
+            defs.push_str("/>");
+        }
+        defs.push_str("</mask>");
+        mask = format!(r#" mask="url(#{id})""#);
+    }
+
+    let fill_paint = shape.effective_fill_at(frame);
+    let mut fill = paint_attrs(
+        "fill",
+        fill_paint.as_ref().unwrap_or(&Paint::None),
+        defs,
+        next_id,
+    );
+    if fill_paint.is_some() && shape.fill_rule == FillRule::EvenOdd {
+        fill.push_str(r#" fill-rule="evenodd""#);
+    }
+    let style = shape.stroke_style;
+    let cap = match style.cap {
+        LineCap::Butt => "",
+        LineCap::Round => r#" stroke-linecap="round""#,
+        LineCap::Square => r#" stroke-linecap="square""#,
+    };
+    let join = match style.join {
+        LineJoin::Miter if style.miter_limit != StrokeStyle::default().miter_limit => {
+            format!(r#" stroke-miterlimit="{}""#, style.miter_limit)
+        }
+        LineJoin::Miter => String::new(),
+        LineJoin::Round => r#" stroke-linejoin="round""#.to_string(),
+        LineJoin::Bevel => r#" stroke-linejoin="bevel""#.to_string(),
+    };
+    let mut dash = String::new();
+    if let Some((pattern, offset)) = shape.dash_at(frame) {
+        let list: Vec<String> = pattern.iter().map(f32::to_string).collect();
+        let _ = write!(dash, r#" stroke-dasharray="{}""#, list.join(" "));
+        if offset != 0.0 {
+            let _ = write!(dash, r#" stroke-dashoffset="{offset}""#);
+        }
+    }
+    let stroke = match shape.stroke {
+        Some(c) => format!(
+            r#"{} stroke-width="{}"{cap}{join}{dash}"#,
+            paint_attrs("stroke", &Paint::Solid(c), defs, next_id),
+            shape.stroke_width_at(frame)
+        ),
+        None => String::new(),
+    };
+    // Sub-paths share one element so holes follow the fill rule.
+    let path_list = |trim: Option<(f32, f32)>| {
+        let mut d = String::new();
+        for path in &shape.paths {
+            let part = match trim {
+                Some((s, e)) => path_data(&path.trim(s, e, 0.2)),
+                None => path_data(path),
+            };
+            if !d.is_empty() && !part.is_empty() {
+                d.push(' ');
+            }
+            d.push_str(&part);
+        }
+        d
+    };
+    let fill_trim = shape.trim.filter(|_| shape.trim_target.trims_fill());
+    let stroke_trim = shape.trim.filter(|_| shape.trim_target.trims_stroke());
+    let blend = match shape.blend_mode {
+        BlendMode::Normal => "",
+        BlendMode::Multiply => r#" style="mix-blend-mode:multiply""#,
+        BlendMode::Screen => r#" style="mix-blend-mode:screen""#,
+        BlendMode::Overlay => r#" style="mix-blend-mode:overlay""#,
+        BlendMode::Darken => r#" style="mix-blend-mode:darken""#,
+        BlendMode::Lighten => r#" style="mix-blend-mode:lighten""#,
+        BlendMode::Add => r#" style="mix-blend-mode:plus-lighter""#,
+    };
+    let mut layer = transform_attr(matrix);
+    if opacity < 1.0 {
+        let _ = write!(layer, r#" opacity="{opacity}""#);
+    }
+    if fill_trim != stroke_trim && fill_paint.is_some() && !stroke.is_empty() {
+        // Only one paint is trimmed; both sit in one group so they fade
+        // and blend together.
+        let (fill_d, stroke_d) = (path_list(fill_trim), path_list(stroke_trim));
+        let _ = write!(body, r#"<g{layer}{mask}{blend}>"#);
+        if !fill_d.is_empty() {
+            let _ = write!(body, r#"<path d="{fill_d}"{fill}/>"#);
+        }
+        if !stroke_d.is_empty() {
+            let _ = write!(body, r#"<path d="{stroke_d}" fill="none"{stroke}/>"#);
+        }
+        body.push_str("</g>");
+    } else {
+        let d = path_list(if fill_paint.is_some() {
+            fill_trim
+        } else {
+            stroke_trim
+        });
+        if !d.is_empty() {
+            let _ = write!(body, r#"<path d="{d}"{layer}{fill}{stroke}{mask}{blend}/>"#);
+        }
+    }
+    // Blend groups follow as their own elements, so only they blend.
+    for group in &shape.blend_groups {
+        write_shape(group, matrix, opacity, frame, defs, body, next_id);
+    }
+}
+
+/// ` transform="matrix(...)"` attribute placing content by `matrix`.
+fn transform_attr(matrix: Affine) -> String {
+    format!(
+        r#" transform="matrix({} {} {} {} {} {})""#,
+        matrix.a, matrix.b, matrix.c, matrix.d, matrix.e, matrix.f
+    )
+}
+
+/// Build the paint attributes for `kind` (`fill` or `stroke`), adding any
+/// gradient definition to `defs`.
+fn paint_attrs(kind: &str, paint: &Paint, defs: &mut String, next_id: &mut usize) -> String {
+    match paint {
+        Paint::None => format!(r#" {kind}="none""#),
+        Paint::Solid(c) => format!(
+            r#" {kind}="{}" {kind}-opacity="{}""#,
+            rgb(*c),
+            c.a as f32 / 255.0
+        ),
+        Paint::Linear(g) => {
+            let id = format!("grad{}", *next_id);
+            *next_id += 1;
+            let _ = write!(
+                defs,
+                r#"<linearGradient id="{id}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">"#,
+                g.start.x, g.start.y, g.end.x, g.end.y
+            );
+            write_stops(&g.stops, &g.opacity_stops, g.space, defs);
+            defs.push_str("</linearGradient>");
+            format!(r#" {kind}="url(#{id})""#)
+        }
+        Paint::Radial(g) => {
+            let id = format!("grad{}", *next_id);
+            *next_id += 1;
+            let focus = g
+                .focus
+                .map(|f| format!(r#" fx="{}" fy="{}""#, f.x, f.y))
+                .unwrap_or_default();
+            let _ = write!(
+                defs,
+                r#"<radialGradient id="{id}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}"{focus}>"#,
+                g.center.x, g.center.y, g.radius
+            );
+            write_stops(&g.stops, &g.opacity_stops, g.space, defs);
+            defs.push_str("</radialGradient>");
+            format!(r#" {kind}="url(#{id})""#)
+        }
+    }
+}
+
+/// Emit `<stop>` elements at every color and opacity stop offset, so the
+/// opacity ramp survives even where it has stops of its own.
+fn write_stops(
+    stops: &[GradientStop],
+    opacity: &[OpacityStop],
+    space: ColorSpace,
+    defs: &mut String,
+) {
+    let mut offsets: Vec<f32> = stops
+        .iter()
+        .map(|s| s.offset)
+        .chain(opacity.iter().map(|o| o.offset))
+        .collect();
+    offsets.sort_by(f32::total_cmp);
+    offsets.dedup();
+    for offset in offsets {
+        let color = sample_stops(stops, offset, space);
+        let alpha = color.a as f32 / 255.0 * sample_opacity(opacity, offset);
+        let _ = write!(
+            defs,
+            r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+            offset,
+            rgb(color),
+            alpha
+        );
+    }
+}
+
+fn rgb(c: Color) -> String {
+    format!("rgb({},{},{})", c.r, c.g, c.b)
+}
+
+/// Serialize a [`Path`] into SVG path data.
+fn path_data(path: &Path) -> String {
+    let mut d = String::new();
+    for seg in &path.segments {
+        let has_point = !d.is_empty();
+        if has_point {
+            d.push(' ');
+        }
+        let _ = match *seg {
+            PathSeg::MoveTo(p) => write!(d, "M{} {}", p.x, p.y),
+            PathSeg::LineTo(p) => write!(d, "L{} {}", p.x, p.y),
+            PathSeg::Cubic(c1, c2, p) => {
+                write!(d, "C{} {} {} {} {} {}", c1.x, c1.y, c2.x, c2.y, p.x, p.y)
+            }
+            PathSeg::Arc {
+                center,
+                radii,
+                start,
+                sweep,
+            } => {
+                let at = |deg: f32| Vec2 {
+                    x: center.x + radii.x * deg.to_radians().cos(),
+                    y: center.y + radii.y * deg.to_radians().sin(),
+                };
+                // An arc whose ends meet is a no-op in SVG, so full turns
+                // are written as two halves.
+                let sweep = sweep.clamp(-360.0, 360.0);
+                let parts: &[(f32, f32)] = if sweep.abs() >= 360.0 {
+                    &[(start, sweep * 0.5), (start + sweep * 0.5, sweep * 0.5)]
+                } else {
+                    &[(start, sweep)]
+                };
+                // Without a current point the arc has to start a subpath.
+                let from = at(start);
+                let to_start = if has_point { "L" } else { "M" };
+                let _ = write!(d, "{to_start}{} {}", from.x, from.y);
+                for &(start, sweep) in parts {
+                    let to = at(start + sweep);
+                    let _ = write!(
+                        d,
+                        " A{} {} 0 {} {} {} {}",
+                        radii.x,
+                        radii.y,
+                        u8::from(sweep.abs() > 180.0),
+                        u8::from(sweep > 0.0),
+                        to.x,
+                        to.y
+                    );
+                }
+                Ok(())
+            }
+            PathSeg::Close => write!(d, "Z"),
+        };
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::json;
+
+    #[test]
+    fn export_fill_stroke() {
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+        let data = std::fs::read(path).unwrap();
+        let comp = json::from_slice(&data).unwrap();
+        let svg = frame_to_svg(&comp, 0);
+
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.tag_name().name(), "svg");
+        assert_eq!(root.attribute("width"), Some("8"));
+        let path = root
+            .descendants()
+            .find(|n| n.has_tag_name("path"))
+            .expect("path element");
+        assert_eq!(path.attribute("fill"), Some("rgb(0,0,255)"));
+        assert_eq!(path.attribute("stroke"), Some("rgb(255,0,0)"));
+        assert!(path.attribute("d").unwrap().starts_with("M1 1"));
+    }
+
+    #[test]
+    fn layers_are_sampled_at_their_local_frame() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/data/layer_start_time.json");
+        let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        // Both halves start at frame 5: nothing shows before that, and five
+        // frames later the shape and the precomp content are half faded in.
+        let opacities = |frame: u32| {
+            let svg = frame_to_svg(&comp, frame);
+            let doc = roxmltree::Document::parse(&svg).unwrap();
+            let paths = doc.descendants().filter(|n| n.has_tag_name("path")).count();
+            let opacities: Vec<f32> = doc
+                .descendants()
+                .filter_map(|n| n.attribute("opacity"))
+                .map(|o| o.parse().unwrap())
+                .collect();
+            (paths, opacities)
+        };
+        assert_eq!(opacities(3).0, 0);
+        let (paths, halves) = opacities(10);
+        assert_eq!((paths, halves.len()), (2, 2));
+        assert!(halves.iter().all(|o| (o - 0.5).abs() < 0.01), "{halves:?}");
+    }
+
+    #[test]
+    fn scaled_layer_keeps_stroke_in_layer_space() {
+        let json = br#"{"w":64,"h":64,"ip":0,"op":1,"fr":30,"layers":[{"ty":4,
+            "ks":{"s":{"k":[200,200]},"p":{"k":[4,0]},"o":{"k":50}},
+            "shapes":[{"ty":"rc","p":{"k":[8,8]},"s":{"k":[8,8]}},
+                {"ty":"st","c":{"k":[1,0,0,1]},"w":{"k":3}}]}]}"#;
+        let svg = frame_to_svg(&json::from_slice(json).unwrap(), 0);
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let path = doc
+            .descendants()
+            .find(|n| n.has_tag_name("path"))
+            .expect("path element");
+        assert_eq!(path.attribute("transform"), Some("matrix(2 0 0 2 4 0)"));
+        assert_eq!(path.attribute("stroke-width"), Some("3"));
+        assert_eq!(path.attribute("opacity"), Some("0.5"));
+        assert!(path.attribute("d").unwrap().starts_with("M4 4"));
+    }
+
+    #[test]
+    fn full_circle_arc_is_split() {
+        let mut path = Path::new();
+        path.arc(Vec2 { x: 5.0, y: 5.0 }, Vec2 { x: 2.0, y: 2.0 }, 0.0, 360.0);
+        let d = path_data(&path);
+        // Two half turns, through the opposite point and back.
+        assert_eq!(d.matches(" A").count(), 2, "{d}");
+        assert!(d.starts_with("M7 5 A2 2 0 0 1 3 5 A2 2 0 0 1 7 "), "{d}");
+    }
+
+    #[test]
+    fn subtract_mask_exports_as_luminance_mask() {
+        let json = br#"{"fr":30,"ip":0,"op":1,"w":8,"h":8,"layers":[{"ty":4,"hasMask":true,
+            "masksProperties":[{"mode":"s","inv":true,"pt":{"k":{"d":"m 2 2 l 6 2 l 6 6 o"}},"o":{"k":50}}],
+            "shapes":[{"ty":"rc","p":{"k":[4,4]},"s":{"k":[8,8]}},{"ty":"fl","c":{"k":[1,0,0,1]}}]}]}"#;
+        let svg = frame_to_svg(&json::from_slice(json).unwrap(), 0);
+        let doc = roxmltree::Document::parse(&svg).unwrap();
+        let mask = doc
+            .descendants()
+            .find(|n| n.has_tag_name("mask"))
+            .expect("mask element");
+        let paths: Vec<_> = mask.children().filter(|n| n.has_tag_name("path")).collect();
+        // Full coverage first, then the inverted mask takes half of it away
+        // outside the triangle.
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].attribute("fill"), Some("#fff"));
+        assert_eq!(paths[1].attribute("fill"), Some("#000"));
+        assert_eq!(paths[1].attribute("fill-rule"), Some("evenodd"));
+        assert_eq!(paths[1].attribute("fill-opacity"), Some("0.5"));
+        let id = mask.attribute("id").unwrap();
+        let masked = doc
+            .descendants()
+            .find(|n| n.attribute("mask").is_some())
+            .expect("masked element");
+        assert_eq!(
+            masked.attribute("mask"),
+            Some(format!("url(#{id})").as_str())
+        );
+    }
+}
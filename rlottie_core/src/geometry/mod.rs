@@ -4,7 +4,11 @@
 //! Mirrors: rlottie/src/vector/vpath.h
 
 mod path;
+mod stroke;
 mod tess;
 
-pub use path::{LineSegment, Path, PathSeg};
-pub use tess::{tessellate, Mesh};
+pub use path::{winding_number, LineSegment, Path, PathSeg};
+pub(crate) use stroke::DEFAULT_MITER_LIMIT;
+pub use stroke::{offset_path, stroke_joins, stroke_to_path};
+pub(crate) use stroke::{polyline_joins, polyline_outline, polyline_segments, polylines, Polyline};
+pub use tess::{tessellate, tessellate_debug, Mesh};
@@ -22,6 +22,33 @@ impl LineSegment {
         let dy = self.to.y - self.from.y;
         (dx * dx + dy * dy).sqrt()
     }
+
+    /// Where the segment crosses the horizontal line at `y`, with `1` for a
+    /// downward and `-1` for an upward edge. The lower end point is
+    /// included and the upper excluded, so shared vertices count once.
+    pub fn crossing(&self, y: f32) -> Option<(f32, i32)> {
+        let (a, b) = (self.from, self.to);
+        let dir = if a.y <= y && b.y > y {
+            1
+        } else if b.y <= y && a.y > y {
+            -1
+        } else {
+            return None;
+        };
+        Some((a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y), dir))
+    }
+}
+
+/// Winding number of `path` around `p`: the summed directions of the edges
+/// crossed by a ray from `p` to the left. Sub-paths are implicitly closed
+/// and curves are flattened with `tolerance`.
+pub fn winding_number(path: &Path, p: Vec2, tolerance: f32) -> i32 {
+    path.closed_edges(tolerance)
+        .iter()
+        .filter_map(|e| e.crossing(p.y))
+        .filter(|&(x, _)| x < p.x)
+        .map(|(_, dir)| dir)
+        .sum()
 }
 
 /// Basic path drawing commands.
@@ -93,6 +120,101 @@ impl Path {
         self.segments.push(PathSeg::Close);
     }
 
+    /// Whether the path has nothing to draw: no segments beyond moves and
+    /// closes, as left by a collapsed trim or an empty dash.
+    pub fn is_empty(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|seg| matches!(seg, PathSeg::MoveTo(_) | PathSeg::Close))
+    }
+
+    /// Split the path into independent sub-paths, each starting at a move command.
+    pub fn subpaths(&self) -> Vec<Path> {
+        let mut out: Vec<Path> = Vec::new();
+        for seg in &self.segments {
+            if matches!(seg, PathSeg::MoveTo(_)) || out.is_empty() {
+                out.push(Path::new());
+            }
+            if let Some(last) = out.last_mut() {
+                last.segments.push(seg.clone());
+            }
+        }
+        out
+    }
+
+    /// Flattened edges of every sub-path, adding the closing edge of open
+    /// sub-paths as a fill would.
+    pub fn closed_edges(&self, tolerance: f32) -> Vec<LineSegment> {
+        let mut edges = Vec::new();
+        for sub in self.subpaths() {
+            let segs = sub.flatten(tolerance);
+            if let (Some(first), Some(last)) = (segs.first(), segs.last()) {
+                if last.to != first.from {
+                    edges.push(LineSegment {
+                        from: last.to,
+                        to: first.from,
+                    });
+                }
+            }
+            edges.extend(segs);
+        }
+        edges
+    }
+
+    /// Copy of the path with every point passed through `map`.
+    ///
+    /// Arcs keep their angles; their center is mapped and each radius is
+    /// measured along the mapped axis, which is exact for scale and
+    /// translation.
+    pub fn map_points(&self, map: impl Fn(Vec2) -> Vec2) -> Path {
+        let segments = self
+            .segments
+            .iter()
+            .map(|seg| match *seg {
+                PathSeg::MoveTo(p) => PathSeg::MoveTo(map(p)),
+                PathSeg::LineTo(p) => PathSeg::LineTo(map(p)),
+                PathSeg::Cubic(c1, c2, p) => PathSeg::Cubic(map(c1), map(c2), map(p)),
+                PathSeg::Arc {
+                    center,
+                    radii,
+                    start,
+                    sweep,
+                } => {
+                    let c = map(center);
+                    let axis = |d: Vec2| {
+                        let q = map(Vec2 {
+                            x: center.x + d.x,
+                            y: center.y + d.y,
+                        });
+                        ((q.x - c.x).powi(2) + (q.y - c.y).powi(2)).sqrt()
+                    };
+                    PathSeg::Arc {
+                        center: c,
+                        radii: Vec2 {
+                            x: axis(Vec2 { x: radii.x, y: 0.0 }),
+                            y: axis(Vec2 { x: 0.0, y: radii.y }),
+                        },
+                        start,
+                        sweep,
+                    }
+                }
+                PathSeg::Close => PathSeg::Close,
+            })
+            .collect();
+        Path { segments }
+    }
+
+    /// Winding number around `p`. See [`winding_number`].
+    pub fn winding_at(&self, p: Vec2, tolerance: f32) -> i32 {
+        winding_number(self, p, tolerance)
+    }
+
+    /// Outset (positive) or inset (negative) every contour by `distance`.
+    /// See [`super::offset_path`].
+    pub fn offset(&self, distance: f32) -> Path {
+        super::offset_path(self, distance)
+    }
+
     /// Add a rounded rectangle path using uniform corner radius.
     pub fn add_round_rect(&mut self, x: f32, y: f32, w: f32, h: f32, radius: f32) {
         if w <= 0.0 || h <= 0.0 {
@@ -158,10 +280,7 @@ impl Path {
 
     /// Approximate path length by summing flattened segment lengths.
     pub fn length(&self, tolerance: f32) -> f32 {
-        self.flatten(tolerance)
-            .iter()
-            .map(LineSegment::length)
-            .sum()
+        self.flatten_iter(tolerance).map(|seg| seg.length()).sum()
     }
 
     /// Return a new path trimmed between `start` and `end` fractions.
@@ -192,29 +311,173 @@ impl Path {
         }
     }
 
+    /// Return the "on" pieces of a dash `pattern` of alternating dash and gap
+    /// lengths walked along the path, starting `offset` into the pattern.
+    ///
+    /// The walk carries on across segment boundaries by arc length and
+    /// restarts at each sub-path. An odd-length pattern is repeated to make
+    /// it even; one with negative, non-finite or only zero lengths leaves
+    /// the path undashed.
+    pub fn dash(&self, pattern: &[f32], offset: f32, tolerance: f32) -> Self {
+        let pattern: SmallVec<[f32; 8]> = if pattern.len() % 2 == 1 {
+            pattern.iter().chain(pattern).copied().collect()
+        } else {
+            pattern.iter().copied().collect()
+        };
+        let cycle: f32 = pattern.iter().sum();
+        if pattern.iter().any(|d| !d.is_finite() || *d < 0.0) || cycle <= 0.0 {
+            return self.clone();
+        }
+        let mut phase = if offset.is_finite() {
+            offset.rem_euclid(cycle)
+        } else {
+            0.0
+        };
+        let mut first = 0;
+        while phase >= pattern[first] {
+            phase -= pattern[first];
+            first = (first + 1) % pattern.len();
+        }
+
+        let mut out = Self::new();
+        for sub in self.subpaths() {
+            let mut idx = first;
+            let mut remaining = pattern[idx] - phase;
+            let mut pen_down = false;
+            for seg in sub.flatten_iter(tolerance) {
+                let len = seg.length();
+                let mut pos = 0.0;
+                while pos < len {
+                    let step = remaining.min(len - pos);
+                    if idx % 2 == 0 && step > 0.0 {
+                        if !pen_down {
+                            out.move_to(lerp(seg.from, seg.to, pos / len));
+                            pen_down = true;
+                        }
+                        out.line_to(lerp(seg.from, seg.to, (pos + step) / len));
+                    }
+                    pos += step;
+                    remaining -= step;
+                    if remaining <= 0.0 {
+                        idx = (idx + 1) % pattern.len();
+                        remaining = pattern[idx];
+                        pen_down = false;
+                    }
+                }
+            }
+        }
+        out
+    }
+
     /// Flatten the path into line segments using recursive subdivision of cubics.
     pub fn flatten(&self, tolerance: f32) -> SmallVec<[LineSegment; 32]> {
-        let mut result: SmallVec<[LineSegment; 32]> = SmallVec::new();
-        let mut start = Vec2::default();
-        let mut current = Vec2::default();
-        let mut has_start = false;
-        for seg in &self.segments {
-            match *seg {
+        self.flatten_iter(tolerance).collect()
+    }
+
+    /// Lazily yield the segments of [`Path::flatten`] in the same order,
+    /// subdividing each cubic or arc only as its segments are consumed.
+    pub fn flatten_iter(&self, tolerance: f32) -> impl Iterator<Item = LineSegment> + '_ {
+        Flatten {
+            segments: self.segments.iter(),
+            tolerance,
+            start: Vec2::default(),
+            current: Vec2::default(),
+            has_start: false,
+            cubics: SmallVec::new(),
+            arc: None,
+            pending: None,
+        }
+    }
+}
+
+/// Subdivision depth after which a cubic is emitted as a line regardless of
+/// flatness, bounding output to `2^MAX_FLATTEN_DEPTH` segments per curve.
+const MAX_FLATTEN_DEPTH: u32 = 16;
+
+/// A cubic still to be flattened: end points, control points and the
+/// subdivision depth left.
+type PendingCubic = (Vec2, Vec2, Vec2, Vec2, u32);
+
+/// Remaining steps of an arc being flattened.
+struct ArcSteps {
+    center: Vec2,
+    radii: Vec2,
+    angle: f32,
+    delta: f32,
+    remaining: usize,
+}
+
+/// Iterator behind [`Path::flatten_iter`].
+struct Flatten<'a> {
+    segments: std::slice::Iter<'a, PathSeg>,
+    tolerance: f32,
+    start: Vec2,
+    current: Vec2,
+    has_start: bool,
+    /// Depth-first subdivision stack; the next half to emit is on top. Each
+    /// split replaces one entry with two, so it never outgrows the depth.
+    cubics: SmallVec<[PendingCubic; MAX_FLATTEN_DEPTH as usize + 1]>,
+    arc: Option<ArcSteps>,
+    /// Arc step queued behind the segment joining it to the current point.
+    pending: Option<LineSegment>,
+}
+
+impl Iterator for Flatten<'_> {
+    type Item = LineSegment;
+
+    fn next(&mut self) -> Option<LineSegment> {
+        loop {
+            if let Some(seg) = self.pending.take() {
+                return Some(seg);
+            }
+            if let Some((p0, c1, c2, p3, depth)) = self.cubics.pop() {
+                if depth == 0 || cubic_flat_enough(p0, c1, c2, p3, self.tolerance) {
+                    return Some(LineSegment { from: p0, to: p3 });
+                }
+                let (p0a, c1a, c2a, p3a, p0b, c1b, c2b, p3b) = split_cubic(p0, c1, c2, p3);
+                self.cubics.push((p0b, c1b, c2b, p3b, depth - 1));
+                self.cubics.push((p0a, c1a, c2a, p3a, depth - 1));
+                continue;
+            }
+            if let Some(arc) = &mut self.arc {
+                if arc.remaining > 0 {
+                    let a0 = arc.angle;
+                    let a1 = a0 + arc.delta;
+                    let from = Vec2 {
+                        x: arc.center.x + arc.radii.x * a0.cos(),
+                        y: arc.center.y + arc.radii.y * a0.sin(),
+                    };
+                    let to = Vec2 {
+                        x: arc.center.x + arc.radii.x * a1.cos(),
+                        y: arc.center.y + arc.radii.y * a1.sin(),
+                    };
+                    arc.angle = a1;
+                    arc.remaining -= 1;
+                    let current = std::mem::replace(&mut self.current, to);
+                    if current != from {
+                        self.pending = Some(LineSegment { from, to });
+                        return Some(LineSegment {
+                            from: current,
+                            to: from,
+                        });
+                    }
+                    return Some(LineSegment { from, to });
+                }
+                self.arc = None;
+            }
+            match *self.segments.next()? {
                 PathSeg::MoveTo(p) => {
-                    current = p;
-                    start = p;
-                    has_start = true;
+                    self.current = p;
+                    self.start = p;
+                    self.has_start = true;
                 }
                 PathSeg::LineTo(p) => {
-                    result.push(LineSegment {
-                        from: current,
-                        to: p,
-                    });
-                    current = p;
+                    let from = std::mem::replace(&mut self.current, p);
+                    return Some(LineSegment { from, to: p });
                 }
                 PathSeg::Cubic(c1, c2, p) => {
-                    flatten_cubic(current, c1, c2, p, tolerance, &mut result);
-                    current = p;
+                    let p0 = std::mem::replace(&mut self.current, p);
+                    self.cubics.push((p0, c1, c2, p, MAX_FLATTEN_DEPTH));
                 }
                 PathSeg::Arc {
                     center,
@@ -222,63 +485,29 @@ impl Path {
                     start,
                     sweep,
                 } => {
-                    let start_rad = start.to_radians();
                     let sweep_rad = sweep.to_radians();
-                    let segs = ((sweep_rad.abs() * radii.x.max(radii.y)) / tolerance)
+                    let steps = ((sweep_rad.abs() * radii.x.max(radii.y)) / self.tolerance)
                         .ceil()
                         .max(1.0) as usize;
-                    let mut a0 = start_rad;
-                    let delta = sweep_rad / segs as f32;
-                    for _ in 0..segs {
-                        let a1 = a0 + delta;
-                        let from = Vec2 {
-                            x: center.x + radii.x * a0.cos(),
-                            y: center.y + radii.y * a0.sin(),
-                        };
-                        let to = Vec2 {
-                            x: center.x + radii.x * a1.cos(),
-                            y: center.y + radii.y * a1.sin(),
-                        };
-                        if current != from {
-                            result.push(LineSegment {
-                                from: current,
-                                to: from,
-                            });
-                        }
-                        result.push(LineSegment { from, to });
-                        current = to;
-                        a0 = a1;
-                    }
+                    self.arc = Some(ArcSteps {
+                        center,
+                        radii,
+                        angle: start.to_radians(),
+                        delta: sweep_rad / steps as f32,
+                        remaining: steps,
+                    });
                 }
                 PathSeg::Close => {
-                    if has_start && current != start {
-                        result.push(LineSegment {
-                            from: current,
-                            to: start,
+                    let from = std::mem::replace(&mut self.current, self.start);
+                    if self.has_start && from != self.start {
+                        return Some(LineSegment {
+                            from,
+                            to: self.start,
                         });
                     }
-                    current = start;
                 }
             }
         }
-        result
-    }
-}
-
-fn flatten_cubic(
-    p0: Vec2,
-    c1: Vec2,
-    c2: Vec2,
-    p3: Vec2,
-    tolerance: f32,
-    out: &mut SmallVec<[LineSegment; 32]>,
-) {
-    if cubic_flat_enough(p0, c1, c2, p3, tolerance) {
-        out.push(LineSegment { from: p0, to: p3 });
-    } else {
-        let (p0a, c1a, c2a, p3a, p0b, c1b, c2b, p3b) = split_cubic(p0, c1, c2, p3);
-        flatten_cubic(p0a, c1a, c2a, p3a, tolerance, out);
-        flatten_cubic(p0b, c1b, c2b, p3b, tolerance, out);
     }
 }
 
@@ -373,6 +602,21 @@ fn extract_range(segs: &[LineSegment], from: f32, to: f32) -> Path {
 mod tests {
     use super::*;
 
+    #[test]
+    fn double_loop_winds_twice() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 2.0, y: 2.0 });
+        for _ in 0..2 {
+            path.line_to(Vec2 { x: 8.0, y: 2.0 });
+            path.line_to(Vec2 { x: 8.0, y: 8.0 });
+            path.line_to(Vec2 { x: 2.0, y: 8.0 });
+            path.line_to(Vec2 { x: 2.0, y: 2.0 });
+        }
+        path.close();
+        assert_eq!(path.winding_at(Vec2 { x: 5.0, y: 5.0 }, 0.2).abs(), 2);
+        assert_eq!(path.winding_at(Vec2 { x: 9.0, y: 5.0 }, 0.2), 0);
+    }
+
     #[test]
     fn path_build_and_flatten() {
         let mut path = Path::new();
@@ -401,6 +645,17 @@ mod tests {
         assert!((segs[0].to.x - 5.0).abs() < 1e-5);
     }
 
+    #[test]
+    fn collapsed_trim_is_empty() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 0.0, y: 0.0 });
+        assert!(path.is_empty());
+        path.line_to(Vec2 { x: 10.0, y: 0.0 });
+        path.close();
+        assert!(!path.is_empty());
+        assert!(path.trim(0.3, 0.3, 0.01).is_empty());
+    }
+
     #[test]
     fn path_trim_loop() {
         let mut path = Path::new();
@@ -422,4 +677,51 @@ mod tests {
         let first = segs.first().unwrap();
         assert!((first.from.x - 8.0).abs() < 1e-5);
     }
+
+    #[test]
+    fn flatten_degenerate_cubic_terminates() {
+        // Closed near-cusp loop: start and end coincide, so the flatness
+        // test divides by zero and never succeeds on its own.
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 0.0, y: 0.0 });
+        path.cubic_to(
+            Vec2 { x: 100.0, y: 100.0 },
+            Vec2 {
+                x: -100.0,
+                y: 100.0,
+            },
+            Vec2 { x: 0.0, y: 0.0 },
+        );
+        let segs = path.flatten(1e-9);
+        assert!(!segs.is_empty());
+        assert!(segs.len() <= 1 << MAX_FLATTEN_DEPTH);
+        assert_eq!(segs.last().unwrap().to, Vec2 { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn flatten_iter_matches_flatten() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 0.0, y: 0.0 });
+        path.line_to(Vec2 { x: 10.0, y: 0.0 });
+        path.cubic_to(
+            Vec2 { x: 20.0, y: 0.0 },
+            Vec2 { x: 20.0, y: 10.0 },
+            Vec2 { x: 10.0, y: 10.0 },
+        );
+        path.arc(
+            Vec2 { x: 5.0, y: 10.0 },
+            Vec2 { x: 5.0, y: 5.0 },
+            0.0,
+            180.0,
+        );
+        path.close();
+
+        let lazy: Vec<LineSegment> = path.flatten_iter(0.1).collect();
+        assert_eq!(lazy.as_slice(), path.flatten(0.1).as_slice());
+        assert!(lazy.len() > 4);
+        for pair in lazy.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+        }
+        assert_eq!(lazy.last().unwrap().to, Vec2 { x: 0.0, y: 0.0 });
+    }
 }
@@ -0,0 +1,396 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: stroke outline generation
+//! Mirrors: rlottie/src/vector/freetype/v_ft_stroker.cpp (simplified)
+
+use super::{Path, PathSeg};
+use crate::types::{LineCap, LineJoin, StrokeStyle, Vec2};
+
+/// Flattening tolerance used when walking the stroked path.
+const STROKE_TOLERANCE: f32 = 0.2;
+/// Miter length limit relative to the stroke width.
+pub(crate) const DEFAULT_MITER_LIMIT: f32 = 4.0;
+
+/// Convert the stroked outline of `path` into a fillable [`Path`].
+///
+/// The outline is emitted as a set of convex, consistently wound pieces
+/// (segment bodies, joins and caps) so it can be filled with the regular
+/// path fill pipeline.
+pub fn stroke_to_path(path: &Path, width: f32, style: StrokeStyle) -> Path {
+    polyline_outline(&polylines(path), width, style)
+}
+
+/// Only the join pieces of [`stroke_to_path`]: the wedges filling the outer
+/// side of every corner, for renderers that draw segment bodies themselves.
+pub fn stroke_joins(path: &Path, width: f32, style: StrokeStyle) -> Path {
+    polyline_joins(&polylines(path), width, style)
+}
+
+/// One flattened sub-path: its distinct points and whether it is closed.
+/// A closed sub-path does not repeat its first point at the end.
+pub(crate) type Polyline = (Vec<Vec2>, bool);
+
+/// Flatten every sub-path of `path` once, so the outline, the joins and
+/// any segment bodies drawn separately all walk the same points.
+pub(crate) fn polylines(path: &Path) -> Vec<Polyline> {
+    path.subpaths().iter().map(polyline).collect()
+}
+
+/// Consecutive point pairs of `lines`, including the closing segment of
+/// closed sub-paths.
+pub(crate) fn polyline_segments(lines: &[Polyline]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    lines.iter().flat_map(|(pts, closed)| {
+        let n = pts.len();
+        let count = match n {
+            0 | 1 => 0,
+            _ if *closed => n,
+            _ => n - 1,
+        };
+        (0..count).map(move |i| (pts[i], pts[(i + 1) % n]))
+    })
+}
+
+/// [`stroke_to_path`] over already flattened sub-paths.
+pub(crate) fn polyline_outline(lines: &[Polyline], width: f32, style: StrokeStyle) -> Path {
+    let mut out = Path::new();
+    let hw = width * 0.5;
+    if hw <= 0.0 || !hw.is_finite() {
+        return out;
+    }
+    for line in lines {
+        let (pts, closed) = line;
+        if pts.len() < 2 {
+            continue;
+        }
+        let n = pts.len();
+        for (a, b) in polyline_segments(std::slice::from_ref(line)) {
+            let nrm = normal(a, b, hw);
+            push_polygon(
+                &mut out,
+                &[sub_v(a, nrm), sub_v(b, nrm), add_v(b, nrm), add_v(a, nrm)],
+            );
+        }
+        let join_range = if *closed { 0..n } else { 1..n - 1 };
+        for i in join_range {
+            let prev = pts[(i + n - 1) % n];
+            let cur = pts[i];
+            let next = pts[(i + 1) % n];
+            add_join(&mut out, prev, cur, next, hw, style);
+        }
+        if !closed {
+            add_cap(&mut out, pts[1], pts[0], hw, style.cap);
+            add_cap(&mut out, pts[n - 2], pts[n - 1], hw, style.cap);
+        }
+    }
+    out
+}
+
+/// [`stroke_joins`] over already flattened sub-paths.
+pub(crate) fn polyline_joins(lines: &[Polyline], width: f32, style: StrokeStyle) -> Path {
+    let mut out = Path::new();
+    let hw = width * 0.5;
+    if hw <= 0.0 || !hw.is_finite() {
+        return out;
+    }
+    for (pts, closed) in lines {
+        let n = pts.len();
+        if n < 3 {
+            continue;
+        }
+        let join_range = if *closed { 0..n } else { 1..n - 1 };
+        for i in join_range {
+            let prev = pts[(i + n - 1) % n];
+            let next = pts[(i + 1) % n];
+            add_join(&mut out, prev, pts[i], next, hw, style);
+        }
+    }
+    out
+}
+
+/// Flatten one sub-path into a [`Polyline`].
+fn polyline(sub: &Path) -> Polyline {
+    let closed = sub.segments.iter().any(|s| matches!(s, PathSeg::Close));
+    let mut pts: Vec<Vec2> = Vec::new();
+    for seg in sub.flatten(STROKE_TOLERANCE) {
+        if pts.is_empty() {
+            pts.push(seg.from);
+        }
+        if pts.last() != Some(&seg.to) {
+            pts.push(seg.to);
+        }
+    }
+    if closed && pts.len() > 2 && pts.first() == pts.last() {
+        pts.pop();
+    }
+    (pts, closed)
+}
+
+/// Grow (`distance > 0`) or shrink (`distance < 0`) every contour of `path`
+/// by moving each edge along its outward normal. Contours are treated as
+/// closed and corners are mitered, bounded by the default miter limit.
+pub fn offset_path(path: &Path, distance: f32) -> Path {
+    if distance == 0.0 || !distance.is_finite() {
+        return path.clone();
+    }
+    let mut out = Path::new();
+    for sub in path.subpaths() {
+        let (mut pts, _) = polyline(&sub);
+        if pts.len() > 2 && pts.first() == pts.last() {
+            pts.pop();
+        }
+        let n = pts.len();
+        if n < 3 {
+            continue;
+        }
+        let area = signed_area(&pts);
+        if area == 0.0 {
+            continue;
+        }
+        // The left-hand normal points inside for positive area.
+        let outward = if area > 0.0 { -1.0 } else { 1.0 };
+        let limit = DEFAULT_MITER_LIMIT * distance.abs();
+        let moved: Vec<Vec2> = (0..n)
+            .map(|i| {
+                let prev = pts[(i + n - 1) % n];
+                let cur = pts[i];
+                let next = pts[(i + 1) % n];
+                let n1 = normal(prev, cur, outward);
+                let n2 = normal(cur, next, outward);
+                let denom = 1.0 + n1.x * n2.x + n1.y * n2.y;
+                let mut shift = if denom > f32::EPSILON {
+                    scale_v(add_v(n1, n2), distance / denom)
+                } else {
+                    scale_v(n2, distance)
+                };
+                let len = (shift.x * shift.x + shift.y * shift.y).sqrt();
+                if len > limit {
+                    shift = scale_v(shift, limit / len);
+                }
+                add_v(cur, shift)
+            })
+            .collect();
+        // An inset past the middle turns the contour inside out, flipping
+        // its area or, when it overshoots both ways, every edge; it has
+        // nothing left to cover.
+        let reversed = (0..n).all(|i| {
+            let (j, k) = (i, (i + 1) % n);
+            let was = sub_v(pts[k], pts[j]);
+            let now = sub_v(moved[k], moved[j]);
+            was.x * now.x + was.y * now.y < 0.0
+        });
+        if reversed || signed_area(&moved) * area <= 0.0 {
+            continue;
+        }
+        out.move_to(moved[0]);
+        for &p in &moved[1..] {
+            out.line_to(p);
+        }
+        out.close();
+    }
+    out
+}
+
+/// Twice the signed area of the closed polygon through `pts`.
+fn signed_area(pts: &[Vec2]) -> f32 {
+    let n = pts.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (pts[i], pts[(i + 1) % n]);
+            p.x * q.y - q.x * p.y
+        })
+        .sum()
+}
+
+fn add_v(a: Vec2, b: Vec2) -> Vec2 {
+    Vec2 {
+        x: a.x + b.x,
+        y: a.y + b.y,
+    }
+}
+
+fn sub_v(a: Vec2, b: Vec2) -> Vec2 {
+    Vec2 {
+        x: a.x - b.x,
+        y: a.y - b.y,
+    }
+}
+
+fn scale_v(a: Vec2, s: f32) -> Vec2 {
+    Vec2 {
+        x: a.x * s,
+        y: a.y * s,
+    }
+}
+
+/// Left-hand normal of the segment `a -> b` scaled to `len`.
+fn normal(a: Vec2, b: Vec2, len: f32) -> Vec2 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let l = (dx * dx + dy * dy).sqrt();
+    if l == 0.0 {
+        return Vec2::default();
+    }
+    Vec2 {
+        x: -dy / l * len,
+        y: dx / l * len,
+    }
+}
+
+/// Append a closed polygon, reversing it if needed so every piece shares
+/// the same (positive) winding.
+fn push_polygon(out: &mut Path, pts: &[Vec2]) {
+    let mut area = 0.0;
+    for i in 0..pts.len() {
+        let p = pts[i];
+        let q = pts[(i + 1) % pts.len()];
+        area += p.x * q.y - q.x * p.y;
+    }
+    if area == 0.0 {
+        return;
+    }
+    let ordered: Vec<Vec2> = if area > 0.0 {
+        pts.to_vec()
+    } else {
+        pts.iter().rev().copied().collect()
+    };
+    out.move_to(ordered[0]);
+    for p in &ordered[1..] {
+        out.line_to(*p);
+    }
+    out.close();
+}
+
+/// Append a pie slice centered at `center` sweeping counter-clockwise.
+fn push_sector(out: &mut Path, center: Vec2, radius: f32, start: f32, sweep: f32) {
+    out.move_to(center);
+    out.arc(
+        center,
+        Vec2 {
+            x: radius,
+            y: radius,
+        },
+        start.to_degrees(),
+        sweep.to_degrees(),
+    );
+    out.close();
+}
+
+fn add_join(out: &mut Path, prev: Vec2, cur: Vec2, next: Vec2, hw: f32, style: StrokeStyle) {
+    let d0 = sub_v(cur, prev);
+    let d1 = sub_v(next, cur);
+    let cross = d0.x * d1.y - d0.y * d1.x;
+    let len0 = (d0.x * d0.x + d0.y * d0.y).sqrt();
+    let len1 = (d1.x * d1.x + d1.y * d1.y).sqrt();
+    if cross.abs() <= 1e-6 * len0 * len1 {
+        return;
+    }
+    // The outer side of the corner is opposite to the turn direction.
+    let side = if cross > 0.0 { -1.0 } else { 1.0 };
+    let n0 = scale_v(normal(prev, cur, hw), side);
+    let n1 = scale_v(normal(cur, next, hw), side);
+    match style.join {
+        LineJoin::Bevel => push_polygon(out, &[cur, add_v(cur, n0), add_v(cur, n1)]),
+        LineJoin::Round => {
+            let a0 = n0.y.atan2(n0.x);
+            let a1 = n1.y.atan2(n1.x);
+            let mut delta = a1 - a0;
+            while delta > std::f32::consts::PI {
+                delta -= std::f32::consts::TAU;
+            }
+            while delta < -std::f32::consts::PI {
+                delta += std::f32::consts::TAU;
+            }
+            if delta >= 0.0 {
+                push_sector(out, cur, hw, a0, delta);
+            } else {
+                push_sector(out, cur, hw, a1, -delta);
+            }
+        }
+        LineJoin::Miter => {
+            let bis = add_v(n0, n1);
+            let bl = (bis.x * bis.x + bis.y * bis.y).sqrt();
+            // Cosine of half the angle between the two offset normals.
+            let cos_half = bl / (2.0 * hw);
+            if cos_half <= 0.0 || 1.0 / cos_half > style.miter_limit {
+                push_polygon(out, &[cur, add_v(cur, n0), add_v(cur, n1)]);
+            } else {
+                let miter = add_v(cur, scale_v(bis, hw / (bl * cos_half)));
+                push_polygon(out, &[cur, add_v(cur, n0), miter, add_v(cur, n1)]);
+            }
+        }
+    }
+}
+
+/// Append the cap at `end`, where `from -> end` is the direction of travel.
+fn add_cap(out: &mut Path, from: Vec2, end: Vec2, hw: f32, cap: LineCap) {
+    let nrm = normal(from, end, hw);
+    // Outward direction scaled to half width.
+    let dir = Vec2 {
+        x: nrm.y,
+        y: -nrm.x,
+    };
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Square => push_polygon(
+            out,
+            &[
+                sub_v(end, nrm),
+                add_v(end, nrm),
+                add_v(add_v(end, nrm), dir),
+                add_v(sub_v(end, nrm), dir),
+            ],
+        ),
+        LineCap::Round => {
+            // Sweeping half a turn from the right-hand side passes through `dir`.
+            let start = scale_v(nrm, -1.0);
+            push_sector(out, end, hw, start.y.atan2(start.x), std::f32::consts::PI);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::cpu::draw_path;
+    use crate::types::{Color, Paint};
+
+    #[test]
+    fn round_capped_segment_is_capsule() {
+        let mut line = Path::new();
+        line.move_to(Vec2 { x: 6.0, y: 8.0 });
+        line.line_to(Vec2 { x: 12.0, y: 8.0 });
+        let style = StrokeStyle {
+            cap: LineCap::Round,
+            ..StrokeStyle::default()
+        };
+        let outline = stroke_to_path(&line, 6.0, style);
+        let mut buf = vec![0u8; 20 * 16 * 4];
+        draw_path(
+            &outline,
+            Paint::Solid(Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            }),
+            &mut buf,
+            20,
+            16,
+            20 * 4,
+        );
+        for y in 0..16 {
+            for x in 0..20 {
+                let px = x as f32 + 0.5;
+                let py = y as f32 + 0.5;
+                let cx = px.clamp(6.0, 12.0);
+                let dist = ((px - cx).powi(2) + (py - 8.0).powi(2)).sqrt();
+                let alpha = buf[(y * 20 + x) * 4 + 3];
+                if dist < 2.7 {
+                    assert_eq!(alpha, 255, "pixel ({x},{y}) should be covered");
+                } else if dist > 3.3 {
+                    assert_eq!(alpha, 0, "pixel ({x},{y}) should be empty");
+                }
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@
 use super::Path;
 #[cfg(not(feature = "simd"))]
 use super::{LineSegment, Path};
-use crate::types::Vec2;
+use crate::types::{FillRule, Vec2};
 
 #[cfg(feature = "simd")]
 /// Convert an elliptical arc into cubic Bézier segments for lyon.
@@ -53,12 +53,24 @@ pub struct Mesh {
     pub indices: Vec<u32>,
 }
 
-/// Tessellate a [`Path`] into triangles using the lyon tessellator when
-/// the `simd` feature is enabled. A very naive fan triangulator is used
-/// as a fallback for `no_std` or when lyon is disabled.
 /// Tessellate a [`Path`] into triangles, optionally trimming the length to
 /// the range `[start, end]` before tessellation.
-pub fn tessellate(path: &Path, tolerance: f32, mask: Option<(f32, f32)>) -> Mesh {
+///
+/// With the `simd` feature the lyon tessellator fills the region selected
+/// by `fill_rule`. Without it a naive per-sub-path fan triangulator is used,
+/// which covers every sub-path and so only supports [`FillRule::NonZero`]
+/// for sub-paths wound the same way; `fill_rule` is ignored there.
+///
+/// Fills follow SVG and Lottie: a sub-path without a `Close` is filled as
+/// if a straight edge joined its last point back to its first. `Close`
+/// only matters for stroking, so an open and a closed copy of the same
+/// outline cover the same region.
+pub fn tessellate(
+    path: &Path,
+    tolerance: f32,
+    mask: Option<(f32, f32)>,
+    fill_rule: FillRule,
+) -> Mesh {
     let tmp;
     let src = if let Some((s, e)) = mask {
         tmp = path.trim(s, e, tolerance);
@@ -66,22 +78,58 @@ pub fn tessellate(path: &Path, tolerance: f32, mask: Option<(f32, f32)>) -> Mesh
     } else {
         path
     };
-    tessellate_impl(src, tolerance)
+    tessellate_impl(src, tolerance, fill_rule)
+}
+
+/// Like [`tessellate`], also reporting the orientation of every triangle.
+///
+/// The flag is `true` when a triangle's vertices run counter-clockwise in a
+/// y-up coordinate system (clockwise on screen, where y points down).
+/// Degenerate triangles report `false`.
+pub fn tessellate_debug(
+    path: &Path,
+    tolerance: f32,
+    mask: Option<(f32, f32)>,
+    fill_rule: FillRule,
+) -> (Mesh, Vec<bool>) {
+    let mesh = tessellate(path, tolerance, mask, fill_rule);
+    let ccw = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[tri[i] as usize]);
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) > 0.0
+        })
+        .collect();
+    (mesh, ccw)
 }
 
 #[cfg(feature = "simd")]
-fn tessellate_impl(path: &Path, tolerance: f32) -> Mesh {
+fn tessellate_impl(path: &Path, tolerance: f32, fill_rule: FillRule) -> Mesh {
     use lyon::math::Point;
     use lyon::path::Path as LyonPath;
     use lyon::tessellation::{
-        BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers,
+        self as lyon_tess, BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers,
     };
 
     let mut builder = LyonPath::builder();
+    // lyon wants every sub-path ended; an open end is still filled closed.
+    let mut open = false;
+    let mut start = Vec2::default();
     for seg in &path.segments {
+        if !open && !matches!(seg, super::PathSeg::MoveTo(_) | super::PathSeg::Close) {
+            // Drawing on after a `Close` starts again from the closed point.
+            builder.begin(Point::new(start.x, start.y));
+            open = true;
+        }
         match *seg {
             super::PathSeg::MoveTo(p) => {
+                if open {
+                    builder.end(false);
+                }
                 builder.begin(Point::new(p.x, p.y));
+                open = true;
+                start = p;
             }
             super::PathSeg::LineTo(p) => {
                 builder.line_to(Point::new(p.x, p.y));
@@ -116,16 +164,25 @@ fn tessellate_impl(path: &Path, tolerance: f32) -> Mesh {
                 }
             }
             super::PathSeg::Close => {
-                builder.close();
+                if open {
+                    builder.close();
+                    open = false;
+                }
             }
         }
     }
+    if open {
+        builder.end(false);
+    }
     let lyon_path = builder.build();
     let mut tess = FillTessellator::new();
     let mut buffers: VertexBuffers<Vec2, u32> = VertexBuffers::new();
     tess.tessellate_path(
         &lyon_path,
-        &FillOptions::tolerance(tolerance),
+        &FillOptions::tolerance(tolerance).with_fill_rule(match fill_rule {
+            FillRule::NonZero => lyon_tess::FillRule::NonZero,
+            FillRule::EvenOdd => lyon_tess::FillRule::EvenOdd,
+        }),
         &mut BuffersBuilder::new(&mut buffers, |v: FillVertex| {
             let p = v.position();
             Vec2 { x: p.x, y: p.y }
@@ -139,27 +196,35 @@ fn tessellate_impl(path: &Path, tolerance: f32) -> Mesh {
 }
 
 #[cfg(not(feature = "simd"))]
-fn tessellate_impl(path: &Path, tolerance: f32) -> Mesh {
-    use smallvec::SmallVec;
-    let segs: SmallVec<[LineSegment; 32]> = path.flatten(tolerance);
+fn tessellate_impl(path: &Path, tolerance: f32, _fill_rule: FillRule) -> Mesh {
+    let mut mesh = Mesh::default();
+    for sub in path.subpaths() {
+        fan_fill(&sub.flatten(tolerance), &mut mesh);
+    }
+    mesh
+}
+
+/// Append a triangle fan covering one flattened sub-path.
+#[cfg(not(feature = "simd"))]
+fn fan_fill(segs: &[LineSegment], mesh: &mut Mesh) {
     if segs.is_empty() {
-        return Mesh::default();
+        return;
     }
-    let mut vertices = Vec::new();
+    let base = mesh.vertices.len();
+    let mut vertices = Vec::with_capacity(segs.len() + 1);
     vertices.push(segs[0].from);
-    for seg in &segs {
+    for seg in segs {
         vertices.push(seg.to);
     }
     if vertices.len() > 1 && vertices.last() == vertices.first() {
         vertices.pop();
     }
-    let mut indices = Vec::new();
-    for i in 1..vertices.len() - 1 {
-        indices.push(0);
-        indices.push(i as u32);
-        indices.push(i as u32 + 1);
+    for i in 1..vertices.len().saturating_sub(1) {
+        mesh.indices.push(base as u32);
+        mesh.indices.push((base + i) as u32);
+        mesh.indices.push((base + i + 1) as u32);
     }
-    Mesh { vertices, indices }
+    mesh.vertices.extend(vertices);
 }
 
 #[cfg(test)]
@@ -174,8 +239,86 @@ mod tests {
         path.line_to(Vec2 { x: 1.0, y: 1.0 });
         path.line_to(Vec2 { x: 0.0, y: 1.0 });
         path.close();
-        let mesh = tessellate(&path, 0.1, None);
+        let mesh = tessellate(&path, 0.1, None, FillRule::NonZero);
         assert_eq!(mesh.indices.len(), 6);
         assert!(mesh.vertices.len() >= 4);
     }
+
+    #[test]
+    fn convex_polygon_has_uniform_winding() {
+        let mut path = Path::new();
+        for i in 0..6 {
+            let a = (i as f32 * 60.0).to_radians();
+            let p = Vec2 {
+                x: 5.0 + 4.0 * a.cos(),
+                y: 5.0 + 4.0 * a.sin(),
+            };
+            if i == 0 {
+                path.move_to(p);
+            } else {
+                path.line_to(p);
+            }
+        }
+        path.close();
+        let (mesh, ccw) = tessellate_debug(&path, 0.1, None, FillRule::NonZero);
+        assert_eq!(ccw.len(), mesh.indices.len() / 3);
+        assert!(!ccw.is_empty());
+        assert!(ccw.iter().all(|&w| w == ccw[0]), "{ccw:?}");
+    }
+
+    fn covered(mesh: &Mesh, p: Vec2) -> bool {
+        mesh.indices.chunks_exact(3).any(|tri| {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[tri[i] as usize]);
+            let side = |u: Vec2, v: Vec2| (v.x - u.x) * (p.y - u.y) - (v.y - u.y) * (p.x - u.x);
+            let (d0, d1, d2) = (side(a, b), side(b, c), side(c, a));
+            (d0 >= 0.0 && d1 >= 0.0 && d2 >= 0.0) || (d0 <= 0.0 && d1 <= 0.0 && d2 <= 0.0)
+        })
+    }
+
+    #[test]
+    fn open_subpath_fills_as_if_closed() {
+        // Two sides of a right triangle followed by a separate open square.
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 0.0, y: 0.0 });
+        path.line_to(Vec2 { x: 10.0, y: 0.0 });
+        path.line_to(Vec2 { x: 10.0, y: 10.0 });
+        path.move_to(Vec2 { x: 20.0, y: 0.0 });
+        path.line_to(Vec2 { x: 30.0, y: 0.0 });
+        path.line_to(Vec2 { x: 30.0, y: 10.0 });
+        path.line_to(Vec2 { x: 20.0, y: 10.0 });
+        let mesh = tessellate(&path, 0.1, None, FillRule::NonZero);
+        assert!(covered(&mesh, Vec2 { x: 7.0, y: 3.0 }));
+        assert!(!covered(&mesh, Vec2 { x: 3.0, y: 7.0 }));
+        assert!(covered(&mesh, Vec2 { x: 22.0, y: 8.0 }));
+        assert!(!covered(&mesh, Vec2 { x: 15.0, y: 5.0 }));
+
+        let mut closed = path.clone();
+        closed.segments.insert(3, crate::geometry::PathSeg::Close);
+        closed.close();
+        let closed_mesh = tessellate(&closed, 0.1, None, FillRule::NonZero);
+        for p in [(7.0, 3.0), (3.0, 7.0), (22.0, 8.0), (15.0, 5.0)] {
+            let p = Vec2 { x: p.0, y: p.1 };
+            assert_eq!(covered(&mesh, p), covered(&closed_mesh, p));
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn even_odd_leaves_donut_hole() {
+        let mut path = Path::new();
+        for (lo, hi) in [(0.0, 10.0), (3.0, 7.0)] {
+            path.move_to(Vec2 { x: lo, y: lo });
+            path.line_to(Vec2 { x: hi, y: lo });
+            path.line_to(Vec2 { x: hi, y: hi });
+            path.line_to(Vec2 { x: lo, y: hi });
+            path.close();
+        }
+        let hole = Vec2 { x: 5.0, y: 5.0 };
+        let ring = Vec2 { x: 1.5, y: 5.0 };
+        let even_odd = tessellate(&path, 0.1, None, FillRule::EvenOdd);
+        assert!(!covered(&even_odd, hole));
+        assert!(covered(&even_odd, ring));
+        let non_zero = tessellate(&path, 0.1, None, FillRule::NonZero);
+        assert!(covered(&non_zero, hole));
+    }
 }
@@ -3,6 +3,7 @@
 //! Module: rlottie core library
 //! Mirrors: rlottie
 
+pub mod export;
 pub mod geometry;
 pub mod loader;
 pub mod renderer;
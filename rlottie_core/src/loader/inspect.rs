@@ -0,0 +1,138 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: static feature report
+//! Mirrors: none (rlottie-rs extension)
+
+use super::json::effect_reference;
+use serde_json::Value;
+
+/// Names of the features in the Lottie JSON `data` that this crate loads
+/// but does not render, or skips entirely, each listed once in the order
+/// first met.
+///
+/// This only inspects the document; an empty list means every feature it
+/// uses is known, not that the output matches the reference renderer.
+pub fn unsupported_features(data: &[u8]) -> Result<Vec<String>, serde_json::Error> {
+    let root: Value = serde_json::from_slice(data)?;
+    let mut found = Vec::new();
+    let mut report = |name: String| {
+        if !found.contains(&name) {
+            found.push(name);
+        }
+    };
+    let precomp_layers = root
+        .get("assets")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|asset| asset.get("layers").and_then(Value::as_array));
+    let layer_lists = root
+        .get("layers")
+        .and_then(Value::as_array)
+        .into_iter()
+        .chain(precomp_layers);
+    for layer in layer_lists.flatten() {
+        inspect_layer(layer, &mut report);
+    }
+    if has_expression(&root) {
+        report("expressions".to_owned());
+    }
+    Ok(found)
+}
+
+fn inspect_layer(layer: &Value, report: &mut impl FnMut(String)) {
+    if layer.get("ddd").and_then(Value::as_i64) == Some(1) {
+        report("3D layers".to_owned());
+    }
+    match layer.get("ty").and_then(Value::as_i64) {
+        Some(0 | 2 | 3 | 4) | None => {}
+        Some(1) => report("solid layers".to_owned()),
+        Some(5) => report("text layers".to_owned()),
+        Some(6) => report("audio layers".to_owned()),
+        Some(13) => report("camera layers".to_owned()),
+        Some(ty) => report(format!("layer type {ty}")),
+    }
+    match layer.get("bm").and_then(Value::as_i64) {
+        Some(0..=5 | 16) | None => {}
+        Some(bm) => report(format!("blend mode {bm}")),
+    }
+    if matches!(layer.get("tt").and_then(Value::as_i64), Some(3 | 4)) {
+        report("luma mattes".to_owned());
+    }
+    let masks = layer.get("masksProperties").and_then(Value::as_array);
+    for mask in masks.into_iter().flatten() {
+        match mask.get("mode").and_then(Value::as_str) {
+            Some("a" | "s" | "i" | "n") | None => {}
+            Some(mode) => report(format!("mask mode \"{mode}\"")),
+        }
+    }
+    let effects = layer.get("ef").and_then(Value::as_array);
+    for effect in effects.into_iter().flatten() {
+        let supported = matches!(effect.get("ty").and_then(Value::as_i64), Some(20 | 25 | 29))
+            || effect.get("mn").and_then(Value::as_str) == Some("ADBE Color Control");
+        if !supported {
+            let name = ["nm", "mn"]
+                .iter()
+                .find_map(|k| effect.get(*k).and_then(Value::as_str))
+                .unwrap_or("unnamed");
+            report(format!("effect \"{name}\""));
+        }
+    }
+    if let Some(items) = layer.get("shapes").and_then(Value::as_array) {
+        inspect_shape_items(items, report);
+    }
+}
+
+fn inspect_shape_items(items: &[Value], report: &mut impl FnMut(String)) {
+    for item in items {
+        let name = match item.get("ty").and_then(Value::as_str) {
+            Some("gr") => {
+                match item.get("bm").and_then(Value::as_i64) {
+                    Some(0..=5 | 16) | None => {}
+                    Some(bm) => report(format!("blend mode {bm}")),
+                }
+                if let Some(it) = item.get("it").and_then(Value::as_array) {
+                    inspect_shape_items(it, report);
+                }
+                continue;
+            }
+            Some("tr" | "el" | "rc" | "sr" | "sh" | "fl" | "gf" | "st" | "rp" | "tm") | None => {
+                continue;
+            }
+            Some("gs") => "gradient strokes".to_owned(),
+            Some("mm") => "merge paths".to_owned(),
+            Some("rd") => "round corners".to_owned(),
+            Some("op") => "offset paths".to_owned(),
+            Some("pb") => "pucker & bloat".to_owned(),
+            Some("tw") => "twist".to_owned(),
+            Some("zz") => "zig zag".to_owned(),
+            Some(ty) => format!("shape item \"{ty}\""),
+        };
+        report(name);
+    }
+}
+
+/// Whether any property below `value` is driven by an expression (`"x"`).
+/// Fill colors linked to a "Color Control" effect are supported and do not
+/// count.
+fn has_expression(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(has_expression),
+        Value::Object(map) => {
+            let linked_fill = map.get("ty").and_then(Value::as_str) == Some("fl")
+                && map
+                    .get("c")
+                    .and_then(|c| c.get("x"))
+                    .and_then(Value::as_str)
+                    .and_then(effect_reference)
+                    .is_some();
+            let is_property = map.contains_key("k");
+            map.iter().any(|(key, v)| match key.as_str() {
+                "x" if is_property => v.is_string(),
+                "c" if linked_fill => false,
+                _ => has_expression(v),
+            })
+        }
+        _ => false,
+    }
+}
@@ -3,61 +3,93 @@
 //! Module: JSON composition loader
 //! Mirrors: rlottie/src/lottie/lottiecomposition.cpp
 
+use crate::geometry::{Path, PathSeg};
+use crate::timeline::{Animator, CubicBezier, Keyframe, SpatialKeyframe};
 use crate::types::{
-    Color, Composition, ImageLayer, Layer, MatteType, PathCommand, PreCompLayer, ShapeLayer,
-    Transform, Vec2,
+    Affine, BlendMode, Color, ColorSpace, Composition, FillRule, GradientStop, ImageLayer,
+    ImageSequenceLayer, Layer, LayerEffect, LineCap, LineJoin, LinearGradient, Marker, MaskMode,
+    MatteType, NullLayer, OpacityStop, Paint, PreCompLayer, RadialGradient, ShapeLayer, Transform,
+    TrimTarget, Vec2, ANCHOR_X, ANCHOR_Y, DASH_OFFSET, FILL_OPACITY, OPACITY, POSITION_X,
+    POSITION_Y, ROTATION, SCALE_X, SCALE_Y, STROKE_WIDTH,
 };
 use base64::{engine::general_purpose, Engine as _};
 use image::ImageReader;
 use serde_json::Value;
+use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
-use std::path::Path;
 
 /// Load a composition from a reader containing Lottie JSON.
 pub fn from_reader<R: Read>(mut reader: R) -> Result<Composition, Box<dyn std::error::Error>> {
     let mut s = String::new();
     reader.read_to_string(&mut s)?;
     let root: Value = serde_json::from_str(&s)?;
-    let width = root.get("w").and_then(Value::as_u64).unwrap_or(0) as u32;
-    let height = root.get("h").and_then(Value::as_u64).unwrap_or(0) as u32;
+    composition_from_value(&root)
+}
+
+fn composition_from_value(root: &Value) -> Result<Composition, Box<dyn std::error::Error>> {
+    let ctx = load_context(root)?;
+    ctx.decode_images()?;
+    let raw_layers = root
+        .get("layers")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let layers = parse_layers(raw_layers, &ctx, 0);
+    Ok(build_composition(&ctx, layers, raw_layers))
+}
+
+/// Assemble the root composition from its parsed layers. `raw_layers` is
+/// the document's `"layers"` array, scanned for color controls.
+fn build_composition(ctx: &LoadContext, layers: Vec<Layer>, raw_layers: &[Value]) -> Composition {
+    let mut comp = Composition {
+        width: ctx.width,
+        height: ctx.height,
+        start_frame: ctx.start_frame,
+        end_frame: ctx.end_frame,
+        fps: ctx.fps,
+        pixel_aspect: ctx.pixel_aspect,
+        opacity: 1.0,
+        layers,
+        color_controls: parse_color_controls(raw_layers),
+        markers: ctx.markers.clone(),
+    };
+    comp.apply_color_controls();
+    comp
+}
+
+/// Read the composition header and decode its assets.
+fn load_context(root: &Value) -> Result<LoadContext, Box<dyn std::error::Error>> {
+    let mut width = root.get("w").and_then(Value::as_u64).unwrap_or(0) as u32;
+    let mut height = root.get("h").and_then(Value::as_u64).unwrap_or(0) as u32;
     let start = root.get("ip").and_then(Value::as_f64).unwrap_or(0.0) as u32;
     let end = root.get("op").and_then(Value::as_f64).unwrap_or(0.0) as u32;
-    let fps = root.get("fr").and_then(Value::as_f64).unwrap_or(0.0) as f32;
-    let mut images: HashMap<String, (u32, u32, Vec<u8>)> = HashMap::new();
+    let mut fps = root.get("fr").and_then(Value::as_f64).unwrap_or(0.0) as f32;
+    let pixel_aspect = root
+        .get("par")
+        .and_then(Value::as_f64)
+        .filter(|par| *par > 0.0)
+        .unwrap_or(1.0) as f32;
+    let mut images: HashMap<String, ImageAsset> = HashMap::new();
     let mut assets: HashMap<String, Value> = HashMap::new();
+    let mut asset_order: Vec<AssetEntry> = Vec::new();
     if let Some(asset_arr) = root.get("assets").and_then(Value::as_array) {
         for asset in asset_arr {
             if let Some(id) = asset.get("id").and_then(Value::as_str) {
-                if let Some(p) = asset.get("p").and_then(Value::as_str) {
-                    let width_a = asset.get("w").and_then(Value::as_u64).unwrap_or(0) as u32;
-                    let height_a = asset.get("h").and_then(Value::as_u64).unwrap_or(0) as u32;
-                    let bytes = if asset.get("e").and_then(Value::as_i64) == Some(1) {
-                        if let Some(idx) = p.find(',') {
-                            let mut b64 = p[idx + 1..].trim();
-                            while b64.len() % 4 != 0 {
-                                b64 = &b64[..b64.len() - 1];
-                            }
-                            general_purpose::STANDARD.decode(b64)?
-                        } else {
-                            Vec::new()
-                        }
-                    } else {
-                        let mut path = String::new();
-                        if let Some(u) = asset.get("u").and_then(Value::as_str) {
-                            path.push_str(u);
-                        }
-                        path.push_str(p);
-                        fs::read(Path::new(&path))?
-                    };
-                    if !bytes.is_empty() {
-                        let img = ImageReader::new(std::io::Cursor::new(bytes))
-                            .with_guessed_format()?
-                            .decode()?
-                            .to_rgba8();
-                        images.insert(id.to_string(), (width_a, height_a, img.into_raw()));
-                    }
+                asset_order.push(AssetEntry {
+                    id: id.to_string(),
+                    sequence: asset.get("t").and_then(Value::as_str) == Some("seq"),
+                    fps: asset.get("fr").and_then(Value::as_f64).map(|f| f as f32),
+                });
+                if asset.get("p").and_then(Value::as_str).is_some() {
+                    images.insert(
+                        id.to_string(),
+                        ImageAsset {
+                            asset: asset.clone(),
+                            pixels: OnceCell::new(),
+                        },
+                    );
                 }
                 if asset.get("layers").is_some() {
                     assets.insert(id.to_string(), asset.clone());
@@ -66,18 +98,98 @@ pub fn from_reader<R: Read>(mut reader: R) -> Result<Composition, Box<dyn std::e
         }
     }
 
-    let layers = root
-        .get("layers")
-        .and_then(Value::as_array)
-        .map(|arr| parse_layers(arr, &assets, &images, width, height, fps))
-        .unwrap_or_default();
-    Ok(Composition {
+    if width == 0 || height == 0 || fps <= 0.0 {
+        // Snippets exported from a single precomp may only size the asset.
+        let referenced = root
+            .get("layers")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l.get("refId").and_then(Value::as_str))
+            .find_map(|id| assets.get(id).map(|a| (id, a)));
+        if let Some((id, asset)) = referenced {
+            let dim = |key| asset.get(key).and_then(Value::as_u64).unwrap_or(0) as u32;
+            if width == 0 || height == 0 {
+                width = dim("w");
+                height = dim("h");
+            }
+            if fps <= 0.0 {
+                fps = asset.get("fr").and_then(Value::as_f64).unwrap_or(0.0) as f32;
+            }
+            log::warn!(
+                "root composition is missing w/h/fr; using precomp asset `{id}`: {width}x{height} at {fps} fps"
+            );
+        }
+    }
+
+    Ok(LoadContext {
+        assets,
+        images,
+        asset_order,
         width,
         height,
         start_frame: start,
         end_frame: end,
         fps,
-        layers,
+        pixel_aspect,
+        markers: root
+            .get("markers")
+            .and_then(Value::as_array)
+            .map(|arr| arr.iter().filter_map(parse_marker).collect())
+            .unwrap_or_default(),
+    })
+}
+
+/// Decode the pixels of an image asset: an embedded data URI or a file at
+/// `u` + `p`. `None` when an embedded asset carries no data.
+fn decode_image(asset: &Value) -> Result<Option<ImagePixels>, Box<dyn std::error::Error>> {
+    let Some(p) = asset.get("p").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+    let width = asset.get("w").and_then(Value::as_u64).unwrap_or(0) as u32;
+    let height = asset.get("h").and_then(Value::as_u64).unwrap_or(0) as u32;
+    let bytes = if asset.get("e").and_then(Value::as_i64) == Some(1) {
+        if let Some(idx) = p.find(',') {
+            let mut b64 = p[idx + 1..].trim();
+            while b64.len() % 4 != 0 {
+                b64 = &b64[..b64.len() - 1];
+            }
+            general_purpose::STANDARD.decode(b64)?
+        } else {
+            Vec::new()
+        }
+    } else {
+        let mut path = String::new();
+        if let Some(u) = asset.get("u").and_then(Value::as_str) {
+            path.push_str(u);
+        }
+        path.push_str(p);
+        fs::read(std::path::Path::new(&path))?
+    };
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let img = ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()?
+        .decode()?
+        .to_rgba8();
+    Ok(Some((width, height, img.into_raw())))
+}
+
+/// Read one entry of the root `"markers"` array; unnamed markers are
+/// skipped.
+fn parse_marker(marker: &Value) -> Option<Marker> {
+    let frame = |key| {
+        marker
+            .get(key)
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0)
+            .max(0.0) as u32
+    };
+    Some(Marker {
+        name: marker.get("cm").and_then(Value::as_str)?.to_owned(),
+        start: frame("tm"),
+        duration: frame("dr"),
     })
 }
 
@@ -87,161 +199,901 @@ pub fn from_slice(data: &[u8]) -> Result<Composition, Box<dyn std::error::Error>
     from_reader(cursor)
 }
 
-fn parse_layers(
-    arr: &[Value],
-    assets: &HashMap<String, Value>,
-    images: &HashMap<String, (u32, u32, Vec<u8>)>,
+/// Load every composition bundled in `data`.
+///
+/// Accepts a JSON array of compositions or a dotLottie-style manifest whose
+/// `animations` array holds the compositions inline. A single composition
+/// yields a one-element vector.
+pub fn from_slice_multi(data: &[u8]) -> Result<Vec<Composition>, Box<dyn std::error::Error>> {
+    let root: Value = serde_json::from_slice(data)?;
+    let items = match &root {
+        Value::Array(items) => items,
+        _ => match root.get("animations").and_then(Value::as_array) {
+            Some(items) => items,
+            None => return Ok(vec![composition_from_value(&root)?]),
+        },
+    };
+    items.iter().map(composition_from_value).collect()
+}
+
+/// Composition whose layers are parsed on first use.
+///
+/// The header is read up front, so the size, frame range and frame rate
+/// are available immediately. Each layer is parsed the first time it is
+/// requested and cached, and image assets are decoded when a layer using
+/// them is parsed. The first render parses whatever is left and moves the
+/// cached layers into the composition it reuses for later frames.
+pub struct LazyComposition {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// First frame of the animation
+    pub start_frame: u32,
+    /// Last frame of the animation
+    pub end_frame: u32,
+    /// Frames per second
+    pub fps: f32,
+    raw_layers: Vec<Value>,
+    parsed: Vec<OnceCell<Option<Layer>>>,
+    /// Built composition and, per layer entry, its index in it
+    comp: Option<(Composition, Vec<Option<usize>>)>,
+    ctx: LoadContext,
+}
+
+impl LazyComposition {
+    /// Read the header of a Lottie JSON document without parsing its layers
+    /// or decoding its images.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut root: Value = serde_json::from_slice(data)?;
+        let ctx = load_context(&root)?;
+        let raw_layers = match root.get_mut("layers").map(Value::take) {
+            Some(Value::Array(arr)) => arr,
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            width: ctx.width,
+            height: ctx.height,
+            start_frame: ctx.start_frame,
+            end_frame: ctx.end_frame,
+            fps: ctx.fps,
+            parsed: raw_layers.iter().map(|_| OnceCell::new()).collect(),
+            raw_layers,
+            comp: None,
+            ctx,
+        })
+    }
+
+    /// Number of layer entries in the document, including unsupported ones.
+    pub fn layer_count(&self) -> usize {
+        self.raw_layers.len()
+    }
+
+    /// Number of layer entries parsed so far.
+    pub fn parsed_count(&self) -> usize {
+        if self.comp.is_some() {
+            return self.layer_count();
+        }
+        self.parsed.iter().filter(|p| p.get().is_some()).count()
+    }
+
+    /// The layer at `index`, parsing it on first access. `None` when out of
+    /// range or when the entry is not a supported layer.
+    pub fn layer(&self, index: usize) -> Option<&Layer> {
+        let raw = self.raw_layers.get(index)?;
+        if let Some((comp, slots)) = &self.comp {
+            return slots[index].map(|i| &comp.layers[i]);
+        }
+        self.parsed[index]
+            .get_or_init(|| parse_layer(raw, &self.ctx, 0))
+            .as_ref()
+    }
+
+    /// The fully parsed composition.
+    pub fn composition(&mut self) -> &Composition {
+        let (comp, _) = self.comp.get_or_insert_with(|| {
+            let mut layers = Vec::new();
+            let mut slots = Vec::with_capacity(self.raw_layers.len());
+            for (cell, raw) in self.parsed.iter_mut().zip(&self.raw_layers) {
+                let layer = cell
+                    .take()
+                    .unwrap_or_else(|| parse_layer(raw, &self.ctx, 0));
+                slots.push(layer.is_some().then_some(layers.len()));
+                layers.extend(layer);
+            }
+            (
+                build_composition(&self.ctx, layers, &self.raw_layers),
+                slots,
+            )
+        });
+        comp
+    }
+
+    /// Render a frame into the provided RGBA8888 buffer.
+    pub fn render_sync(
+        &mut self,
+        frame: u32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) {
+        self.composition()
+            .render_sync(frame, buffer, width, height, stride);
+    }
+}
+
+/// Maximum nesting of precomp references, guarding against cyclic assets.
+const MAX_PRECOMP_DEPTH: usize = 32;
+
+/// Position and kind of an asset in the file's `assets` array.
+struct AssetEntry {
+    id: String,
+    /// Asset is a frame of an image sequence (`"t": "seq"`)
+    sequence: bool,
+    /// Frame rate declared on the asset
+    fps: Option<f32>,
+}
+
+/// Decoded image: width and height declared by the asset, then RGBA8888
+/// pixels.
+type ImagePixels = (u32, u32, Vec<u8>);
+
+/// Image asset whose pixels are decoded on first use.
+struct ImageAsset {
+    asset: Value,
+    pixels: OnceCell<Option<ImagePixels>>,
+}
+
+/// Shared state built once per file and consulted while parsing layers.
+struct LoadContext {
+    /// Precomp assets keyed by id
+    assets: HashMap<String, Value>,
+    /// Image assets keyed by id
+    images: HashMap<String, ImageAsset>,
+    /// Assets in file order, used to resolve image sequences
+    asset_order: Vec<AssetEntry>,
+    /// Root composition width
     width: u32,
+    /// Root composition height
     height: u32,
+    /// Root first frame, shared by precomps so they follow parent time
+    start_frame: u32,
+    /// Root last frame
+    end_frame: u32,
+    /// Root frame rate
     fps: f32,
-) -> Vec<Layer> {
+    /// Root pixel aspect ratio
+    pixel_aspect: f32,
+    /// Root timeline markers
+    markers: Vec<Marker>,
+}
+
+impl LoadContext {
+    /// Decode every image asset not decoded yet, failing on the first that
+    /// cannot be read.
+    fn decode_images(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for image in self.images.values() {
+            if image.pixels.get().is_none() {
+                let _ = image.pixels.set(decode_image(&image.asset)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pixels of the image asset `id`, decoding it on first use. An asset
+    /// that fails to decode is logged and treated as missing.
+    fn image(&self, id: &str) -> Option<&ImagePixels> {
+        let image = self.images.get(id)?;
+        image
+            .pixels
+            .get_or_init(|| {
+                decode_image(&image.asset).unwrap_or_else(|err| {
+                    log::warn!("failed to decode image asset `{id}`: {err}");
+                    None
+                })
+            })
+            .as_ref()
+    }
+}
+
+fn parse_layers(arr: &[Value], ctx: &LoadContext, depth: usize) -> Vec<Layer> {
     let mut out = Vec::new();
     for layer in arr {
-        if let Some(l) = parse_layer(layer, assets, images, width, height, fps) {
+        if let Some(l) = parse_layer(layer, ctx, depth) {
             out.push(l);
         }
     }
     out
 }
 
-fn parse_layer(
-    layer: &Value,
-    assets: &HashMap<String, Value>,
-    images: &HashMap<String, (u32, u32, Vec<u8>)>,
-    width: u32,
-    height: u32,
-    fps: f32,
-) -> Option<Layer> {
+fn parse_layer(layer: &Value, ctx: &LoadContext, depth: usize) -> Option<Layer> {
     match layer.get("ty").and_then(Value::as_i64)? {
-        4 => {
-            let mut paths = Vec::new();
-            let mut fill = None;
-            let mut stroke = None;
-            let mut stroke_width = 1.0;
-            let mut repeater: Option<(u32, Transform)> = None;
-            let mut trim: Option<(f32, f32)> = None;
-            let is_mask = layer.get("td").and_then(Value::as_i64) == Some(1);
-            let matte = match layer.get("tt").and_then(Value::as_i64) {
-                Some(1) => Some(MatteType::Alpha),
-                Some(2) => Some(MatteType::AlphaInv),
-                _ => None,
+        4 => Some(Layer::Shape(parse_shape_layer(layer))),
+        3 => Some(Layer::Null(NullLayer {
+            index: layer_index(layer, "ind"),
+            parent: layer_index(layer, "parent"),
+            transform: layer.get("ks").map(parse_transform).unwrap_or_default(),
+        })),
+        0 => parse_precomp_layer(layer, ctx, depth).map(Layer::PreComp),
+        2 => parse_image_sequence(layer, ctx)
+            .map(Layer::ImageSequence)
+            .or_else(|| parse_image_layer(layer, ctx).map(Layer::Image)),
+        _ => None,
+    }
+}
+
+/// Read a layer index field such as `"ind"` or `"parent"`.
+fn layer_index(layer: &Value, key: &str) -> Option<u32> {
+    layer
+        .get(key)
+        .and_then(Value::as_u64)
+        .and_then(|i| u32::try_from(i).ok())
+}
+
+/// Read a layer's start time `"st"` in frames, zero when absent.
+fn start_time(layer: &Value) -> f32 {
+    layer.get("st").and_then(Value::as_f64).unwrap_or(0.0) as f32
+}
+
+fn parse_shape_layer(layer: &Value) -> ShapeLayer {
+    let mut shape = ShapeLayer {
+        stroke_width: 1.0,
+        ..ShapeLayer::default()
+    };
+    if let Some(ks) = layer.get("ks") {
+        shape.transform = parse_transform(ks);
+    }
+    shape.index = layer_index(layer, "ind");
+    shape.parent = layer_index(layer, "parent");
+    shape.time_remap = layer.get("tm").and_then(parse_scalar_animator);
+    shape.start_time = start_time(layer);
+    if let Some(shape_arr) = layer.get("shapes").and_then(Value::as_array) {
+        collect_shape_items(shape_arr, &mut shape, 1.0);
+    }
+    shape.has_mask = layer
+        .get("hasMask")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if let Some(arr) = layer.get("masksProperties").and_then(Value::as_array) {
+        let mut paths = Vec::new();
+        for m in arr {
+            let Some(path) = m.get("pt").and_then(parse_shape_data) else {
+                continue;
             };
-            if let Some(shape_arr) = layer.get("shapes").and_then(Value::as_array) {
-                for shape in shape_arr {
-                    if let Some(ty) = shape.get("ty").and_then(Value::as_str) {
-                        match ty {
-                            "sh" => {
-                                if let Some(d) = shape
-                                    .get("ks")
-                                    .and_then(|k| k.get("d"))
-                                    .and_then(Value::as_str)
-                                {
-                                    paths.push(parse_path(d));
+            let x = m
+                .get("x")
+                .and_then(|x| x.get("k"))
+                .and_then(Value::as_f64)
+                .unwrap_or(0.0) as f32;
+            let opacity = m
+                .get("o")
+                .and_then(|o| o.get("k"))
+                .and_then(Value::as_f64)
+                .unwrap_or(100.0) as f32;
+            paths.push(path);
+            shape.mask_expansion.push(x);
+            shape.mask_modes.push(parse_mask_mode(m));
+            shape
+                .mask_inverted
+                .push(m.get("inv").and_then(Value::as_bool).unwrap_or(false));
+            shape.mask_opacity.push(opacity / 100.0);
+        }
+        if !paths.is_empty() {
+            shape.mask = Some(paths);
+        }
+    }
+    if let Some(arr) = layer.get("ef").and_then(Value::as_array) {
+        shape.effects = arr.iter().filter_map(parse_effect).collect();
+    }
+    shape.is_mask = layer.get("td").and_then(Value::as_i64) == Some(1);
+    shape.matte = match layer.get("tt").and_then(Value::as_i64) {
+        Some(1) => Some(MatteType::Alpha),
+        Some(2) => Some(MatteType::AlphaInv),
+        _ => None,
+    };
+    shape.blend_mode = parse_blend_mode(layer.get("bm").and_then(Value::as_i64));
+    shape
+}
+
+/// Map a Lottie `"bm"` code to a [`BlendMode`]; unsupported modes draw
+/// as `Normal`.
+fn parse_blend_mode(code: Option<i64>) -> BlendMode {
+    match code {
+        Some(1) => BlendMode::Multiply,
+        Some(2) => BlendMode::Screen,
+        Some(3) => BlendMode::Overlay,
+        Some(4) => BlendMode::Darken,
+        Some(5) => BlendMode::Lighten,
+        Some(16) => BlendMode::Add,
+        _ => BlendMode::Normal,
+    }
+}
+
+/// Accumulate shape items into `shape`, keyed by each item's `"ty"`.
+///
+/// Groups (`"gr"`) are recursed into through their `"it"` array, and the
+/// paths they contribute are mapped through the group's `"tr"` item, so
+/// nested group transforms compose from the inside out. Fill and stroke
+/// colors have their alpha scaled by `opacity`, the product of the
+/// enclosing groups' opacities. A repeater
+/// (`"rp"`) stamps copies of the paths that precede it in its own group,
+/// each offset by one more step of its transform; its start and end
+/// opacities are not applied, as a layer has a single fill. A group with a
+/// non-normal blend mode (`"bm"`) is collected into its own entry of
+/// [`ShapeLayer::blend_groups`] instead; paints and trims listed after it
+/// still reach it, as they reach a normal group's paths. Unknown types,
+/// and known items whose properties fail to parse, leave the state
+/// gathered so far untouched.
+fn collect_shape_items(items: &[Value], shape: &mut ShapeLayer, opacity: f32) {
+    let group_start = shape.paths.len();
+    let blend_start = shape.blend_groups.len();
+    // Whether a fill or stroke sits between this stack's paths and its trim.
+    let mut fill_below_paths = false;
+    let mut stroke_below_paths = false;
+    for item in items {
+        let Some(ty) = item.get("ty").and_then(Value::as_str) else {
+            continue;
+        };
+        let has_paths = shape.paths.len() > group_start || shape.blend_groups.len() > blend_start;
+        match ty {
+            "fl" | "gf" => fill_below_paths = has_paths,
+            "st" => stroke_below_paths = has_paths,
+            _ => {}
+        }
+        match ty {
+            "gr" => {
+                if let Some(it) = item.get("it").and_then(Value::as_array) {
+                    check_group_count(item, it);
+                    let group_tr = it
+                        .iter()
+                        .find(|i| i.get("ty").and_then(Value::as_str) == Some("tr"))
+                        .map(parse_transform);
+                    let group_opacity = group_tr.as_ref().map_or(1.0, |tr| tr.opacity);
+                    let mode = parse_blend_mode(item.get("bm").and_then(Value::as_i64));
+                    if mode != BlendMode::Normal {
+                        // Drawn on its own so only this group blends.
+                        let mut group = ShapeLayer {
+                            stroke_width: 1.0,
+                            blend_mode: mode,
+                            ..ShapeLayer::default()
+                        };
+                        collect_shape_items(it, &mut group, opacity * group_opacity);
+                        if let Some(tr) = &group_tr {
+                            transform_group(&mut group, &Affine::from_transform(tr));
+                        }
+                        shape.blend_groups.push(group);
+                        continue;
+                    }
+                    let first = shape.paths.len();
+                    let first_blend = shape.blend_groups.len();
+                    // A gradient set inside the group lives in its space too.
+                    let outer_paint = shape.fill_paint.take();
+                    collect_shape_items(it, shape, opacity * group_opacity);
+                    match (&group_tr, shape.fill_paint.take()) {
+                        (Some(tr), Some(paint)) => {
+                            shape.fill_paint = Some(paint.transformed(&Affine::from_transform(tr)));
+                        }
+                        (_, inner) => shape.fill_paint = inner.or(outer_paint),
+                    }
+                    if let Some(tr) = group_tr {
+                        for path in &mut shape.paths[first..] {
+                            *path = path.map_points(|p| tr.apply(p));
+                        }
+                        let m = Affine::from_transform(&tr);
+                        for group in &mut shape.blend_groups[first_blend..] {
+                            transform_group(group, &m);
+                        }
+                    }
+                }
+            }
+            "el" => {
+                if let (Some(center), Some(size)) =
+                    (static_vec2(item.get("p")), static_vec2(item.get("s")))
+                {
+                    shape.paths.push(ellipse_path(center, size));
+                }
+            }
+            "rc" => {
+                if let (Some(center), Some(size)) =
+                    (static_vec2(item.get("p")), static_vec2(item.get("s")))
+                {
+                    let radius = static_f32(item.get("r")).unwrap_or(0.0);
+                    shape.paths.push(rect_path(center, size, radius));
+                }
+            }
+            "sr" => {
+                let points = static_f32(item.get("pt")).unwrap_or(0.0).round();
+                if let (Some(center), Some(outer), true) = (
+                    static_vec2(item.get("p")),
+                    static_f32(item.get("or")),
+                    points >= 3.0,
+                ) {
+                    // sy 1 is a star, 2 a polygon without inner vertices.
+                    let inner = match item.get("sy").and_then(Value::as_i64) {
+                        Some(2) => None,
+                        _ => Some(static_f32(item.get("ir")).unwrap_or(0.0)),
+                    };
+                    let rotation = static_f32(item.get("r")).unwrap_or(0.0);
+                    shape.paths.push(polystar_path(
+                        center,
+                        points as usize,
+                        outer,
+                        inner,
+                        rotation,
+                    ));
+                }
+            }
+            "sh" => {
+                if let Some(path) = item.get("ks").and_then(parse_shape_data) {
+                    shape.paths.push(path);
+                }
+            }
+            "fl" => {
+                // A keyframed opacity is sampled with the color; a static
+                // one is folded into it like the group opacity.
+                let o = item.get("o");
+                shape.animators.remove(FILL_OPACITY);
+                let opacity = match o.filter(|o| is_animated(o)) {
+                    Some(o) => {
+                        if let Some(anim) = parse_scalar_animator(o) {
+                            shape.animators.insert(FILL_OPACITY, anim);
+                        }
+                        opacity
+                    }
+                    None => opacity * static_f32(o).map_or(1.0, |o| o / 100.0),
+                };
+                let anim = item
+                    .get("c")
+                    .filter(|c| is_animated(c))
+                    .and_then(parse_color_animator);
+                if let Some(mut anim) = anim {
+                    for key in &mut anim.frames {
+                        key.start_v = fade(key.start_v, opacity);
+                        key.end_v = fade(key.end_v, opacity);
+                    }
+                    shape.fill = Some(anim.value(0.0));
+                    shape.fill_animator = Some(anim);
+                } else if let Some(c) = parse_color(item) {
+                    shape.fill = Some(fade(c, opacity));
+                }
+                shape.fill_control = item
+                    .get("c")
+                    .and_then(|c| c.get("x"))
+                    .and_then(Value::as_str)
+                    .and_then(effect_reference)
+                    .map(str::to_owned);
+                match item.get("r").and_then(Value::as_i64) {
+                    Some(1) => shape.fill_rule = FillRule::NonZero,
+                    Some(2) => shape.fill_rule = FillRule::EvenOdd,
+                    _ => {}
+                }
+            }
+            "gf" => {
+                if let Some(paint) = parse_gradient(item) {
+                    let item_opacity = static_f32(item.get("o")).map_or(1.0, |o| o / 100.0);
+                    shape.fill_paint = Some(paint.faded(opacity * item_opacity));
+                }
+                match item.get("r").and_then(Value::as_i64) {
+                    Some(1) => shape.fill_rule = FillRule::NonZero,
+                    Some(2) => shape.fill_rule = FillRule::EvenOdd,
+                    _ => {}
+                }
+            }
+            "st" => {
+                if let Some(c) = parse_color(item) {
+                    shape.stroke = Some(fade(c, opacity));
+                }
+                let w = item.get("w");
+                if let Some(w) = w.and_then(|k| k.get("k")).and_then(Value::as_f64) {
+                    shape.stroke_width = w as f32;
+                } else if let Some(anim) = w.and_then(parse_scalar_animator) {
+                    shape.stroke_width = anim.value(0.0);
+                    shape.animators.insert(STROKE_WIDTH, anim);
+                }
+                let style = &mut shape.stroke_style;
+                style.cap = match item.get("lc").and_then(Value::as_u64) {
+                    Some(2) => LineCap::Round,
+                    Some(3) => LineCap::Square,
+                    _ => LineCap::Butt,
+                };
+                style.join = match item.get("lj").and_then(Value::as_u64) {
+                    Some(2) => LineJoin::Round,
+                    Some(3) => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                };
+                if let Some(ml) = item.get("ml").and_then(Value::as_f64) {
+                    style.miter_limit = ml as f32;
+                }
+                if let Some(dashes) = item.get("d").and_then(Value::as_array) {
+                    // Each length is static, or keyframed with its frame 0
+                    // value kept as the static fallback.
+                    let length = |d: &Value| {
+                        let v = d.get("v");
+                        match static_f32(v) {
+                            Some(len) => Some((len, None)),
+                            None => v
+                                .and_then(parse_scalar_animator)
+                                .map(|a| (a.value(0.0), Some(a))),
+                        }
+                    };
+                    let mut pattern = Vec::new();
+                    shape.dash_animators.clear();
+                    for d in dashes {
+                        match d.get("n").and_then(Value::as_str) {
+                            Some("d" | "g") => {
+                                if let Some((len, anim)) = length(d) {
+                                    pattern.push(len);
+                                    shape.dash_animators.push(anim);
                                 }
                             }
-                            "fl" => fill = parse_color(shape),
-                            "st" => {
-                                stroke = parse_color(shape);
-                                if let Some(w) = shape
-                                    .get("w")
-                                    .and_then(|k| k.get("k"))
-                                    .and_then(Value::as_f64)
-                                {
-                                    stroke_width = w as f32;
+                            Some("o") => {
+                                let (offset, anim) = length(d).unwrap_or((0.0, None));
+                                shape.dash_offset = offset;
+                                if let Some(anim) = anim {
+                                    shape.animators.insert(DASH_OFFSET, anim);
                                 }
                             }
-                            "rp" => {
-                                repeater = parse_repeater(shape);
-                            }
-                            "tm" => {
-                                let s = shape
-                                    .get("s")
-                                    .and_then(|v| v.get("k"))
-                                    .and_then(Value::as_f64)
-                                    .unwrap_or(0.0) as f32
-                                    / 100.0;
-                                let e = shape
-                                    .get("e")
-                                    .and_then(|v| v.get("k"))
-                                    .and_then(Value::as_f64)
-                                    .unwrap_or(1.0) as f32
-                                    / 100.0;
-                                trim = Some((s, e));
-                            }
                             _ => {}
                         }
                     }
+                    shape.dash = (!pattern.is_empty()).then_some(pattern);
                 }
             }
-            if let Some((copies, tr)) = repeater {
-                let original = paths.clone();
-                for i in 1..copies {
-                    for cmds in &original {
-                        paths.push(apply_transform(cmds, &tr, i as f32));
+            "rp" => {
+                if let Some((copies, tr)) = parse_repeater(item) {
+                    let original = shape.paths[group_start..].to_vec();
+                    for i in 1..copies {
+                        for path in &original {
+                            shape
+                                .paths
+                                .push(path.map_points(|p| apply_point(p, &tr, i as f32)));
+                        }
                     }
                 }
             }
-            Some(Layer::Shape(ShapeLayer {
-                paths,
-                fill,
-                stroke,
-                stroke_width,
-                mask: None,
-                trim,
-                animators: HashMap::new(),
-                is_mask,
-                matte,
-            }))
-        }
-        0 => {
-            let ref_id = layer.get("refId").and_then(Value::as_str)?;
-            if let Some(asset) = assets.get(ref_id) {
-                if let Some(arr) = asset.get("layers").and_then(Value::as_array) {
-                    let comp = Composition {
-                        width,
-                        height,
-                        start_frame: 0,
-                        end_frame: 0,
-                        fps,
-                        layers: parse_layers(arr, assets, images, width, height, fps),
-                    };
-                    return Some(Layer::PreComp(PreCompLayer {
-                        comp: Box::new(comp),
-                    }));
-                }
+            "tm" => {
+                let value = |key| {
+                    item.get(key)
+                        .and_then(|v| v.get("k"))
+                        .and_then(Value::as_f64)
+                };
+                shape.trim = Some(trim_fractions(value("s"), value("e")));
+                // A paint only takes the operators listed before it, so a
+                // fill or stroke between the paths and the trim keeps its
+                // whole path. Paints listed ahead of every path are trimmed.
+                shape.trim_target = match (fill_below_paths, stroke_below_paths) {
+                    (false, false) => TrimTarget::FillAndStroke,
+                    (true, false) => TrimTarget::Stroke,
+                    (false, true) => TrimTarget::Fill,
+                    (true, true) => TrimTarget::None,
+                };
             }
-            None
-        }
-        2 => {
-            let ref_id = layer.get("refId").and_then(Value::as_str)?;
-            if let Some((w, h, data)) = images.get(ref_id).cloned() {
-                return Some(Layer::Image(ImageLayer {
-                    width: w,
-                    height: h,
-                    pixels: data,
-                }));
+            _ => {}
+        }
+        for group in &mut shape.blend_groups[blend_start..] {
+            match ty {
+                "fl" | "gf" | "st" => inherit_paint(group, item, opacity),
+                "tm" => inherit_trim(group, shape.trim, shape.trim_target),
+                _ => {}
             }
-            None
         }
-        _ => None,
     }
 }
 
+/// Apply a paint item listed after `group` in its parent's stack to the
+/// group and the blend groups nested in it.
+fn inherit_paint(group: &mut ShapeLayer, item: &Value, opacity: f32) {
+    collect_shape_items(std::slice::from_ref(item), group, opacity);
+    for nested in &mut group.blend_groups {
+        inherit_paint(nested, item, opacity);
+    }
+}
+
+/// Apply a trim listed after `group` in its parent's stack to the group and
+/// the blend groups nested in it.
+fn inherit_trim(group: &mut ShapeLayer, trim: Option<(f32, f32)>, target: TrimTarget) {
+    group.trim = trim;
+    group.trim_target = target;
+    for nested in &mut group.blend_groups {
+        inherit_trim(nested, trim, target);
+    }
+}
+
+/// Map a blend group's paths, gradient and nested blend groups through `m`.
+fn transform_group(group: &mut ShapeLayer, m: &Affine) {
+    for path in &mut group.paths {
+        *path = path.map_points(|p| m.transform_point(p));
+    }
+    group.fill_paint = group.fill_paint.as_ref().map(|paint| paint.transformed(m));
+    for nested in &mut group.blend_groups {
+        transform_group(nested, m);
+    }
+}
+
+/// Warn when a group's `"np"` disagrees with the items it actually lists.
+/// The count is informational only; traversal always walks `"it"`.
+fn check_group_count(group: &Value, items: &[Value]) {
+    let Some(np) = group.get("np").and_then(Value::as_u64) else {
+        return;
+    };
+    let count = items
+        .iter()
+        .filter(|i| i.get("ty").and_then(Value::as_str) != Some("tr"))
+        .count();
+    if np as usize != count {
+        log::warn!("shape group declares np={np} but lists {count} items");
+    }
+}
+
+/// Normalize trim start/end to fractions of the path length.
+///
+/// Lottie stores them in percent, but some tools write fractions. Values
+/// are taken as fractions only when both lie within `0..=1` and at least
+/// one has a fractional part; anything else is read as percent.
+fn trim_fractions(start: Option<f64>, end: Option<f64>) -> (f32, f32) {
+    let (s, e) = (start.unwrap_or(0.0), end.unwrap_or(100.0));
+    let in_unit = |v: f64| (0.0..=1.0).contains(&v);
+    let fractional = |v: f64| v.fract() != 0.0;
+    let scale = if in_unit(s) && in_unit(e) && (fractional(s) || fractional(e)) {
+        1.0
+    } else {
+        0.01
+    };
+    ((s * scale) as f32, (e * scale) as f32)
+}
+
+fn parse_precomp_layer(layer: &Value, ctx: &LoadContext, depth: usize) -> Option<PreCompLayer> {
+    let ref_id = layer.get("refId").and_then(Value::as_str)?;
+    if depth >= MAX_PRECOMP_DEPTH {
+        return None;
+    }
+    if let Some(asset) = ctx.assets.get(ref_id) {
+        if let Some(arr) = asset.get("layers").and_then(Value::as_array) {
+            let size = |key| layer.get(key).and_then(Value::as_u64).map(|v| v as u32);
+            let comp = Composition {
+                width: size("w").unwrap_or(ctx.width),
+                height: size("h").unwrap_or(ctx.height),
+                start_frame: ctx.start_frame,
+                end_frame: ctx.end_frame,
+                fps: ctx.fps,
+                pixel_aspect: ctx.pixel_aspect,
+                opacity: 1.0,
+                layers: parse_layers(arr, ctx, depth + 1),
+                color_controls: HashMap::new(),
+                markers: Vec::new(),
+            };
+            return Some(PreCompLayer {
+                comp: Box::new(comp),
+                index: layer_index(layer, "ind"),
+                parent: layer_index(layer, "parent"),
+                transform: layer.get("ks").map(parse_transform).unwrap_or_default(),
+                collapse_transform: layer.get("ct").and_then(Value::as_i64) == Some(1),
+                time_remap: layer.get("tm").and_then(parse_scalar_animator),
+                start_time: start_time(layer),
+            });
+        }
+    }
+    None
+}
+
+fn parse_image_layer(layer: &Value, ctx: &LoadContext) -> Option<ImageLayer> {
+    let ref_id = layer.get("refId").and_then(Value::as_str)?;
+    if let Some((w, h, data)) = ctx.image(ref_id).cloned() {
+        return Some(ImageLayer {
+            width: w,
+            height: h,
+            pixels: data,
+            transform: layer.get("ks").map(parse_transform).unwrap_or_default(),
+            time_remap: layer.get("tm").and_then(parse_scalar_animator),
+            start_time: start_time(layer),
+        });
+    }
+    None
+}
+
+/// Parse an image layer whose `refId` names a `"t": "seq"` asset. The
+/// sequence is that asset followed by every consecutive sequence asset.
+fn parse_image_sequence(layer: &Value, ctx: &LoadContext) -> Option<ImageSequenceLayer> {
+    let ref_id = layer.get("refId").and_then(Value::as_str)?;
+    let start = ctx.asset_order.iter().position(|a| a.id == ref_id)?;
+    let first = &ctx.asset_order[start];
+    if !first.sequence {
+        return None;
+    }
+    let frames: Vec<ImageLayer> = ctx.asset_order[start..]
+        .iter()
+        .map_while(|a| a.sequence.then(|| ctx.image(&a.id)).flatten())
+        .map(|(w, h, data)| ImageLayer {
+            width: *w,
+            height: *h,
+            pixels: data.clone(),
+            transform: Transform::default(),
+            time_remap: None,
+            start_time: 0.0,
+        })
+        .collect();
+    if frames.is_empty() {
+        return None;
+    }
+    Some(ImageSequenceLayer {
+        frames,
+        fps: first.fps.unwrap_or(ctx.fps),
+    })
+}
+
+/// Mask combine mode; lighten, darken and difference fall back to add.
+fn parse_mask_mode(mask: &Value) -> MaskMode {
+    match mask.get("mode").and_then(Value::as_str) {
+        Some("s") => MaskMode::Subtract,
+        Some("i") => MaskMode::Intersect,
+        Some("n") => MaskMode::None,
+        _ => MaskMode::Add,
+    }
+}
+
+/// Parse a shape property holding either a `d` path string or Lottie
+/// `v`/`i`/`o`/`c` vertex data, unwrapping a static `k` if present.
+fn parse_shape_data(prop: &Value) -> Option<Path> {
+    let data = prop.get("k").filter(|k| k.is_object()).unwrap_or(prop);
+    if let Some(d) = data.get("d").and_then(Value::as_str) {
+        return Some(parse_path(d));
+    }
+    let verts = data.get("v").and_then(Value::as_array)?;
+    let point = |arr: Option<&Vec<Value>>, idx: usize| -> Vec2 {
+        arr.and_then(|a| a.get(idx))
+            .and_then(Value::as_array)
+            .map(|p| Vec2 {
+                x: p.first().and_then(Value::as_f64).unwrap_or(0.0) as f32,
+                y: p.get(1).and_then(Value::as_f64).unwrap_or(0.0) as f32,
+            })
+            .unwrap_or_default()
+    };
+    let in_t = data.get("i").and_then(Value::as_array);
+    let out_t = data.get("o").and_then(Value::as_array);
+    let closed = data.get("c").and_then(Value::as_bool).unwrap_or(false);
+    let mut path = Path {
+        segments: Vec::with_capacity(verts.len() + 2),
+    };
+    let segment = |from: usize, to: usize| {
+        let p0 = point(Some(verts), from);
+        let p1 = point(Some(verts), to);
+        let o = point(out_t, from);
+        let i = point(in_t, to);
+        if o == Vec2::default() && i == Vec2::default() {
+            PathSeg::LineTo(p1)
+        } else {
+            PathSeg::Cubic(
+                Vec2 {
+                    x: p0.x + o.x,
+                    y: p0.y + o.y,
+                },
+                Vec2 {
+                    x: p1.x + i.x,
+                    y: p1.y + i.y,
+                },
+                p1,
+            )
+        }
+    };
+    if verts.is_empty() {
+        return Some(path);
+    }
+    path.move_to(point(Some(verts), 0));
+    for idx in 1..verts.len() {
+        path.segments.push(segment(idx - 1, idx));
+    }
+    if closed {
+        path.segments.push(segment(verts.len() - 1, 0));
+        path.close();
+    }
+    Some(path)
+}
+
+/// Control point distance, relative to the radius, of a quarter-circle cubic.
+const ELLIPSE_KAPPA: f32 = 0.5523;
+
+/// Closed four-cubic approximation of the ellipse `size` wide and high around
+/// `center`, starting at the top and running clockwise on screen.
+fn ellipse_path(center: Vec2, size: Vec2) -> Path {
+    let (rx, ry) = (size.x * 0.5, size.y * 0.5);
+    let (kx, ky) = (rx * ELLIPSE_KAPPA, ry * ELLIPSE_KAPPA);
+    let at = |dx: f32, dy: f32| Vec2 {
+        x: center.x + dx,
+        y: center.y + dy,
+    };
+    let mut path = Path::new();
+    path.move_to(at(0.0, -ry));
+    path.cubic_to(at(kx, -ry), at(rx, -ky), at(rx, 0.0));
+    path.cubic_to(at(rx, ky), at(kx, ry), at(0.0, ry));
+    path.cubic_to(at(-kx, ry), at(-rx, ky), at(-rx, 0.0));
+    path.cubic_to(at(-rx, -ky), at(-kx, -ry), at(0.0, -ry));
+    path.close();
+    path
+}
+
+/// Closed rectangle `size` wide and high around `center`, running clockwise
+/// on screen. Corners become quarter-ellipse cubics of `radius`, clamped to
+/// half the smaller side so opposite arcs never overlap.
+fn rect_path(center: Vec2, size: Vec2, radius: f32) -> Path {
+    let (hw, hh) = (size.x.abs() * 0.5, size.y.abs() * 0.5);
+    let r = radius.clamp(0.0, hw.min(hh));
+    let k = r * ELLIPSE_KAPPA;
+    let at = |dx: f32, dy: f32| Vec2 {
+        x: center.x + dx,
+        y: center.y + dy,
+    };
+    let mut path = Path::new();
+    path.move_to(at(-hw + r, -hh));
+    path.line_to(at(hw - r, -hh));
+    if r > 0.0 {
+        path.cubic_to(at(hw - r + k, -hh), at(hw, -hh + r - k), at(hw, -hh + r));
+    }
+    path.line_to(at(hw, hh - r));
+    if r > 0.0 {
+        path.cubic_to(at(hw, hh - r + k), at(hw - r + k, hh), at(hw - r, hh));
+    }
+    path.line_to(at(-hw + r, hh));
+    if r > 0.0 {
+        path.cubic_to(at(-hw + r - k, hh), at(-hw, hh - r + k), at(-hw, hh - r));
+    }
+    path.line_to(at(-hw, -hh + r));
+    if r > 0.0 {
+        path.cubic_to(at(-hw, -hh + r - k), at(-hw + r - k, -hh), at(-hw + r, -hh));
+    }
+    path.close();
+    path
+}
+
+/// Closed polygon with `points` outer vertices at `outer` from `center`,
+/// alternating with vertices at `inner` for a star. The first outer vertex
+/// points up, turned clockwise by `rotation` degrees.
+fn polystar_path(
+    center: Vec2,
+    points: usize,
+    outer: f32,
+    inner: Option<f32>,
+    rotation: f32,
+) -> Path {
+    let radii: &[f32] = match &inner {
+        Some(inner) => &[outer, *inner],
+        None => &[outer],
+    };
+    let count = points * radii.len();
+    let step = std::f32::consts::TAU / count as f32;
+    let mut path = Path::new();
+    for i in 0..count {
+        let angle = (rotation - 90.0).to_radians() + step * i as f32;
+        let r = radii[i % radii.len()];
+        let p = Vec2 {
+            x: center.x + r * angle.cos(),
+            y: center.y + r * angle.sin(),
+        };
+        if i == 0 {
+            path.move_to(p);
+        } else {
+            path.line_to(p);
+        }
+    }
+    path.close();
+    path
+}
+
 /// Parse a simple path string using m/l/c/o verbs.
-fn parse_path(data: &str) -> Vec<PathCommand> {
-    let mut cmds = Vec::new();
+fn parse_path(data: &str) -> Path {
+    let mut path = Path::new();
     let mut it = data.split_whitespace();
     while let Some(tok) = it.next() {
         match tok {
             "m" => {
                 let x: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
                 let y: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
-                cmds.push(PathCommand::MoveTo(Vec2 { x, y }));
+                path.move_to(Vec2 { x, y });
             }
             "l" => {
                 let x: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
                 let y: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
-                cmds.push(PathCommand::LineTo(Vec2 { x, y }));
+                path.line_to(Vec2 { x, y });
             }
             "c" => {
                 let x1: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
@@ -250,41 +1102,294 @@ fn parse_path(data: &str) -> Vec<PathCommand> {
                 let y2: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
                 let x: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
                 let y: f32 = it.next().unwrap_or("0").parse().unwrap_or(0.0);
-                cmds.push(PathCommand::CubicTo(
-                    Vec2 { x: x1, y: y1 },
-                    Vec2 { x: x2, y: y2 },
-                    Vec2 { x, y },
-                ));
+                path.cubic_to(Vec2 { x: x1, y: y1 }, Vec2 { x: x2, y: y2 }, Vec2 { x, y });
             }
-            "o" => cmds.push(PathCommand::Close),
+            "o" => path.close(),
             _ => {}
         }
     }
-    cmds
+    path
+}
+
+/// Parse a keyframed scalar property (`"a": 1`) into an [`Animator`].
+///
+/// Each keyframe runs from its own `"t"` to the next one's, easing with the
+/// `"o"`/`"i"` tangents. The end value is the explicit `"e"` when present,
+/// otherwise the following keyframe's `"s"`.
+fn parse_scalar_animator(prop: &Value) -> Option<Animator<f32>> {
+    parse_component_animator(prop, 0)
+}
+
+/// Like [`parse_scalar_animator`], animating component `component` of a
+/// multi-dimensional property such as a position.
+fn parse_component_animator(prop: &Value, component: usize) -> Option<Animator<f32>> {
+    let keys = prop.get("k").and_then(Value::as_array)?;
+    let value = |v: Option<&Value>| -> Option<f32> {
+        match v? {
+            Value::Array(a) => a.get(component).and_then(Value::as_f64),
+            other => other.as_f64(),
+        }
+        .map(|f| f as f32)
+    };
+    let scalar = |v: Option<&Value>| -> Option<f32> {
+        match v? {
+            Value::Array(a) => a.first().and_then(Value::as_f64),
+            other => other.as_f64(),
+        }
+        .map(|f| f as f32)
+    };
+    let tangent = |v: Option<&Value>, default: Vec2| -> Vec2 {
+        v.map(|t| Vec2 {
+            x: scalar(t.get("x")).unwrap_or(default.x),
+            y: scalar(t.get("y")).unwrap_or(default.y),
+        })
+        .unwrap_or(default)
+    };
+    let mut frames = Vec::new();
+    for (i, key) in keys.iter().enumerate() {
+        let Some(next) = keys.get(i + 1) else { break };
+        let start = key.get("t").and_then(Value::as_f64)?.max(0.0) as u32;
+        let end = next.get("t").and_then(Value::as_f64)?.max(0.0) as u32;
+        let start_v = value(key.get("s"))?;
+        let end_v = value(key.get("e"))
+            .or_else(|| value(next.get("s")))
+            .unwrap_or(start_v);
+        let ease = CubicBezier::new(
+            tangent(key.get("o"), Vec2 { x: 0.0, y: 0.0 }),
+            tangent(key.get("i"), Vec2 { x: 1.0, y: 1.0 }),
+        );
+        frames.push(Keyframe {
+            start,
+            end,
+            start_v,
+            end_v,
+            ease,
+            hold: key.get("h").and_then(Value::as_i64) == Some(1),
+        });
+    }
+    if frames.is_empty() {
+        None
+    } else {
+        Some(Animator { frames })
+    }
+}
+
+/// Keyframes of an animated `[r, g, b, a]` color property. Channels are
+/// eased together, taking the timing of the red channel; a missing alpha
+/// is opaque.
+fn parse_color_animator(prop: &Value) -> Option<Animator<Color>> {
+    let [r, g, b] = [0, 1, 2].map(|i| parse_component_animator(prop, i));
+    let (r, g, b) = (r?, g?, b?);
+    let a = parse_component_animator(prop, 3);
+    let color = |i: usize, end: bool| {
+        let channel = |anim: &Animator<f32>, default: f32| {
+            let v = anim
+                .frames
+                .get(i)
+                .map_or(default, |k| if end { k.end_v } else { k.start_v });
+            (v.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+        Color {
+            r: channel(&r, 0.0),
+            g: channel(&g, 0.0),
+            b: channel(&b, 0.0),
+            a: a.as_ref().map_or(255, |a| channel(a, 1.0)),
+        }
+    };
+    let frames = r
+        .frames
+        .iter()
+        .enumerate()
+        .map(|(i, key)| Keyframe {
+            start: key.start,
+            end: key.end,
+            start_v: color(i, false),
+            end_v: color(i, true),
+            ease: key.ease.clone(),
+            hold: key.hold,
+        })
+        .collect();
+    Some(Animator { frames })
 }
 
 fn parse_color(obj: &Value) -> Option<Color> {
-    if let Some(arr) = obj
-        .get("c")
-        .and_then(|c| c.get("k"))
-        .and_then(Value::as_array)
-    {
+    obj.get("c").and_then(parse_color_prop)
+}
+
+/// Gradient of a `gf` item: `t` 1 is linear from `s` to `e`, 2 is radial
+/// around `s` reaching `e`, focused by the `h` highlight length and `a`
+/// angle. The `g` stops hold `p` groups of offset and RGB, optionally
+/// followed by offset and opacity pairs that become the gradient's opacity
+/// stops.
+fn parse_gradient(item: &Value) -> Option<Paint> {
+    let start = static_vec2(item.get("s"))?;
+    let end = static_vec2(item.get("e"))?;
+    let g = item.get("g")?;
+    let count = g.get("p").and_then(Value::as_u64)? as usize;
+    let values: Vec<f32> = g
+        .get("k")
+        .and_then(|k| k.get("k"))
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(|v| v.as_f64().map(|v| v as f32))
+        .collect();
+    if count == 0 || values.len() < count * 4 {
+        return None;
+    }
+    let (colors, opacities) = values.split_at(count * 4);
+    let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut stops: Vec<GradientStop> = colors
+        .chunks_exact(4)
+        .map(|c| GradientStop {
+            offset: c[0],
+            color: Color {
+                r: channel(c[1]),
+                g: channel(c[2]),
+                b: channel(c[3]),
+                a: 255,
+            },
+        })
+        .collect();
+    stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    let mut opacity_stops: Vec<OpacityStop> = opacities
+        .chunks_exact(2)
+        .map(|o| OpacityStop {
+            offset: o[0],
+            opacity: o[1].clamp(0.0, 1.0),
+        })
+        .collect();
+    opacity_stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    match item.get("t").and_then(Value::as_u64) {
+        Some(2) => {
+            let (dx, dy) = (end.x - start.x, end.y - start.y);
+            let radius = (dx * dx + dy * dy).sqrt();
+            // Highlight length is a percentage of the radius, kept inside
+            // the circle; its angle turns away from the start-end line.
+            let length = static_f32(item.get("h")).unwrap_or(0.0).clamp(-99.0, 99.0) / 100.0;
+            let angle = dy.atan2(dx) + static_f32(item.get("a")).unwrap_or(0.0).to_radians();
+            let focus = (length != 0.0).then(|| Vec2 {
+                x: start.x + angle.cos() * length * radius,
+                y: start.y + angle.sin() * length * radius,
+            });
+            Some(Paint::Radial(RadialGradient {
+                center: start,
+                radius,
+                focus,
+                stops,
+                opacity_stops,
+                space: ColorSpace::default(),
+            }))
+        }
+        _ => Some(Paint::Linear(LinearGradient {
+            start,
+            end,
+            stops,
+            opacity_stops,
+            space: ColorSpace::default(),
+        })),
+    }
+}
+
+/// `color` with its alpha scaled by `opacity`.
+fn fade(color: Color, opacity: f32) -> Color {
+    Color {
+        a: (color.a as f32 * opacity).round() as u8,
+        ..color
+    }
+}
+
+/// Read a static `[r, g, b, a]` color from a property's `"k"`.
+fn parse_color_prop(prop: &Value) -> Option<Color> {
+    if let Some(arr) = prop.get("k").and_then(Value::as_array) {
         if arr.len() >= 4 {
             let r = arr[0].as_f64().unwrap_or(0.0);
             let g = arr[1].as_f64().unwrap_or(0.0);
             let b = arr[2].as_f64().unwrap_or(0.0);
             let a = arr[3].as_f64().unwrap_or(1.0);
             return Some(Color {
-                r: (r * 255.0) as u8,
-                g: (g * 255.0) as u8,
-                b: (b * 255.0) as u8,
-                a: (a * 255.0) as u8,
+                r: (r.clamp(0.0, 1.0) * 255.0).round() as u8,
+                g: (g.clamp(0.0, 1.0) * 255.0).round() as u8,
+                b: (b.clamp(0.0, 1.0) * 255.0).round() as u8,
+                a: (a.clamp(0.0, 1.0) * 255.0).round() as u8,
             });
         }
     }
     None
 }
 
+/// "Color Control" effects (`"ADBE Color Control"`) on any of `layers`,
+/// keyed by effect name. Later layers win on duplicate names.
+fn parse_color_controls(layers: &[Value]) -> HashMap<String, Color> {
+    let mut controls = HashMap::new();
+    let effects = layers
+        .iter()
+        .filter_map(|l| l.get("ef").and_then(Value::as_array))
+        .flatten();
+    for effect in effects {
+        if effect.get("mn").and_then(Value::as_str) != Some("ADBE Color Control") {
+            continue;
+        }
+        let name = effect.get("nm").and_then(Value::as_str);
+        let color = effect
+            .get("ef")
+            .and_then(Value::as_array)
+            .and_then(|params| {
+                params
+                    .iter()
+                    .find(|p| p.get("ty").and_then(Value::as_i64) == Some(2))
+            })
+            .and_then(|p| p.get("v"))
+            .and_then(parse_color_prop);
+        if let (Some(name), Some(color)) = (name, color) {
+            controls.insert(name.to_owned(), color);
+        }
+    }
+    controls
+}
+
+/// Name of the effect an expression reads, from its first
+/// `effect('Name')` call; either quote style is accepted.
+pub(crate) fn effect_reference(expr: &str) -> Option<&str> {
+    let rest = &expr[expr.find("effect(")? + "effect(".len()..];
+    let quote = rest.chars().next().filter(|&q| q == '\'' || q == '"')?;
+    let rest = &rest[1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+/// Parse one entry of a layer's `"ef"` array. Effect parameters are read by
+/// position within the entry's own `"ef"` list, as exporters emit them.
+/// Unsupported and disabled effects yield `None`.
+fn parse_effect(effect: &Value) -> Option<LayerEffect> {
+    if effect.get("en").and_then(Value::as_i64) == Some(0) {
+        return None;
+    }
+    let params = effect.get("ef").and_then(Value::as_array)?;
+    let value = |i: usize| params.get(i).and_then(|p| p.get("v"));
+    let scalar = |i: usize| {
+        value(i)
+            .and_then(|v| v.get("k"))
+            .and_then(Value::as_f64)
+            .map(|k| k as f32)
+    };
+    let color = |i: usize| value(i).and_then(parse_color_prop);
+    match effect.get("ty").and_then(Value::as_i64)? {
+        20 => Some(LayerEffect::Tint {
+            black: color(0)?,
+            white: color(1)?,
+            amount: scalar(2).unwrap_or(100.0) / 100.0,
+        }),
+        25 => Some(LayerEffect::DropShadow {
+            color: color(0)?,
+            opacity: scalar(1).unwrap_or(128.0) / 255.0,
+            direction: scalar(2).unwrap_or(135.0),
+            distance: scalar(3).unwrap_or(5.0),
+            softness: scalar(4).unwrap_or(0.0),
+        }),
+        29 => Some(LayerEffect::GaussianBlur { radius: scalar(0)? }),
+        _ => None,
+    }
+}
+
 fn parse_repeater(obj: &Value) -> Option<(u32, Transform)> {
     let copies = obj
         .get("c")
@@ -294,64 +1399,175 @@ fn parse_repeater(obj: &Value) -> Option<(u32, Transform)> {
     if copies <= 1 {
         return None;
     }
+    let tr = obj.get("tr").map(parse_transform).unwrap_or_default();
+    Some((copies, tr))
+}
+
+/// Parse the static position, scale, rotation, anchor and opacity of a
+/// `"tr"` or `"ks"` object. Animated properties keep their defaults.
+fn parse_transform(t: &Value) -> Transform {
     let mut tr = Transform::default();
-    if let Some(t) = obj.get("tr") {
-        if let Some(p) = t
-            .get("p")
-            .and_then(|k| k.get("k"))
-            .and_then(Value::as_array)
-        {
-            if p.len() >= 2 {
-                tr.position = Vec2 {
-                    x: p[0].as_f64().unwrap_or(0.0) as f32,
-                    y: p[1].as_f64().unwrap_or(0.0) as f32,
-                };
+    if let Some(p) = static_vec2(t.get("p")) {
+        tr.position = p;
+    }
+    if let Some(s) = static_vec2(t.get("s")) {
+        tr.scale = Vec2 {
+            x: s.x / 100.0,
+            y: s.y / 100.0,
+        };
+    }
+    if let Some(r) = t.get("r").and_then(|k| k.get("k")).and_then(Value::as_f64) {
+        tr.rotation = r as f32;
+    }
+    if let Some(p) = t.get("p").filter(|p| is_animated(p)) {
+        for (key, component) in [(POSITION_X, 0), (POSITION_Y, 1)] {
+            if let Some(anim) = parse_component_animator(p, component) {
+                tr.animators.insert(key, anim);
             }
         }
-        if let Some(s) = t
-            .get("s")
-            .and_then(|k| k.get("k"))
-            .and_then(Value::as_array)
-        {
-            if s.len() >= 2 {
-                tr.scale = Vec2 {
-                    x: s[0].as_f64().unwrap_or(100.0) as f32 / 100.0,
-                    y: s[1].as_f64().unwrap_or(100.0) as f32 / 100.0,
-                };
+        tr.motion = parse_motion(p, &tr.animators);
+    }
+    // Separated dimensions keep x and y as properties of their own.
+    if let Some(p) = t
+        .get("p")
+        .filter(|p| p.get("s").and_then(Value::as_bool) == Some(true))
+    {
+        for (key, axis, value) in [
+            (POSITION_X, "x", &mut tr.position.x),
+            (POSITION_Y, "y", &mut tr.position.y),
+        ] {
+            let Some(prop) = p.get(axis) else { continue };
+            if let Some(v) = static_f32(Some(prop)) {
+                *value = v;
+            }
+            if let Some(anim) = Some(prop)
+                .filter(|prop| is_animated(prop))
+                .and_then(parse_scalar_animator)
+            {
+                *value = anim.value(0.0);
+                tr.animators.insert(key, anim);
             }
         }
-        if let Some(r) = t.get("r").and_then(|k| k.get("k")).and_then(Value::as_f64) {
-            tr.rotation = r as f32;
+    }
+    if let Some(s) = t.get("s").filter(|s| is_animated(s)) {
+        for (key, component) in [(SCALE_X, 0), (SCALE_Y, 1)] {
+            if let Some(mut anim) = parse_component_animator(s, component) {
+                // Lottie scale is a percentage.
+                for frame in &mut anim.frames {
+                    frame.start_v /= 100.0;
+                    frame.end_v /= 100.0;
+                }
+                tr.animators.insert(key, anim);
+            }
         }
-        if let Some(a) = t
-            .get("a")
-            .and_then(|k| k.get("k"))
-            .and_then(Value::as_array)
-        {
-            if a.len() >= 2 {
-                tr.anchor = Vec2 {
-                    x: a[0].as_f64().unwrap_or(0.0) as f32,
-                    y: a[1].as_f64().unwrap_or(0.0) as f32,
-                };
+        tr.scale = tr.at(0.0).scale;
+    }
+    if let Some(anim) = t
+        .get("r")
+        .filter(|r| is_animated(r))
+        .and_then(parse_scalar_animator)
+    {
+        tr.animators.insert(ROTATION, anim);
+    }
+    if let Some(a) = static_vec2(t.get("a")) {
+        tr.anchor = a;
+    }
+    if let Some(a) = t.get("a").filter(|a| is_animated(a)) {
+        for (key, component) in [(ANCHOR_X, 0), (ANCHOR_Y, 1)] {
+            if let Some(anim) = parse_component_animator(a, component) {
+                tr.animators.insert(key, anim);
             }
         }
     }
-    Some((copies, tr))
+    if let Some(o) = t.get("o").and_then(|k| k.get("k")).and_then(Value::as_f64) {
+        tr.opacity = (o as f32 / 100.0).clamp(0.0, 1.0);
+    }
+    if let Some(mut anim) = t
+        .get("o")
+        .filter(|o| is_animated(o))
+        .and_then(parse_scalar_animator)
+    {
+        // Lottie opacity is a percentage.
+        for key in &mut anim.frames {
+            key.start_v /= 100.0;
+            key.end_v /= 100.0;
+        }
+        tr.opacity = anim.value(0.0);
+        tr.animators.insert(OPACITY, anim);
+    }
+    tr
 }
 
-fn apply_transform(cmds: &[PathCommand], tr: &Transform, idx: f32) -> Vec<PathCommand> {
-    cmds.iter()
-        .map(|c| match *c {
-            PathCommand::MoveTo(p) => PathCommand::MoveTo(apply_point(p, tr, idx)),
-            PathCommand::LineTo(p) => PathCommand::LineTo(apply_point(p, tr, idx)),
-            PathCommand::CubicTo(c1, c2, p) => PathCommand::CubicTo(
-                apply_point(c1, tr, idx),
-                apply_point(c2, tr, idx),
-                apply_point(p, tr, idx),
-            ),
-            PathCommand::Close => PathCommand::Close,
+/// Curved position keyframes from the `"to"`/`"ti"` tangents of `prop`,
+/// timed like the already parsed component animators. Empty when every
+/// tangent is zero or absent, leaving the straight component lerp in charge.
+fn parse_motion(prop: &Value, animators: &HashMap<&str, Animator<f32>>) -> Vec<SpatialKeyframe> {
+    let (Some(keys), Some(xs), Some(ys)) = (
+        prop.get("k").and_then(Value::as_array),
+        animators.get(POSITION_X),
+        animators.get(POSITION_Y),
+    ) else {
+        return Vec::new();
+    };
+    let tangent = |key: &Value, name: &str| key.get(name).and_then(vec2_array).unwrap_or_default();
+    let motion: Vec<SpatialKeyframe> = keys
+        .iter()
+        .zip(xs.frames.iter().zip(&ys.frames))
+        .map(|(key, (x, y))| SpatialKeyframe {
+            key: Keyframe {
+                start: x.start,
+                end: x.end,
+                start_v: Vec2 {
+                    x: x.start_v,
+                    y: y.start_v,
+                },
+                end_v: Vec2 {
+                    x: x.end_v,
+                    y: y.end_v,
+                },
+                ease: x.ease.clone(),
+                hold: x.hold,
+            },
+            out_tangent: tangent(key, "to"),
+            in_tangent: tangent(key, "ti"),
         })
-        .collect()
+        .collect();
+    if motion.iter().all(SpatialKeyframe::is_linear) {
+        return Vec::new();
+    }
+    motion
+}
+
+/// Whether a property is keyframed: `"a": 1`, or a `"k"` list of keyframes.
+fn is_animated(prop: &Value) -> bool {
+    prop.get("a").and_then(Value::as_i64) == Some(1)
+        || prop
+            .get("k")
+            .and_then(Value::as_array)
+            .and_then(|k| k.first())
+            .is_some_and(Value::is_object)
+}
+
+/// Read a static 2D value from a property whose `"k"` is a bare number array.
+fn static_vec2(prop: Option<&Value>) -> Option<Vec2> {
+    vec2_array(prop?.get("k")?)
+}
+
+/// Read a static number from a property whose `"k"` is a bare number.
+fn static_f32(prop: Option<&Value>) -> Option<f32> {
+    prop?.get("k")?.as_f64().map(|v| v as f32)
+}
+
+/// Read a `[x, y, ...]` number array.
+fn vec2_array(v: &Value) -> Option<Vec2> {
+    let k = v.as_array()?;
+    match (k.first()?.as_f64(), k.get(1)?.as_f64()) {
+        (Some(x), Some(y)) => Some(Vec2 {
+            x: x as f32,
+            y: y as f32,
+        }),
+        _ => None,
+    }
 }
 
 fn apply_point(p: Vec2, tr: &Transform, idx: f32) -> Vec2 {
@@ -398,6 +1614,71 @@ mod tests {
         assert_eq!(from_reader_comp.layers.len(), from_slice_comp.layers.len());
     }
 
+    #[test]
+    fn from_slice_multi_reads_every_composition() {
+        let array = br#"[
+            {"w":16,"h":8,"fr":30,"ip":0,"op":10,"layers":[]},
+            {"w":4,"h":12,"fr":24,"ip":0,"op":5,"layers":[]}
+        ]"#;
+        let comps = from_slice_multi(array).unwrap();
+        let sizes: Vec<_> = comps.iter().map(|c| (c.width, c.height)).collect();
+        assert_eq!(sizes, vec![(16, 8), (4, 12)]);
+
+        let manifest = br#"{"animations":[{"w":2,"h":3,"layers":[]}]}"#;
+        assert_eq!(from_slice_multi(manifest).unwrap()[0].height, 3);
+        let single = br#"{"w":5,"h":6,"layers":[]}"#;
+        assert_eq!(from_slice_multi(single).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn zero_position_tangents_fall_back_to_lerp() {
+        let ks = |tangents: &str| -> Transform {
+            let json = format!(
+                r#"{{"p":{{"a":1,"k":[
+                    {{"t":0,"s":[0,0],"o":{{"x":0,"y":0}},"i":{{"x":1,"y":1}}{tangents}}},
+                    {{"t":10,"s":[30,10]}}]}}}}"#
+            );
+            parse_transform(&serde_json::from_str(&json).unwrap())
+        };
+        let plain = ks("");
+        let zero = ks(r#","to":[0,0],"ti":[0,0]"#);
+        let curved = ks(r#","to":[0,20],"ti":[0,0]"#);
+        assert!(zero.motion.is_empty());
+        assert_eq!(curved.motion.len(), 1);
+        for f in 0..=10 {
+            let p = zero.at(f as f32).position;
+            assert_eq!(p, plain.at(f as f32).position);
+            assert!((p.y * 3.0 - p.x).abs() < 1e-4, "{p:?} off the line");
+        }
+        let mid = curved.at(5.0).position;
+        assert!(mid.y * 3.0 - mid.x > 1.0, "{mid:?}");
+        assert_eq!(curved.at(10.0).position, Vec2 { x: 30.0, y: 10.0 });
+    }
+
+    #[test]
+    fn shape_parses_into_path_segments() {
+        let json = br#"{"w":8,"h":8,"layers":[{"ty":4,"shapes":[{"ty":"sh","ks":{"k":{
+            "v":[[0,0],[4,0],[4,4]],
+            "i":[[0,0],[0,0],[0,-2]],
+            "o":[[0,0],[0,0],[0,0]],
+            "c":true}}}]}]}"#;
+        let comp = from_slice(json).unwrap();
+        let Layer::Shape(shape) = &comp.layers[0] else {
+            panic!("expected shape layer");
+        };
+        let v = |x, y| Vec2 { x, y };
+        assert_eq!(
+            shape.paths[0].segments,
+            vec![
+                PathSeg::MoveTo(v(0.0, 0.0)),
+                PathSeg::LineTo(v(4.0, 0.0)),
+                PathSeg::Cubic(v(4.0, 0.0), v(4.0, 2.0), v(4.0, 4.0)),
+                PathSeg::LineTo(v(0.0, 0.0)),
+                PathSeg::Close,
+            ]
+        );
+    }
+
     #[test]
     fn parse_fill_stroke() {
         let path =
@@ -412,6 +1693,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn color_channels_round_like_gradients() {
+        let prop = serde_json::json!({"k": [0.5, 0.2, 1.0, 0.5]});
+        let half = Color {
+            r: 128,
+            g: 51,
+            b: 255,
+            a: 128,
+        };
+        assert_eq!(parse_color_prop(&prop), Some(half));
+        let keyed = serde_json::json!({"a": 1, "k": [
+            {"t": 0, "s": [0.5, 0.2, 1.0, 0.5]},
+            {"t": 10, "s": [0.5, 0.2, 1.0, 0.5]}
+        ]});
+        let anim = parse_color_animator(&keyed).unwrap();
+        assert_eq!(anim.value(0.0), half);
+    }
+
     #[test]
     fn parse_repeater() {
         let path =
@@ -439,4 +1738,42 @@ mod tests {
             panic!("expected image layer");
         }
     }
+
+    fn load_fixture(name: &str) -> Composition {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/data")
+            .join(name);
+        from_reader(File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn unified_path_shape_fields() {
+        let comp = load_fixture("mask.json");
+        let (Layer::Shape(src), Layer::Shape(dst)) = (&comp.layers[0], &comp.layers[1]) else {
+            panic!("expected shape layers");
+        };
+        assert!(src.is_mask);
+        assert_eq!(dst.matte, Some(MatteType::Alpha));
+        assert!(dst.fill.is_some());
+
+        let comp = load_fixture("trim.json");
+        let Layer::Shape(shape) = &comp.layers[0] else {
+            panic!("expected shape layer");
+        };
+        assert!(shape.trim.is_some());
+        assert!(shape.stroke.is_some());
+    }
+
+    #[test]
+    fn unified_path_precomp_and_image() {
+        let comp = load_fixture("precomp.json");
+        let Layer::PreComp(pre) = &comp.layers[0] else {
+            panic!("expected precomp layer");
+        };
+        assert_eq!(pre.comp.width, comp.width);
+        assert!(matches!(pre.comp.layers[0], Layer::Shape(_)));
+
+        let comp = load_fixture("image_embedded.json");
+        assert!(matches!(comp.layers[0], Layer::Image(_)));
+    }
 }
@@ -3,5 +3,7 @@
 //! Module: load handlers
 //! Mirrors: rlottie/src/lottie.cpp
 
+pub mod inspect;
 pub mod json;
+pub use inspect::*;
 pub use json::*;
@@ -3,11 +3,22 @@
 //! Module: software rasterizer
 //! Mirrors: rlottie/src/vector/vpainter.cpp (simplified)
 
-use crate::geometry::{tessellate, Path};
-use crate::types::TextLayer;
-use crate::types::{Color, GradientStop, LinearGradient, MatteType, Paint, RadialGradient, Vec2};
+#[cfg(any(test, feature = "bench"))]
+use crate::geometry::tessellate;
+use crate::geometry::{
+    polyline_joins, polyline_outline, polyline_segments, polylines, Path, Polyline,
+};
+use crate::timeline::Lerp;
+use crate::types::{
+    Affine, BlendMode, Color, ColorSpace, FillRule, GradientStop, LineCap, LinearGradient,
+    MatteType, OpacityStop, Paint, RadialGradient, StrokeStyle, Vec2,
+};
+use crate::types::{ImageLayer, TextLayer};
 
 /// Fill a path with the given paint into the RGBA8888 buffer.
+///
+/// Sub-paths are filled together under the non-zero rule in one scanline
+/// pass, so each covered pixel is blended exactly once.
 pub fn draw_path(
     path: &Path,
     paint: Paint,
@@ -16,10 +27,33 @@ pub fn draw_path(
     height: usize,
     stride: usize,
 ) {
-    let mesh = tessellate(path, 0.2, None);
-    if let Paint::Solid(_) = paint {
-        // solid fill handled; other paints use sampling
+    draw_fill(
+        path,
+        FillRule::NonZero,
+        paint,
+        buffer,
+        width,
+        height,
+        stride,
+    );
+}
+
+/// The per-triangle fill [`draw_path`] replaced: tessellate, then test every
+/// pixel in each triangle's bounding box. Kept to benchmark against.
+#[cfg(any(test, feature = "bench"))]
+#[doc(hidden)]
+pub fn draw_path_triangles(
+    path: &Path,
+    paint: Paint,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    if let Paint::None = paint {
+        return;
     }
+    let mesh = tessellate(path, 0.2, None, FillRule::NonZero);
     for tri in mesh.indices.chunks(3) {
         if tri.len() < 3 {
             continue;
@@ -27,49 +61,105 @@ pub fn draw_path(
         let v0 = mesh.vertices[tri[0] as usize];
         let v1 = mesh.vertices[tri[1] as usize];
         let v2 = mesh.vertices[tri[2] as usize];
-        fill_triangle_paint(v0, v1, v2, &paint, buffer, width, height, stride);
+        fill_triangle_paint(
+            v0, v1, v2, [false; 3], &paint, None, buffer, width, height, stride,
+        );
+    }
+}
+
+/// Fill all sub-paths of `path` together as one compound shape, so that
+/// overlaps are painted once and holes follow `rule`.
+pub fn draw_fill(
+    path: &Path,
+    rule: FillRule,
+    paint: Paint,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    fill_path(path, rule, &paint, None, buffer, width, height, stride);
+}
+
+/// Compound fill as in [`draw_fill`], limited to pixels where `mask` is set.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_fill_masked(
+    path: &Path,
+    rule: FillRule,
+    paint: Paint,
+    mask: &[u8],
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    fill_path(
+        path,
+        rule,
+        &paint,
+        Some(mask),
+        buffer,
+        width,
+        height,
+        stride,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fill_path(
+    path: &Path,
+    rule: FillRule,
+    paint: &Paint,
+    mask: Option<&[u8]>,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    if let Paint::None = paint {
+        return;
     }
+    fill_spans(path, rule, width, height, |x, y, coverage| {
+        if !mask_allows(mask, stride, x, y) {
+            return;
+        }
+        let mut color = sample_paint(
+            paint,
+            Vec2 {
+                x: x as f32 + 0.5,
+                y: y as f32 + 0.5,
+            },
+        );
+        color.a = quantize(color.a as f32 * coverage);
+        blend_pixel(buffer, stride, x, y, color);
+    });
+}
+
+/// Whether `mask`, laid out like the target buffer, lets pixel `x`,`y`
+/// be drawn. No mask allows every pixel.
+fn mask_allows(mask: Option<&[u8]>, stride: usize, x: usize, y: usize) -> bool {
+    mask.is_none_or(|mask| mask.get(y * stride + x * 4 + 3).is_some_and(|&m| m != 0))
 }
 
-/// Stroke a path with the given paint and width.
+/// Stroke a path with the given paint, width and style.
+///
+/// Butt-capped thin strokes are drawn as one quad per segment with the
+/// join wedges filled between them; any other cap, or a stroke wider than
+/// its segments are long, fills the whole stroke outline instead.
+#[allow(clippy::too_many_arguments)]
 pub fn draw_stroke(
     path: &Path,
     width_px: f32,
+    style: StrokeStyle,
     paint: Paint,
     buffer: &mut [u8],
     width: usize,
     height: usize,
     stride: usize,
 ) {
-    let segs = path.flatten(0.2);
-    for seg in segs {
-        let dx = seg.to.x - seg.from.x;
-        let dy = seg.to.y - seg.from.y;
-        let len = (dx * dx + dy * dy).sqrt();
-        if len == 0.0 {
-            continue;
-        }
-        let nx = -dy / len * width_px * 0.5;
-        let ny = dx / len * width_px * 0.5;
-        let p1 = Vec2 {
-            x: seg.from.x + nx,
-            y: seg.from.y + ny,
-        };
-        let p2 = Vec2 {
-            x: seg.from.x - nx,
-            y: seg.from.y - ny,
-        };
-        let p3 = Vec2 {
-            x: seg.to.x - nx,
-            y: seg.to.y - ny,
-        };
-        let p4 = Vec2 {
-            x: seg.to.x + nx,
-            y: seg.to.y + ny,
-        };
-        fill_triangle_paint(p1, p2, p3, &paint, buffer, width, height, stride);
-        fill_triangle_paint(p1, p3, p4, &paint, buffer, width, height, stride);
-    }
+    stroke_path(
+        path, width_px, style, &paint, None, buffer, width, height, stride,
+    );
 }
 
 /// Fill a path applying a binary mask buffer where non-zero values allow drawing.
@@ -82,19 +172,16 @@ pub fn draw_path_masked(
     height: usize,
     stride: usize,
 ) {
-    let mesh = tessellate(path, 0.2, None);
-    let Paint::Solid(color) = paint else {
-        return;
-    };
-    for tri in mesh.indices.chunks(3) {
-        if tri.len() < 3 {
-            continue;
-        }
-        let v0 = mesh.vertices[tri[0] as usize];
-        let v1 = mesh.vertices[tri[1] as usize];
-        let v2 = mesh.vertices[tri[2] as usize];
-        fill_triangle_masked(v0, v1, v2, color, mask, buffer, width, height, stride);
-    }
+    draw_fill_masked(
+        path,
+        FillRule::NonZero,
+        paint,
+        mask,
+        buffer,
+        width,
+        height,
+        stride,
+    );
 }
 
 /// Stroke a path applying a mask buffer.
@@ -102,6 +189,7 @@ pub fn draw_path_masked(
 pub fn draw_stroke_masked(
     path: &Path,
     width_px: f32,
+    style: StrokeStyle,
     paint: Paint,
     mask: &[u8],
     buffer: &mut [u8],
@@ -109,13 +197,53 @@ pub fn draw_stroke_masked(
     height: usize,
     stride: usize,
 ) {
-    let segs = path.flatten(0.2);
-    let Paint::Solid(color) = paint else {
+    stroke_path(
+        path,
+        width_px,
+        style,
+        &paint,
+        Some(mask),
+        buffer,
+        width,
+        height,
+        stride,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn stroke_path(
+    path: &Path,
+    width_px: f32,
+    style: StrokeStyle,
+    paint: &Paint,
+    mask: Option<&[u8]>,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    if let Paint::None = paint {
         return;
-    };
-    for seg in segs {
-        let dx = seg.to.x - seg.from.x;
-        let dy = seg.to.y - seg.from.y;
+    }
+    // Flatten once; the outline or the bodies and joins share the points.
+    let lines = polylines(path);
+    if style.cap != LineCap::Butt || needs_outline(&lines, width_px) {
+        let outline = polyline_outline(&lines, width_px, style);
+        fill_path(
+            &outline,
+            FillRule::NonZero,
+            paint,
+            mask,
+            buffer,
+            width,
+            height,
+            stride,
+        );
+        return;
+    }
+    for (from, to) in polyline_segments(&lines) {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
         let len = (dx * dx + dy * dy).sqrt();
         if len == 0.0 {
             continue;
@@ -123,40 +251,59 @@ pub fn draw_stroke_masked(
         let nx = -dy / len * width_px * 0.5;
         let ny = dx / len * width_px * 0.5;
         let p1 = Vec2 {
-            x: seg.from.x + nx,
-            y: seg.from.y + ny,
+            x: from.x + nx,
+            y: from.y + ny,
         };
         let p2 = Vec2 {
-            x: seg.from.x - nx,
-            y: seg.from.y - ny,
+            x: from.x - nx,
+            y: from.y - ny,
         };
         let p3 = Vec2 {
-            x: seg.to.x - nx,
-            y: seg.to.y - ny,
+            x: to.x - nx,
+            y: to.y - ny,
         };
         let p4 = Vec2 {
-            x: seg.to.x + nx,
-            y: seg.to.y + ny,
+            x: to.x + nx,
+            y: to.y + ny,
         };
-        fill_triangle_masked(p1, p2, p3, color, mask, buffer, width, height, stride);
-        fill_triangle_masked(p1, p3, p4, color, mask, buffer, width, height, stride);
+        // Only the long sides are outer edges; the diagonal and the ends
+        // meet other triangles of the same stroke.
+        let (side_a, side_b) = ([false, true, false], [false, false, true]);
+        fill_triangle_paint(
+            p1, p2, p3, side_a, paint, mask, buffer, width, height, stride,
+        );
+        fill_triangle_paint(
+            p1, p3, p4, side_b, paint, mask, buffer, width, height, stride,
+        );
     }
+    let joins = polyline_joins(&lines, width_px, style);
+    fill_path(
+        &joins,
+        FillRule::NonZero,
+        paint,
+        mask,
+        buffer,
+        width,
+        height,
+        stride,
+    );
 }
 
 /// Rasterize a path into an alpha mask buffer.
 pub fn draw_mask(path: &Path, mask: &mut [u8], width: usize, height: usize) {
-    let mesh = tessellate(path, 0.2, None);
-    for tri in mesh.indices.chunks(3) {
-        if tri.len() < 3 {
-            continue;
+    fill_spans(path, FillRule::NonZero, width, height, |x, y, coverage| {
+        if let Some(m) = mask.get_mut(y * width + x) {
+            *m = (*m).max(quantize(coverage * 255.0));
         }
-        let v0 = mesh.vertices[tri[0] as usize];
-        let v1 = mesh.vertices[tri[1] as usize];
-        let v2 = mesh.vertices[tri[2] as usize];
-        fill_triangle_mask(v0, v1, v2, mask, width, height);
-    }
+    });
 }
 
+/// Composite the matted layer `src` over `dest`.
+///
+/// `src` is premultiplied, as left by drawing over a cleared buffer. The
+/// mask coverage scales all four of its channels, and the result goes
+/// over `dest` with premultiplied source-over, rounding each channel to
+/// nearest.
 #[allow(clippy::too_many_arguments)]
 pub fn blend_masked(
     dest: &mut [u8],
@@ -169,40 +316,217 @@ pub fn blend_masked(
 ) {
     for y in 0..height {
         for x in 0..width {
-            let o = y * stride + x * 4;
             let mut m = mask[y * width + x] as f32 / 255.0;
             if matte == MatteType::AlphaInv {
                 m = 1.0 - m;
             }
-            let sa = src[o + 3] as f32 / 255.0 * m;
-            if sa == 0.0 {
+            blend_layer_pixel(dest, src, y * stride + x * 4, m);
+        }
+    }
+}
+
+/// Composite a layer rendered on its own in `src` over `dest`, blending the
+/// same way as [`blend_masked`] with full coverage.
+pub fn composite(dest: &mut [u8], src: &[u8], width: usize, height: usize, stride: usize) {
+    composite_layer(dest, src, 1.0, BlendMode::Normal, width, height, stride);
+}
+
+/// Composite as in [`composite`] with every pixel's alpha multiplied by
+/// `opacity`, fading the layer uniformly where its own shapes overlap, and
+/// its colors combined with `dest` through `mode`.
+pub fn composite_layer(
+    dest: &mut [u8],
+    src: &[u8],
+    opacity: f32,
+    mode: BlendMode,
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    for y in 0..height {
+        for x in 0..width {
+            let o = y * stride + x * 4;
+            if mode == BlendMode::Normal {
+                blend_layer_pixel(dest, src, o, opacity);
+                continue;
+            }
+            let src_a = src[o + 3] as f32 / 255.0;
+            if src_a == 0.0 {
                 continue;
             }
-            let sr = src[o] as f32 * m;
-            let sg = src[o + 1] as f32 * m;
-            let sb = src[o + 2] as f32 * m;
+            let straight = |c: u8| quantize((c as f32 / src_a).min(255.0));
+            let color = Color {
+                r: straight(src[o]),
+                g: straight(src[o + 1]),
+                b: straight(src[o + 2]),
+                a: quantize(src_a * opacity * 255.0),
+            };
+            blend_pixel_mode(dest, stride, x, y, color, mode);
+        }
+    }
+}
 
-            let dr = dest[o] as f32;
-            let dg = dest[o + 1] as f32;
-            let db = dest[o + 2] as f32;
-            let da = dest[o + 3] as f32 / 255.0;
+/// Blend the premultiplied pixel at offset `o` of `src` over `dest`, with
+/// all of its channels scaled by `coverage`.
+fn blend_layer_pixel(dest: &mut [u8], src: &[u8], o: usize, coverage: f32) {
+    let sa = src[o + 3] as f32 / 255.0 * coverage;
+    if sa == 0.0 {
+        return;
+    }
+    let ia = 1.0 - sa;
+    for c in 0..3 {
+        dest[o + c] = quantize(src[o + c] as f32 * coverage + dest[o + c] as f32 * ia);
+    }
+    let da = dest[o + 3] as f32 / 255.0;
+    dest[o + 3] = quantize((sa + da * ia) * 255.0);
+}
 
-            let ia = 1.0 - sa;
-            let out_a = sa + da * ia;
-            let out_r = sr + dr * ia;
-            let out_g = sg + dg * ia;
-            let out_b = sb + db * ia;
+/// Blit an [`ImageLayer`] with nearest-neighbor sampling. `matrix` maps
+/// image pixel coordinates to buffer space; each covered buffer pixel
+/// samples the image texel under its center.
+pub fn draw_image(
+    img: &ImageLayer,
+    matrix: Affine,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    let src_w = img.width as usize;
+    for_each_texel(
+        matrix,
+        (src_w, img.height as usize),
+        width,
+        height,
+        |x, y, sx, sy| {
+            let o = (sy * src_w + sx) * 4;
+            let Some(px) = img.pixels.get(o..o + 4) else {
+                return;
+            };
+            let color = Color {
+                r: px[0],
+                g: px[1],
+                b: px[2],
+                a: px[3],
+            };
+            if color.a != 0 {
+                blend_pixel(buffer, stride, x, y, color);
+            }
+        },
+    );
+}
 
-            dest[o] = out_r.min(255.0) as u8;
-            dest[o + 1] = out_g.min(255.0) as u8;
-            dest[o + 2] = out_b.min(255.0) as u8;
-            dest[o + 3] = (out_a * 255.0).min(255.0) as u8;
+/// Composite a premultiplied RGBA8888 raster of `size` pixels, tightly
+/// packed, source-over `buffer` with nearest-neighbor sampling. `matrix`
+/// maps raster pixel coordinates to buffer space as in [`draw_image`].
+pub(crate) fn draw_raster(
+    src: &[u8],
+    size: (usize, usize),
+    matrix: Affine,
+    buffer: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    for_each_texel(matrix, size, width, height, |x, y, sx, sy| {
+        let s = (sy * size.0 + sx) * 4;
+        let sa = src[s + 3] as f32 / 255.0;
+        if sa == 0.0 {
+            return;
+        }
+        let o = y * stride + x * 4;
+        for c in 0..4 {
+            buffer[o + c] = quantize(src[s + c] as f32 + buffer[o + c] as f32 * (1.0 - sa));
+        }
+    });
+}
+
+/// Call `f(x, y, src_x, src_y)` for every pixel of a `width` x `height`
+/// target whose center `matrix` maps back inside a source of `size`
+/// pixels, with the source texel it lands on.
+fn for_each_texel(
+    matrix: Affine,
+    size: (usize, usize),
+    width: usize,
+    height: usize,
+    mut f: impl FnMut(usize, usize, usize, usize),
+) {
+    let Some(inverse) = matrix.invert() else {
+        return;
+    };
+    let (w, h) = (size.0 as f32, size.1 as f32);
+    let corners = [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)]
+        .map(|(x, y)| matrix.transform_point(Vec2 { x, y }));
+    let min = corners.iter().fold(
+        Vec2 {
+            x: f32::MAX,
+            y: f32::MAX,
+        },
+        |m, p| Vec2 {
+            x: m.x.min(p.x),
+            y: m.y.min(p.y),
+        },
+    );
+    let max = corners.iter().fold(
+        Vec2 {
+            x: f32::MIN,
+            y: f32::MIN,
+        },
+        |m, p| Vec2 {
+            x: m.x.max(p.x),
+            y: m.y.max(p.y),
+        },
+    );
+    let min_x = min.x.floor().max(0.0) as usize;
+    let min_y = min.y.floor().max(0.0) as usize;
+    let max_x = max.x.ceil().clamp(0.0, width as f32) as usize;
+    let max_y = max.y.ceil().clamp(0.0, height as f32) as usize;
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let src = inverse.transform_point(Vec2 {
+                x: x as f32 + 0.5,
+                y: y as f32 + 0.5,
+            });
+            let (src_x, src_y) = (src.x.floor(), src.y.floor());
+            if src_x < 0.0 || src_x >= w || src_y < 0.0 || src_y >= h {
+                continue;
+            }
+            f(x, y, src_x as usize, src_y as usize);
         }
     }
 }
 
 /// Render a [`TextLayer`] into the RGBA8888 buffer.
+///
+/// With [`TextLayer::outlines`] set the glyphs are filled and stroked as
+/// paths; if the font data cannot be parsed the bitmaps are drawn instead.
 pub fn draw_text(layer: &TextLayer, buffer: &mut [u8], width: usize, height: usize, stride: usize) {
+    if let Some(outlines) = &layer.outlines {
+        if let Some(path) = text_path(layer, &outlines.font_data) {
+            draw_fill(
+                &path,
+                FillRule::NonZero,
+                outlines.fill.clone(),
+                buffer,
+                width,
+                height,
+                stride,
+            );
+            if let Some((color, stroke_width)) = outlines.stroke {
+                draw_stroke(
+                    &path,
+                    stroke_width,
+                    StrokeStyle::default(),
+                    Paint::Solid(color),
+                    buffer,
+                    width,
+                    height,
+                    stride,
+                );
+            }
+            return;
+        }
+    }
     let mut cursor_x = layer.position.x;
     let base_y = layer.position.y;
     for ch in layer.text.chars() {
@@ -224,102 +548,308 @@ pub fn draw_text(layer: &TextLayer, buffer: &mut [u8], width: usize, height: usi
                     continue;
                 }
                 let mut c = layer.color;
-                c.a = ((cov as u32 * c.a as u32) / 255) as u8;
+                c.a = quantize(cov as f32 * c.a as f32 / 255.0);
                 blend_pixel(buffer, stride, xx as usize, yy as usize, c);
             }
         }
         cursor_x += metrics.advance_width;
     }
 }
+
+/// Outlines of `layer.text` laid out along its baseline, in pixels.
+fn text_path(layer: &TextLayer, font_data: &[u8]) -> Option<Path> {
+    let face = ttf_parser::Face::from_slice(font_data, 0).ok()?;
+    let mut pen = GlyphPen {
+        path: Path::new(),
+        origin: layer.position,
+        scale: layer.size / face.units_per_em() as f32,
+        last: Vec2::default(),
+    };
+    for ch in layer.text.chars() {
+        let Some(glyph) = face.glyph_index(ch) else {
+            continue;
+        };
+        face.outline_glyph(glyph, &mut pen);
+        pen.origin.x += face.glyph_hor_advance(glyph).unwrap_or(0) as f32 * pen.scale;
+    }
+    Some(pen.path)
+}
+
+/// Collects glyph outlines, flipping font units (y up) into pixels (y down)
+/// around the glyph origin.
+struct GlyphPen {
+    path: Path,
+    origin: Vec2,
+    scale: f32,
+    /// Current point in pixels, the start of the next quadratic
+    last: Vec2,
+}
+
+impl GlyphPen {
+    fn point(&self, x: f32, y: f32) -> Vec2 {
+        Vec2 {
+            x: self.origin.x + x * self.scale,
+            y: self.origin.y - y * self.scale,
+        }
+    }
+}
+
+impl ttf_parser::OutlineBuilder for GlyphPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.last = self.point(x, y);
+        self.path.move_to(self.last);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.last = self.point(x, y);
+        self.path.line_to(self.last);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p0, c, p) = (self.last, self.point(x1, y1), self.point(x, y));
+        let two_thirds = |a: Vec2| Vec2 {
+            x: a.x + (c.x - a.x) * (2.0 / 3.0),
+            y: a.y + (c.y - a.y) * (2.0 / 3.0),
+        };
+        self.path.cubic_to(two_thirds(p0), two_thirds(p), p);
+        self.last = p;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.last = self.point(x, y);
+        self.path
+            .cubic_to(self.point(x1, y1), self.point(x2, y2), self.last);
+    }
+
+    fn close(&mut self) {
+        self.path.close();
+    }
+}
+
+/// Fill triangle `a`,`b`,`c` with `paint`, antialiasing the edges marked
+/// in `smooth` and skipping pixels `mask` does not allow.
 #[allow(clippy::too_many_arguments)]
 fn fill_triangle_paint(
     a: Vec2,
     b: Vec2,
     c: Vec2,
+    smooth: [bool; 3],
     paint: &Paint,
+    mask: Option<&[u8]>,
     buf: &mut [u8],
     width: usize,
     height: usize,
     stride: usize,
 ) {
-    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as i32;
-    let max_x = a.x.max(b.x).max(c.x).ceil().min(width as f32) as i32;
-    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as i32;
-    let max_y = a.y.max(b.y).max(c.y).ceil().min(height as f32) as i32;
+    if is_degenerate(a, b, c) {
+        return;
+    }
+    // Smooth edges reach up to a pixel past the triangle.
+    let pad = if smooth.contains(&true) { 1.0 } else { 0.0 };
+    let min_x = (a.x.min(b.x).min(c.x) - pad).floor().max(0.0) as i32;
+    let max_x = (a.x.max(b.x).max(c.x) + pad).ceil().min(width as f32) as i32;
+    let min_y = (a.y.min(b.y).min(c.y) - pad).floor().max(0.0) as i32;
+    let max_y = (a.y.max(b.y).max(c.y) + pad).ceil().min(height as f32) as i32;
 
     for y in min_y..max_y {
         for x in min_x..max_x {
             let px = x as f32 + 0.5;
             let py = y as f32 + 0.5;
-            if inside_triangle(px, py, a, b, c) {
-                let color = sample_paint(paint, Vec2 { x: px, y: py });
+            let p = Vec2 { x: px, y: py };
+            let coverage = triangle_coverage(p, a, b, c, smooth);
+            if coverage > 0.0 && mask_allows(mask, stride, x as usize, y as usize) {
+                let mut color = sample_paint(paint, p);
+                color.a = quantize(color.a as f32 * coverage);
                 blend_pixel(buf, stride, x as usize, y as usize, color);
             }
         }
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn fill_triangle_mask(a: Vec2, b: Vec2, c: Vec2, buf: &mut [u8], width: usize, height: usize) {
-    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as i32;
-    let max_x = a.x.max(b.x).max(c.x).ceil().min(width as f32) as i32;
-    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as i32;
-    let max_y = a.y.max(b.y).max(c.y).ceil().min(height as f32) as i32;
-
-    for y in min_y..max_y {
-        for x in min_x..max_x {
-            let px = x as f32 + 0.5;
-            let py = y as f32 + 0.5;
-            if inside_triangle(px, py, a, b, c) {
-                let idx = y as usize * width + x as usize;
-                if idx < buf.len() {
-                    buf[idx] = 255;
-                }
-            }
-        }
-    }
+/// Whether a stroke of `width_px` is wider than one of its segments is long.
+/// Per-segment quads of such strokes overlap heavily and double-blend, so
+/// they are rendered as a single filled outline instead.
+fn needs_outline(lines: &[Polyline], width_px: f32) -> bool {
+    polyline_segments(lines)
+        .map(|(a, b)| (b.x - a.x).hypot(b.y - a.y))
+        .any(|len| len > 0.0 && len < width_px)
 }
 
-#[allow(clippy::too_many_arguments)]
-fn fill_triangle_masked(
-    a: Vec2,
-    b: Vec2,
-    c: Vec2,
-    color: Color,
-    mask: &[u8],
-    buf: &mut [u8],
+/// Call `plot(x, y, coverage)` for every pixel of `path` filled under
+/// `rule`. Open sub-paths are implicitly closed.
+///
+/// Pixels whose centers lie inside are fully covered; spans carry the same
+/// winding as [`crate::geometry::winding_number`] at their centers. Edges
+/// are anti-aliased on the outside: a pixel next to the filled ones whose
+/// center is less than half a pixel from an edge is covered by the strip
+/// the edge reaches into it, `0.5 - distance`. Shapes on pixel boundaries
+/// are therefore unchanged, and slanted edges gain a soft fringe.
+fn fill_spans(
+    path: &Path,
+    rule: FillRule,
     width: usize,
     height: usize,
-    stride: usize,
+    mut plot: impl FnMut(usize, usize, f32),
 ) {
-    let min_x = a.x.min(b.x).min(c.x).floor().max(0.0) as i32;
-    let max_x = a.x.max(b.x).max(c.x).ceil().min(width as f32) as i32;
-    let min_y = a.y.min(b.y).min(c.y).floor().max(0.0) as i32;
-    let max_y = a.y.max(b.y).max(c.y).ceil().min(height as f32) as i32;
+    let edges = path.closed_edges(0.2);
+    // Pixels that can be touched: the edges' bounds plus the fringe.
+    let (min, max) = edges.iter().fold(
+        ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN)),
+        |(min, max), e| {
+            (
+                (
+                    min.0.min(e.from.x).min(e.to.x),
+                    min.1.min(e.from.y).min(e.to.y),
+                ),
+                (
+                    max.0.max(e.from.x).max(e.to.x),
+                    max.1.max(e.from.y).max(e.to.y),
+                ),
+            )
+        },
+    );
+    let x_start = (min.0 - 1.0).floor().clamp(0.0, width as f32) as usize;
+    let x_end = (max.0 + 1.0).ceil().clamp(0.0, width as f32) as usize;
+    let y_start = (min.1 - 1.0).floor().clamp(0.0, height as f32) as usize;
+    let y_end = (max.1 + 1.0).ceil().clamp(0.0, height as f32) as usize;
+    if x_start >= x_end || y_start >= y_end {
+        return;
+    }
+    let box_w = x_end - x_start;
+    let cell = |x: usize, y: usize| (y - y_start) * box_w + (x - x_start);
 
-    for y in min_y..max_y {
-        for x in min_x..max_x {
-            let px = x as f32 + 0.5;
+    let mut inside = vec![false; box_w * (y_end - y_start)];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    for y in y_start..y_end {
+        let py = y as f32 + 0.5;
+        crossings.clear();
+        crossings.extend(edges.iter().filter_map(|e| e.crossing(py)));
+        crossings.sort_by(|l, r| l.0.total_cmp(&r.0));
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += pair[0].1;
+            let filled = match rule {
+                FillRule::NonZero => winding != 0,
+                FillRule::EvenOdd => winding % 2 != 0,
+            };
+            if !filled {
+                continue;
+            }
+            let x0 = (pair[0].0 - 0.5).ceil().max(0.0) as usize;
+            let x1 = (pair[1].0 - 0.5).ceil().clamp(0.0, width as f32) as usize;
+            for x in x0..x1 {
+                inside[cell(x, y)] = true;
+                plot(x, y, 1.0);
+            }
+        }
+    }
+
+    // Fringe coverage of the pixels outside, from the nearest edge. Rows
+    // and columns are found with a pixel of slack; coverage only counts
+    // within half a pixel.
+    let mut fringe = vec![0.0f32; inside.len()];
+    for e in &edges {
+        if e.length() == 0.0 {
+            continue;
+        }
+        let (a, b) = (e.from, e.to);
+        let y0 = (a.y.min(b.y) - 1.5).ceil().max(y_start as f32) as usize;
+        let y1 = (a.y.max(b.y) + 0.5).ceil().clamp(0.0, y_end as f32) as usize;
+        for y in y0..y1 {
             let py = y as f32 + 0.5;
-            if inside_triangle(px, py, a, b, c) {
-                let moff = y as usize * stride + x as usize * 4 + 3;
-                if moff < mask.len() && mask[moff] != 0 {
-                    blend_pixel(buf, stride, x as usize, y as usize, color);
+            // Only the part of the edge within a pixel of this row's
+            // centers can be close enough.
+            let (t0, t1) = if a.y == b.y {
+                (0.0, 1.0)
+            } else {
+                let ta = (py - 1.0 - a.y) / (b.y - a.y);
+                let tb = (py + 1.0 - a.y) / (b.y - a.y);
+                (ta.min(tb).max(0.0), ta.max(tb).min(1.0))
+            };
+            if t0 > t1 {
+                continue;
+            }
+            let xa = a.x + (b.x - a.x) * t0;
+            let xb = a.x + (b.x - a.x) * t1;
+            let x0 = (xa.min(xb) - 1.5).ceil().max(x_start as f32) as usize;
+            let x1 = (xa.max(xb) + 0.5).ceil().clamp(0.0, x_end as f32) as usize;
+            for x in x0..x1 {
+                let i = cell(x, y);
+                if inside[i] {
+                    continue;
                 }
+                let p = Vec2 {
+                    x: x as f32 + 0.5,
+                    y: py,
+                };
+                fringe[i] = fringe[i].max(0.5 - segment_distance(p, a, b));
             }
         }
     }
+    let box_h = y_end - y_start;
+    for (i, &coverage) in fringe.iter().enumerate() {
+        if coverage <= 0.0 {
+            continue;
+        }
+        // Edges with no filled pixel beside them, such as a contour wound
+        // twice under even-odd, bound nothing.
+        let (bx, by) = (i % box_w, i / box_w);
+        let beside_fill = (by.saturating_sub(1)..(by + 2).min(box_h)).any(|ny| {
+            (bx.saturating_sub(1)..(bx + 2).min(box_w)).any(|nx| inside[ny * box_w + nx])
+        });
+        if beside_fill {
+            plot(x_start + bx, y_start + by, coverage);
+        }
+    }
+}
+
+/// Distance from `p` to the segment `ab`.
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq > 0.0 {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p.x - (a.x + dx * t)).hypot(p.y - (a.y + dy * t))
 }
 
 fn edge(px: f32, py: f32, a: Vec2, b: Vec2) -> f32 {
     (px - a.x) * (b.y - a.y) - (py - a.y) * (b.x - a.x)
 }
 
-fn inside_triangle(px: f32, py: f32, a: Vec2, b: Vec2, c: Vec2) -> bool {
-    let e1 = edge(px, py, a, b);
-    let e2 = edge(px, py, b, c);
-    let e3 = edge(px, py, c, a);
-    (e1 >= 0.0 && e2 >= 0.0 && e3 >= 0.0) || (e1 <= 0.0 && e2 <= 0.0 && e3 <= 0.0)
+/// Whether a triangle has no area or a non-finite vertex. Such triangles
+/// cover nothing, but the edge tests would still accept pixels on a
+/// zero-area sliver and give arbitrary answers for NaN.
+fn is_degenerate(a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let finite = |p: Vec2| p.x.is_finite() && p.y.is_finite();
+    !(finite(a) && finite(b) && finite(c)) || edge(c.x, c.y, a, b) == 0.0
+}
+
+/// Coverage of the pixel centered at `p` by the triangle `abc`, in 0..=1.
+///
+/// Edges `ab`, `bc` and `ca` flagged in `smooth` are anti-aliased as in
+/// [`fill_spans`]: a pixel whose center is inside is fully covered, and one
+/// outside within half a pixel of the edge gets `0.5 - distance`. The other
+/// edges are tested at the pixel center only, so triangles sharing such an
+/// edge, like the halves of a stroke quad, tile without a seam.
+fn triangle_coverage(p: Vec2, a: Vec2, b: Vec2, c: Vec2, smooth: [bool; 3]) -> f32 {
+    // Signed distances come out positive inside whichever way abc winds.
+    let orient = edge(c.x, c.y, a, b).signum();
+    let mut coverage = 1.0f32;
+    for ((u, v), smooth) in [(a, b), (b, c), (c, a)].into_iter().zip(smooth) {
+        let d = edge(p.x, p.y, u, v) * orient;
+        if d >= 0.0 {
+            continue;
+        }
+        if !smooth {
+            return 0.0;
+        }
+        let len = ((v.x - u.x).powi(2) + (v.y - u.y).powi(2)).sqrt();
+        coverage = coverage.min((d / len + 0.5).max(0.0));
+    }
+    coverage
 }
 
 fn blend_pixel(buf: &mut [u8], stride: usize, x: usize, y: usize, src: Color) {
@@ -340,69 +870,321 @@ fn blend_pixel(buf: &mut [u8], stride: usize, x: usize, y: usize, src: Color) {
     let out_g = src.g as f32 * sa + dst_g * ia;
     let out_b = src.b as f32 * sa + dst_b * ia;
 
-    buf[offset] = out_r.min(255.0) as u8;
-    buf[offset + 1] = out_g.min(255.0) as u8;
-    buf[offset + 2] = out_b.min(255.0) as u8;
-    buf[offset + 3] = (out_a * 255.0).min(255.0) as u8;
+    buf[offset] = quantize(out_r);
+    buf[offset + 1] = quantize(out_g);
+    buf[offset + 2] = quantize(out_b);
+    buf[offset + 3] = quantize(out_a * 255.0);
+}
+
+/// Blend a straight-alpha `src` over the premultiplied pixel at `x`,`y`,
+/// first combining its color with the backdrop's through `mode`.
+///
+/// Follows the W3C separable blend modes: where both are opaque the result
+/// is the blended color, and where either is transparent the other shows
+/// through as in a plain source-over composite.
+pub fn blend_pixel_mode(
+    buf: &mut [u8],
+    stride: usize,
+    x: usize,
+    y: usize,
+    src: Color,
+    mode: BlendMode,
+) {
+    if mode == BlendMode::Normal {
+        blend_pixel(buf, stride, x, y, src);
+        return;
+    }
+    let offset = y * stride + x * 4;
+    if offset + 3 >= buf.len() || src.a == 0 {
+        return;
+    }
+    let sa = src.a as f32 / 255.0;
+    let da = buf[offset + 3] as f32 / 255.0;
+    for (c, s) in [src.r, src.g, src.b].into_iter().enumerate() {
+        let dst = buf[offset + c] as f32 / 255.0;
+        let cb = if da > 0.0 { (dst / da).min(1.0) } else { 0.0 };
+        let cs = s as f32 / 255.0;
+        let blended = blend_channel(cb, cs, mode);
+        let out = sa * (1.0 - da) * cs + sa * da * blended + (1.0 - sa) * dst;
+        buf[offset + c] = quantize(out * 255.0);
+    }
+    buf[offset + 3] = quantize((sa + da * (1.0 - sa)) * 255.0);
 }
 
-fn lerp_color(a: Color, b: Color, t: f32) -> Color {
-    let clamped = t.clamp(0.0, 1.0);
-    let ir = a.r as f32 + (b.r as f32 - a.r as f32) * clamped;
-    let ig = a.g as f32 + (b.g as f32 - a.g as f32) * clamped;
-    let ib = a.b as f32 + (b.b as f32 - a.b as f32) * clamped;
-    let ia = a.a as f32 + (b.a as f32 - a.a as f32) * clamped;
+/// Blend one straight channel of the source `cs` with the backdrop `cb`,
+/// both in `0..=1`.
+fn blend_channel(cb: f32, cs: f32, mode: BlendMode) -> f32 {
+    let multiply = |a: f32, b: f32| a * b;
+    let screen = |a: f32, b: f32| a + b - a * b;
+    match mode {
+        BlendMode::Normal => cs,
+        BlendMode::Multiply => multiply(cb, cs),
+        BlendMode::Screen => screen(cb, cs),
+        BlendMode::Overlay if cb <= 0.5 => multiply(cs, 2.0 * cb),
+        BlendMode::Overlay => screen(cs, 2.0 * cb - 1.0),
+        BlendMode::Darken => cb.min(cs),
+        BlendMode::Lighten => cb.max(cs),
+        BlendMode::Add => (cb + cs).min(1.0),
+    }
+}
+
+/// Quantize a 0..=255 channel value to a byte, rounding to nearest like the
+/// reference renderer. Truncating instead biases every blend slightly dark.
+fn quantize(v: f32) -> u8 {
+    (v + 0.5).min(255.0) as u8
+}
+
+/// Interpolate between two colors in `space`. Channels that leave the sRGB
+/// gamut on the way back from HSL or OKLab are clamped.
+fn lerp_color_in(a: Color, b: Color, t: f32, space: ColorSpace) -> Color {
+    if space == ColorSpace::Rgb {
+        return a.lerp(b, t);
+    }
+    let t = t.clamp(0.0, 1.0);
+    let mix = |x: f32, y: f32| x + (y - x) * t;
+    let alpha = mix(a.a as f32, b.a as f32).round() as u8;
+    // A fully transparent stop has no meaningful hue; borrow the other one.
+    let (a, b) = match (a.a, b.a) {
+        (0, _) => (Color { a: 0, ..b }, b),
+        (_, 0) => (a, Color { a: 0, ..a }),
+        _ => (a, b),
+    };
+    let rgb = match space {
+        ColorSpace::Rgb | ColorSpace::Hsl => {
+            let (h0, s0, l0) = rgb_to_hsl(a);
+            let (h1, s1, l1) = rgb_to_hsl(b);
+            let mut dh = h1 - h0;
+            if dh > 180.0 {
+                dh -= 360.0;
+            } else if dh < -180.0 {
+                dh += 360.0;
+            }
+            hsl_to_rgb((h0 + dh * t).rem_euclid(360.0), mix(s0, s1), mix(l0, l1))
+        }
+        ColorSpace::OkLab => {
+            let la = rgb_to_oklab(a);
+            let lb = rgb_to_oklab(b);
+            oklab_to_rgb([mix(la[0], lb[0]), mix(la[1], lb[1]), mix(la[2], lb[2])])
+        }
+    };
+    let to_u8 = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
     Color {
-        r: ir.round() as u8,
-        g: ig.round() as u8,
-        b: ib.round() as u8,
-        a: ia.round() as u8,
+        r: to_u8(rgb[0]),
+        g: to_u8(rgb[1]),
+        b: to_u8(rgb[2]),
+        a: alpha,
     }
 }
 
-fn sample_stops(stops: &[GradientStop], t: f32) -> Color {
-    if stops.is_empty() {
-        return Color {
-            r: 0,
-            g: 0,
-            b: 0,
-            a: 255,
-        };
+/// Hue in degrees, saturation and lightness in `0..=1`.
+fn rgb_to_hsl(c: Color) -> (f32, f32, f32) {
+    let (r, g, b) = (c.r as f32 / 255.0, c.g as f32 / 255.0, c.b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) * 0.5;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, s, l)
+}
+
+fn hsl_to_rgb(h: f32, s: f32, l: f32) -> [f32; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = l - c * 0.5;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
     }
-    if t <= stops[0].offset {
-        return stops[0].color;
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    let v = v.max(0.0);
+    if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Convert to OKLab `[L, a, b]` (Björn Ottosson's reference matrices).
+fn rgb_to_oklab(c: Color) -> [f32; 3] {
+    let r = srgb_to_linear(c.r as f32 / 255.0);
+    let g = srgb_to_linear(c.g as f32 / 255.0);
+    let b = srgb_to_linear(c.b as f32 / 255.0);
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn oklab_to_rgb(lab: [f32; 3]) -> [f32; 3] {
+    let l = (lab[0] + 0.396_337_78 * lab[1] + 0.215_803_76 * lab[2]).powi(3);
+    let m = (lab[0] - 0.105_561_346 * lab[1] - 0.063_854_17 * lab[2]).powi(3);
+    let s = (lab[0] - 0.089_484_18 * lab[1] - 1.291_485_5 * lab[2]).powi(3);
+    [
+        linear_to_srgb(4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s),
+        linear_to_srgb(-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s),
+        linear_to_srgb(-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s),
+    ]
+}
+
+/// Color of `stops` at `t`, holding the end colors outside their range.
+///
+/// A single stop paints its color everywhere. Without stops there is
+/// nothing to sample and the gradient falls back to opaque black.
+pub(crate) fn sample_stops(stops: &[GradientStop], t: f32, space: ColorSpace) -> Color {
+    let (first, last) = match stops {
+        [] => {
+            return Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255,
+            }
+        }
+        [only] => return only.color,
+        [first, .., last] => (first, last),
+    };
+    if t <= first.offset {
+        return first.color;
     }
     for win in stops.windows(2) {
         let s0 = win[0];
         let s1 = win[1];
         if t <= s1.offset {
             let local = (t - s0.offset) / (s1.offset - s0.offset);
-            return lerp_color(s0.color, s1.color, local);
+            return lerp_color_in(s0.color, s1.color, local, space);
+        }
+    }
+    last.color
+}
+
+/// Opacity of `stops` at `t`, holding the end values outside their range.
+/// Without stops the gradient is fully opaque.
+pub(crate) fn sample_opacity(stops: &[OpacityStop], t: f32) -> f32 {
+    let (first, last) = match stops {
+        [] => return 1.0,
+        [only] => return only.opacity,
+        [first, .., last] => (first, last),
+    };
+    if t <= first.offset {
+        return first.opacity;
+    }
+    for win in stops.windows(2) {
+        let (s0, s1) = (win[0], win[1]);
+        if t <= s1.offset {
+            let local = (t - s0.offset) / (s1.offset - s0.offset);
+            return s0.opacity + (s1.opacity - s0.opacity) * local;
         }
     }
-    stops.last().unwrap().color
+    last.opacity
 }
 
+/// Color of a gradient at `t`: the color ramp with the opacity ramp,
+/// sampled on its own, multiplied into its alpha.
+fn sample_gradient(
+    stops: &[GradientStop],
+    opacity: &[OpacityStop],
+    t: f32,
+    space: ColorSpace,
+) -> Color {
+    let color = sample_stops(stops, t, space);
+    if opacity.is_empty() {
+        return color;
+    }
+    Color {
+        a: quantize(color.a as f32 * sample_opacity(opacity, t).clamp(0.0, 1.0)),
+        ..color
+    }
+}
+
+/// Sample `g` at `p` projected onto the line from its start to its end.
 fn sample_linear(g: &LinearGradient, p: Vec2) -> Color {
-    let span = g.end.x - g.start.x;
-    let t = if span.abs() > 0.0 {
-        ((p.x - g.start.x) / span).clamp(0.0, 1.0)
+    let (dx, dy) = (g.end.x - g.start.x, g.end.y - g.start.y);
+    let span = dx * dx + dy * dy;
+    let t = if span > 0.0 {
+        (((p.x - g.start.x) * dx + (p.y - g.start.y) * dy) / span).clamp(0.0, 1.0)
     } else {
         0.0
     };
-    sample_stops(&g.stops, t)
+    sample_gradient(&g.stops, &g.opacity_stops, t, g.space)
 }
 
+/// Sample `g` at `p`. With a focus, offset 0 sits on the focus and 1 on
+/// the circle, and `t` is how far `p` lies along the ray from the focus
+/// through `p` to the circle, as for SVG and CSS radial gradients.
 fn sample_radial(g: &RadialGradient, p: Vec2) -> Color {
-    let dx = p.x - g.center.x;
-    let dy = p.y - g.center.y;
-    let dist = (dx * dx + dy * dy).sqrt();
-    let t = dist / g.radius;
-    sample_stops(&g.stops, t)
+    let Some(focus) = g.focus else {
+        let dx = p.x - g.center.x;
+        let dy = p.y - g.center.y;
+        let dist = (dx * dx + dy * dy).sqrt();
+        let t = dist / g.radius;
+        return sample_gradient(&g.stops, &g.opacity_stops, t, g.space);
+    };
+    // Solve |focus + s * d - center| = radius for the ray through `p`; `p`
+    // itself is at s = 1, so its offset along the ray is 1 / s.
+    let d = Vec2 {
+        x: p.x - focus.x,
+        y: p.y - focus.y,
+    };
+    let e = Vec2 {
+        x: focus.x - g.center.x,
+        y: focus.y - g.center.y,
+    };
+    let a = d.x * d.x + d.y * d.y;
+    let b = 2.0 * (e.x * d.x + e.y * d.y);
+    let c = e.x * e.x + e.y * e.y - g.radius * g.radius;
+    let disc = b * b - 4.0 * a * c;
+    let t = if a == 0.0 {
+        0.0
+    } else if disc < 0.0 {
+        1.0
+    } else {
+        let s = (-b + disc.sqrt()) / (2.0 * a);
+        if s > 0.0 {
+            1.0 / s
+        } else {
+            1.0
+        }
+    };
+    sample_gradient(&g.stops, &g.opacity_stops, t, g.space)
 }
 
 fn sample_paint(paint: &Paint, p: Vec2) -> Color {
     match paint {
+        Paint::None => Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        },
         Paint::Solid(c) => *c,
         Paint::Linear(g) => sample_linear(g, p),
         Paint::Radial(g) => sample_radial(g, p),
@@ -412,6 +1194,7 @@ fn sample_paint(paint: &Paint, p: Vec2) -> Color {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::LineJoin;
 
     #[test]
     fn draw_simple_rect() {
@@ -453,6 +1236,7 @@ mod tests {
         draw_stroke(
             &path,
             1.0,
+            StrokeStyle::default(),
             Paint::Solid(Color {
                 r: 255,
                 g: 0,
@@ -464,10 +1248,40 @@ mod tests {
             8,
             8 * 4,
         );
-        let off = 1 * 8 * 4 + 1 * 4;
+        let off = 8 * 4 + 4;
         assert_eq!(&buf[off..off + 4], &[255, 0, 0, 255]);
     }
 
+    #[test]
+    fn thick_stroke_blends_once() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 14.0, y: 14.0 });
+        path.line_to(Vec2 { x: 18.0, y: 14.0 });
+        path.line_to(Vec2 { x: 16.0, y: 18.0 });
+        path.close();
+
+        let mut buf = vec![0u8; 32 * 32 * 4];
+        draw_stroke(
+            &path,
+            10.0,
+            StrokeStyle::default(),
+            Paint::Solid(Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 128,
+            }),
+            &mut buf,
+            32,
+            32,
+            32 * 4,
+        );
+        // Edge pixels are anti-aliased; none may be blended twice.
+        let alphas: Vec<u8> = buf.chunks(4).map(|p| p[3]).filter(|&a| a > 0).collect();
+        assert!(alphas.contains(&128));
+        assert!(alphas.iter().all(|&a| a <= 128));
+    }
+
     #[test]
     fn draw_masked_rect() {
         let mut path = Path::new();
@@ -523,4 +1337,421 @@ mod tests {
         let off_in = 4 * 8 * 4 + 4 * 4;
         assert_eq!(&buf[off_in..off_in + 4], &[0, 255, 0, 255]);
     }
+
+    #[test]
+    fn masked_gradient_fill_and_stroke() {
+        let ramp = Paint::Linear(LinearGradient {
+            start: Vec2 { x: 0.0, y: 0.0 },
+            end: Vec2 { x: 8.0, y: 0.0 },
+            stops: [(0.0, 0), (1.0, 255)]
+                .map(|(offset, r)| GradientStop {
+                    offset,
+                    color: Color {
+                        r,
+                        g: 0,
+                        b: 0,
+                        a: 255,
+                    },
+                })
+                .to_vec(),
+            opacity_stops: Vec::new(),
+            space: ColorSpace::Rgb,
+        });
+        let mut square = Path::new();
+        square.move_to(Vec2 { x: 0.0, y: 0.0 });
+        square.line_to(Vec2 { x: 8.0, y: 0.0 });
+        square.line_to(Vec2 { x: 8.0, y: 8.0 });
+        square.line_to(Vec2 { x: 0.0, y: 8.0 });
+        square.close();
+        // Only the right half of the buffer is unmasked.
+        let mut mask = vec![0u8; 8 * 8 * 4];
+        for px in mask.chunks_mut(4).enumerate().filter(|(i, _)| i % 8 >= 4) {
+            px.1[3] = 255;
+        }
+        let red_at = |buf: &[u8], x: usize, y: usize| buf[(y * 8 + x) * 4];
+
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        draw_path_masked(&square, ramp.clone(), &mask, &mut buf, 8, 8, 8 * 4);
+        assert_eq!(buf[(4 * 8 + 2) * 4 + 3], 0);
+        assert!(red_at(&buf, 4, 4) > 0 && red_at(&buf, 4, 4) < red_at(&buf, 7, 4));
+
+        let mut line = Path::new();
+        line.move_to(Vec2 { x: 0.0, y: 4.0 });
+        line.line_to(Vec2 { x: 8.0, y: 4.0 });
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        draw_stroke_masked(
+            &line,
+            2.0,
+            StrokeStyle::default(),
+            ramp,
+            &mask,
+            &mut buf,
+            8,
+            8,
+            8 * 4,
+        );
+        assert_eq!(buf[(4 * 8 + 2) * 4 + 3], 0);
+        assert!(red_at(&buf, 5, 4) > 0 && red_at(&buf, 5, 4) < red_at(&buf, 7, 4));
+    }
+
+    #[test]
+    fn masked_edges_do_not_darken_over_white() {
+        // Half-transparent red as left by `blend_pixel` over a cleared buffer.
+        let mut src = vec![0u8; 8 * 8 * 4];
+        blend_pixel(
+            &mut src,
+            8 * 4,
+            0,
+            0,
+            Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 128,
+            },
+        );
+        let first: Vec<u8> = src[..4].to_vec();
+        for px in src.chunks_mut(4) {
+            px.copy_from_slice(&first);
+        }
+        // Coverage ramps across each row to exercise partial mask edges.
+        let mask: Vec<u8> = (0..64).map(|i| (i % 8 * 36) as u8).collect();
+        let mut dest = vec![255u8; 8 * 8 * 4];
+        blend_masked(&mut dest, &src, &mask, MatteType::Alpha, 8, 8, 8 * 4);
+
+        for (px, &m) in dest.chunks(4).zip(&mask) {
+            let sa = 128.0 / 255.0 * m as f32 / 255.0;
+            let expected = (255.0 * (1.0 - sa)).round() as u8;
+            assert_eq!(px, &[255, expected, expected, 255], "mask {m}");
+        }
+    }
+
+    #[test]
+    fn fill_rule_on_double_wound_square() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 2.0, y: 2.0 });
+        for _ in 0..2 {
+            path.line_to(Vec2 { x: 8.0, y: 2.0 });
+            path.line_to(Vec2 { x: 8.0, y: 8.0 });
+            path.line_to(Vec2 { x: 2.0, y: 8.0 });
+            path.line_to(Vec2 { x: 2.0, y: 2.0 });
+        }
+        path.close();
+        let red = Paint::Solid(Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        });
+        let center = (5 * 10 + 5) * 4 + 3;
+
+        let mut buf = vec![0u8; 10 * 10 * 4];
+        draw_fill(
+            &path,
+            FillRule::NonZero,
+            red.clone(),
+            &mut buf,
+            10,
+            10,
+            10 * 4,
+        );
+        assert_eq!(buf[center], 255);
+
+        let mut buf = vec![0u8; 10 * 10 * 4];
+        draw_fill(&path, FillRule::EvenOdd, red, &mut buf, 10, 10, 10 * 4);
+        assert!(buf.chunks(4).all(|px| px[3] == 0));
+    }
+
+    #[test]
+    fn gradient_fades_to_transparent_without_darkening() {
+        let stops = [
+            GradientStop {
+                offset: 0.0,
+                color: Color {
+                    r: 255,
+                    g: 0,
+                    b: 0,
+                    a: 255,
+                },
+            },
+            GradientStop {
+                offset: 1.0,
+                color: Color {
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: 0,
+                },
+            },
+        ];
+        let mid = sample_stops(&stops, 0.5, ColorSpace::Rgb);
+        assert_eq!((mid.r, mid.g, mid.b), (255, 0, 0));
+        assert!((mid.a as i32 - 128).abs() <= 1);
+    }
+
+    #[test]
+    fn scanline_fill_covers_what_triangles_did() {
+        let mut path = Path::new();
+        for i in 0..24 {
+            let a = (i as f32 * 15.0 + 4.0).to_radians();
+            let p = Vec2 {
+                x: 100.3 + 90.0 * a.cos(),
+                y: 100.7 + 90.0 * a.sin(),
+            };
+            if i == 0 {
+                path.move_to(p);
+            } else {
+                path.line_to(p);
+            }
+        }
+        path.close();
+        let paint = Paint::Solid(Color {
+            r: 0,
+            g: 128,
+            b: 255,
+            a: 255,
+        });
+        let mut spans = vec![0u8; 200 * 200 * 4];
+        let mut tris = vec![0u8; 200 * 200 * 4];
+        draw_path(&path, paint.clone(), &mut spans, 200, 200, 200 * 4);
+        draw_path_triangles(&path, paint, &mut tris, 200, 200, 200 * 4);
+        assert!(spans.chunks(4).filter(|px| px[3] != 0).count() > 20_000);
+        // Spans fill the same pixels solid and only add a partial fringe.
+        for (span, tri) in spans.chunks(4).zip(tris.chunks(4)) {
+            if tri[3] == 255 {
+                assert_eq!(span, tri);
+            } else {
+                assert!(span[3] < 255);
+            }
+        }
+    }
+
+    #[test]
+    fn none_paint_draws_nothing() {
+        let square = |x0: f32| {
+            let mut path = Path::new();
+            path.move_to(Vec2 { x: x0, y: 1.0 });
+            path.line_to(Vec2 {
+                x: x0 + 3.0,
+                y: 1.0,
+            });
+            path.line_to(Vec2 {
+                x: x0 + 3.0,
+                y: 4.0,
+            });
+            path.line_to(Vec2 { x: x0, y: 4.0 });
+            path.close();
+            path
+        };
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        draw_path(&square(0.0), Paint::None, &mut buf, 8, 8, 8 * 4);
+        draw_path(
+            &square(4.0),
+            Paint::Solid(Color {
+                r: 0,
+                g: 0,
+                b: 255,
+                a: 255,
+            }),
+            &mut buf,
+            8,
+            8,
+            8 * 4,
+        );
+        let none_px = 2 * 8 * 4 + 4;
+        assert_eq!(&buf[none_px..none_px + 4], &[0, 0, 0, 0]);
+        let solid_px = 2 * 8 * 4 + 5 * 4;
+        assert_eq!(&buf[solid_px..solid_px + 4], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn blending_rounds_to_nearest() {
+        // Opaque white at half coverage over opaque black: 127.5 rounds to 128.
+        let mut dest = vec![0, 0, 0, 255];
+        blend_layer_pixel(&mut dest, &[255, 255, 255, 255], 0, 0.5);
+        assert_eq!(dest, [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn degenerate_triangles_draw_nothing() {
+        let before: Vec<u8> = (0..4 * 4 * 4).map(|i| i as u8).collect();
+        let mut buf = before.clone();
+        let red = Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let paint = Paint::Solid(red);
+        let nan = Vec2 {
+            x: f32::NAN,
+            y: 1.0,
+        };
+        let (a, b) = (Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 4.0, y: 4.0 });
+        fill_triangle_paint(a, b, nan, [true; 3], &paint, None, &mut buf, 4, 4, 16);
+        let mask = Some(&[255u8; 16][..]);
+        fill_triangle_paint(a, nan, b, [true; 3], &paint, mask, &mut buf, 4, 4, 16);
+        // Collinear vertices through pixel centers.
+        let line = [0.5, 1.5, 3.5].map(|x| Vec2 { x, y: 0.5 });
+        fill_triangle_paint(
+            line[0], line[1], line[2], [false; 3], &paint, None, &mut buf, 4, 4, 16,
+        );
+        assert_eq!(buf, before);
+    }
+
+    #[test]
+    fn fill_edges_are_antialiased_outside() {
+        // Left edge a quarter pixel short of pixel 1's center; the others
+        // lie on pixel boundaries.
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 1.75, y: 2.0 });
+        path.line_to(Vec2 { x: 6.0, y: 2.0 });
+        path.line_to(Vec2 { x: 6.0, y: 6.0 });
+        path.line_to(Vec2 { x: 1.75, y: 6.0 });
+        path.close();
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        let paint = Paint::Solid(Color {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 255,
+        });
+        draw_fill(&path, FillRule::NonZero, paint, &mut buf, 8, 8, 8 * 4);
+        let alpha = |x: usize, y: usize| buf[(y * 8 + x) * 4 + 3];
+        assert_eq!(alpha(1, 3), 64);
+        assert_eq!(alpha(2, 3), 255);
+        assert_eq!(alpha(0, 3), 0);
+        assert_eq!(alpha(3, 1), 0);
+        assert_eq!(alpha(6, 3), 0);
+    }
+
+    #[test]
+    fn slanted_fill_edge_has_partial_pixels() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 1.0, y: 1.0 });
+        path.line_to(Vec2 { x: 30.0, y: 9.0 });
+        path.line_to(Vec2 { x: 1.0, y: 19.0 });
+        path.close();
+        let mut buf = vec![0u8; 32 * 20 * 4];
+        let paint = Paint::Solid(Color {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255,
+        });
+        draw_fill(&path, FillRule::NonZero, paint, &mut buf, 32, 20, 32 * 4);
+        let partial = buf.chunks(4).filter(|px| px[3] > 0 && px[3] < 255).count();
+        assert!(partial > 20, "{partial} partially covered pixels");
+        // Partial pixels keep the paint's color, premultiplied.
+        for px in buf.chunks(4).filter(|px| px[3] > 0) {
+            assert_eq!(px[2], px[3]);
+        }
+    }
+
+    #[test]
+    fn diagonal_stroke_edges_are_antialiased() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 2.0, y: 3.0 });
+        path.line_to(Vec2 { x: 28.0, y: 17.0 });
+        let mut buf = vec![0u8; 32 * 20 * 4];
+        let paint = Paint::Solid(Color {
+            r: 0,
+            g: 0,
+            b: 255,
+            a: 255,
+        });
+        draw_stroke(
+            &path,
+            3.0,
+            StrokeStyle::default(),
+            paint,
+            &mut buf,
+            32,
+            20,
+            32 * 4,
+        );
+        let alphas: Vec<u8> = buf.chunks(4).map(|px| px[3]).collect();
+        assert!(alphas.contains(&255));
+        let partial = alphas.iter().filter(|&&a| a > 0 && a < 255).count();
+        assert!(partial > 20, "{partial} partially covered pixels");
+    }
+
+    #[test]
+    fn round_caps_extend_past_endpoints() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 10.0, y: 10.0 });
+        path.line_to(Vec2 { x: 20.0, y: 10.0 });
+        let paint = Paint::Solid(Color {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 255,
+        });
+        let alpha_at = |cap, x: usize| {
+            let style = StrokeStyle {
+                cap,
+                ..StrokeStyle::default()
+            };
+            let mut buf = vec![0u8; 32 * 20 * 4];
+            draw_stroke(&path, 4.0, style, paint.clone(), &mut buf, 32, 20, 32 * 4);
+            buf[(9 * 32 + x) * 4 + 3]
+        };
+        // Pixel 21 is centered 1.5 px past the end, pixel 8 1.5 px before.
+        assert_eq!(
+            (alpha_at(LineCap::Butt, 21), alpha_at(LineCap::Butt, 8)),
+            (0, 0)
+        );
+        assert!(alpha_at(LineCap::Round, 21) > 0);
+        assert!(alpha_at(LineCap::Round, 8) > 0);
+        assert_eq!(alpha_at(LineCap::Round, 23), 0);
+    }
+
+    #[test]
+    fn right_angle_corner_is_joined() {
+        let mut path = Path::new();
+        path.move_to(Vec2 { x: 4.5, y: 10.5 });
+        path.line_to(Vec2 { x: 12.5, y: 10.5 });
+        path.line_to(Vec2 { x: 12.5, y: 2.5 });
+        let paint = Paint::Solid(Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        });
+        // Pixel (13, 11) lies outside both segment bodies, in the outer
+        // corner that the join has to fill.
+        for join in [LineJoin::Miter, LineJoin::Round] {
+            let style = StrokeStyle {
+                join,
+                ..StrokeStyle::default()
+            };
+            let mut buf = vec![0u8; 16 * 16 * 4];
+            draw_stroke(&path, 4.0, style, paint.clone(), &mut buf, 16, 16, 16 * 4);
+            assert_eq!(buf[(11 * 16 + 13) * 4 + 3], 255, "{join:?}");
+        }
+    }
+
+    #[test]
+    fn degenerate_gradient_stops() {
+        let teal = Color {
+            r: 0,
+            g: 128,
+            b: 128,
+            a: 200,
+        };
+        let single = [GradientStop {
+            offset: 0.7,
+            color: teal,
+        }];
+        for t in [-1.0, 0.0, 0.7, 0.9, 2.0] {
+            assert_eq!(sample_stops(&single, t, ColorSpace::Rgb), teal, "t = {t}");
+        }
+        let black = Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        assert_eq!(sample_stops(&[], 0.5, ColorSpace::OkLab), black);
+    }
 }
@@ -0,0 +1,192 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: layer effects
+//! Mirrors: none (rlottie-rs extension)
+
+use crate::types::{Color, LayerEffect};
+
+/// Apply `effects` in order to a layer rendered on its own into `buf`.
+///
+/// `buf` holds premultiplied RGBA as left by drawing over a cleared buffer.
+/// Lengths in the effects are composition units and are multiplied by
+/// `unit` to get pixels.
+pub fn apply_effects(
+    effects: &[LayerEffect],
+    buf: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    unit: f32,
+) {
+    for effect in effects {
+        match *effect {
+            LayerEffect::GaussianBlur { radius } => {
+                gaussian_blur(buf, width, height, stride, radius * unit);
+            }
+            LayerEffect::Tint {
+                black,
+                white,
+                amount,
+            } => tint(buf, width, height, stride, black, white, amount),
+            LayerEffect::DropShadow {
+                color,
+                opacity,
+                direction,
+                distance,
+                softness,
+            } => {
+                let angle = direction.to_radians();
+                let offset = (
+                    (angle.sin() * distance * unit).round() as isize,
+                    (-angle.cos() * distance * unit).round() as isize,
+                );
+                let mut shadow = vec![0u8; buf.len()];
+                for y in 0..height {
+                    for x in 0..width {
+                        let (Some(sx), Some(sy)) = (
+                            x.checked_add_signed(-offset.0).filter(|&sx| sx < width),
+                            y.checked_add_signed(-offset.1).filter(|&sy| sy < height),
+                        ) else {
+                            continue;
+                        };
+                        let a = buf[sy * stride + sx * 4 + 3] as f32 / 255.0
+                            * opacity
+                            * (color.a as f32 / 255.0);
+                        let o = y * stride + x * 4;
+                        shadow[o] = (color.r as f32 * a).round() as u8;
+                        shadow[o + 1] = (color.g as f32 * a).round() as u8;
+                        shadow[o + 2] = (color.b as f32 * a).round() as u8;
+                        shadow[o + 3] = (a * 255.0).round() as u8;
+                    }
+                }
+                gaussian_blur(&mut shadow, width, height, stride, softness * unit);
+                // Layer over its shadow, both premultiplied.
+                for y in 0..height {
+                    for x in 0..width {
+                        let o = y * stride + x * 4;
+                        let ia = 1.0 - buf[o + 3] as f32 / 255.0;
+                        for c in 0..4 {
+                            let v = buf[o + c] as f32 + shadow[o + c] as f32 * ia;
+                            buf[o + c] = v.round().min(255.0) as u8;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Separable Gaussian blur of premultiplied RGBA. `radius` spans two
+/// standard deviations; pixels outside the buffer count as transparent.
+fn gaussian_blur(buf: &mut [u8], width: usize, height: usize, stride: usize, radius: f32) {
+    let sigma = radius * 0.5;
+    if sigma.is_nan() || sigma < 0.1 {
+        return;
+    }
+    let reach = (sigma * 3.0).ceil() as isize;
+    let mut kernel: Vec<f32> = (-reach..=reach)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|k| *k /= total);
+
+    let mut rows = vec![0f32; width * height * 4];
+    for y in 0..height {
+        for x in 0..width {
+            for (k, w) in kernel.iter().enumerate() {
+                let Some(sx) = x
+                    .checked_add_signed(k as isize - reach)
+                    .filter(|&sx| sx < width)
+                else {
+                    continue;
+                };
+                let src = y * stride + sx * 4;
+                let dst = (y * width + x) * 4;
+                for c in 0..4 {
+                    rows[dst + c] += buf[src + c] as f32 * w;
+                }
+            }
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let mut acc = [0f32; 4];
+            for (k, w) in kernel.iter().enumerate() {
+                let Some(sy) = y
+                    .checked_add_signed(k as isize - reach)
+                    .filter(|&sy| sy < height)
+                else {
+                    continue;
+                };
+                let src = (sy * width + x) * 4;
+                for c in 0..4 {
+                    acc[c] += rows[src + c] * w;
+                }
+            }
+            let o = y * stride + x * 4;
+            for c in 0..4 {
+                buf[o + c] = acc[c].round().min(255.0) as u8;
+            }
+        }
+    }
+}
+
+/// Map each pixel's luminance onto the `black`..`white` ramp and mix the
+/// result with the original color by `amount`. Alpha is left unchanged.
+fn tint(
+    buf: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+    black: Color,
+    white: Color,
+    amount: f32,
+) {
+    let amount = amount.clamp(0.0, 1.0);
+    let black = [black.r, black.g, black.b].map(f32::from);
+    let white = [white.r, white.g, white.b].map(f32::from);
+    for y in 0..height {
+        for x in 0..width {
+            let o = y * stride + x * 4;
+            let a = buf[o + 3] as f32 / 255.0;
+            if a == 0.0 {
+                continue;
+            }
+            let straight = [0, 1, 2].map(|c| (buf[o + c] as f32 / a).min(255.0));
+            let luma = (0.299 * straight[0] + 0.587 * straight[1] + 0.114 * straight[2]) / 255.0;
+            for c in 0..3 {
+                let mapped = black[c] + (white[c] - black[c]) * luma;
+                let out = straight[c] + (mapped - straight[c]) * amount;
+                buf[o + c] = (out * a).round().min(255.0) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blur_spreads_coverage_without_darkening() {
+        let mut buf = vec![0u8; 9 * 9 * 4];
+        let center = (4 * 9 + 4) * 4;
+        buf[center..center + 4].copy_from_slice(&[255, 0, 0, 255]);
+        apply_effects(
+            &[LayerEffect::GaussianBlur { radius: 2.0 }],
+            &mut buf,
+            9,
+            9,
+            9 * 4,
+            1.0,
+        );
+        let next = center + 4;
+        assert!(buf[center + 3] < 255);
+        assert!(buf[next + 3] > 0);
+        // Premultiplied red stays pure red wherever it spread.
+        for px in buf.chunks(4) {
+            assert_eq!(px[0], px[3]);
+            assert_eq!((px[1], px[2]), (0, 0));
+        }
+    }
+}
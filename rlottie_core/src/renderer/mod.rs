@@ -2,7 +2,13 @@
 //! Mirrors: rlottie/src/vpainter.cpp (simplified)
 
 pub mod cpu;
+pub mod effects;
+pub mod options;
+pub mod player;
 pub use cpu::*;
+pub use effects::*;
+pub use options::*;
+pub use player::*;
 
 #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
 pub mod wasm;
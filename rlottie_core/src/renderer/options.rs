@@ -0,0 +1,1004 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: render configuration
+//! Mirrors: rlottie/inc/rlottie.h (Surface)
+
+use crate::types::{BlendMode, Color, Composition, Layer, LayerId, Vec2};
+use std::cell::Cell;
+
+/// Origin of the coordinate space that layer positions are relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Origin {
+    /// Lottie convention: `(0,0)` is the top-left corner of the composition.
+    #[default]
+    TopLeft,
+    /// `(0,0)` is the center of the composition.
+    Center,
+}
+
+/// Counters collected while rendering a single frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    /// Number of mask or matte coverage buffers allocated
+    pub mask_buffers: u32,
+    /// Number of fill and stroke paths handed to the rasterizer
+    pub paths_drawn: u32,
+}
+
+/// Reason a render request was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderError {
+    /// Width or height is larger than [`Renderer::max_dimension`].
+    DimensionTooLarge {
+        /// Requested width
+        width: usize,
+        /// Requested height
+        height: usize,
+        /// Limit that was exceeded
+        max: usize,
+    },
+}
+
+/// Default for [`Renderer::max_dimension`].
+const DEFAULT_MAX_DIMENSION: usize = 16384;
+
+/// Axis-aligned pixel rectangle within a render buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    /// Left edge in pixels
+    pub x: usize,
+    /// Top edge in pixels
+    pub y: usize,
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+}
+
+impl Rect {
+    /// Whether the rectangle covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Frame renderer carrying options that affect how compositions are drawn.
+///
+/// The options are public fields, but the renderer also keeps private
+/// per-render state, so code outside this crate cannot build one with
+/// struct update syntax. Start from [`Renderer::new`] and chain the
+/// `with_*` methods, or assign the fields of a mutable renderer.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    /// Coordinate origin applied before layer transforms
+    pub origin: Origin,
+    /// Ignore the declared composition size and scale the content bounds of
+    /// the whole animation to fit the buffer, centered with aspect preserved.
+    /// Takes precedence over `origin`.
+    pub auto_fit: bool,
+    /// Pixel aspect ratio used instead of the composition's own
+    pub pixel_aspect: Option<f32>,
+    /// Opacity used instead of the composition's own
+    pub opacity: Option<f32>,
+    /// Clear the buffer to `clear_color` before [`Renderer::render`] draws.
+    /// When `false` the frame accumulates over whatever the buffer holds.
+    pub clear: bool,
+    /// Color the buffer is cleared to, stored premultiplied like drawn pixels
+    pub clear_color: Color,
+    /// Largest width or height a render may request. Larger requests fail
+    /// with [`RenderError::DimensionTooLarge`] before anything is allocated.
+    pub max_dimension: usize,
+    /// Mirror the frame vertically, for hosts whose buffers have `y`
+    /// pointing up. Lottie content is `y`-down.
+    pub y_up: bool,
+    /// Only draw (and clear) pixels inside this rectangle of the buffer.
+    /// Content keeps the placement it has in the full buffer.
+    pub clip: Option<Rect>,
+    state: RenderState,
+}
+
+/// Per-render bookkeeping a [`Renderer`] updates through `&self`.
+#[derive(Debug, Clone, Default)]
+struct RenderState {
+    stats: Cell<RenderStats>,
+    /// Content hash and animation bounds of the last auto-fitted composition
+    fit_bounds: Cell<Option<(u64, Vec2, Vec2)>>,
+    /// Full buffer size and clip origin while a clipped draw is running
+    clipped: Cell<Option<(usize, usize, Vec2)>>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self {
+            origin: Origin::default(),
+            auto_fit: false,
+            pixel_aspect: None,
+            opacity: None,
+            clear: true,
+            clear_color: Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+            max_dimension: DEFAULT_MAX_DIMENSION,
+            y_up: false,
+            clip: None,
+            state: RenderState::default(),
+        }
+    }
+}
+
+impl Renderer {
+    /// Create a renderer with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set [`Renderer::origin`].
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Set [`Renderer::auto_fit`].
+    pub fn with_auto_fit(mut self, auto_fit: bool) -> Self {
+        self.auto_fit = auto_fit;
+        self
+    }
+
+    /// Set [`Renderer::pixel_aspect`].
+    pub fn with_pixel_aspect(mut self, pixel_aspect: f32) -> Self {
+        self.pixel_aspect = Some(pixel_aspect);
+        self
+    }
+
+    /// Set [`Renderer::opacity`].
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = Some(opacity);
+        self
+    }
+
+    /// Set [`Renderer::clear`].
+    pub fn with_clear(mut self, clear: bool) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    /// Set [`Renderer::clear_color`].
+    pub fn with_clear_color(mut self, color: Color) -> Self {
+        self.clear_color = color;
+        self
+    }
+
+    /// Set [`Renderer::max_dimension`].
+    pub fn with_max_dimension(mut self, max: usize) -> Self {
+        self.max_dimension = max;
+        self
+    }
+
+    /// Set [`Renderer::y_up`].
+    pub fn with_y_up(mut self, y_up: bool) -> Self {
+        self.y_up = y_up;
+        self
+    }
+
+    /// Set [`Renderer::clip`].
+    pub fn with_clip(mut self, clip: Rect) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    /// Render `frame` of `comp` into the provided RGBA8888 buffer.
+    ///
+    /// Returns the smallest rectangle containing every pixel the frame
+    /// changed from the cleared state, or from the buffer's previous
+    /// contents when [`Renderer::clear`] is off. It lies inside
+    /// [`Renderer::clip`]; a frame that draws nothing yields an empty
+    /// rectangle.
+    pub fn render(
+        &self,
+        comp: &Composition,
+        frame: u32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> Result<Rect, RenderError> {
+        self.check_size(width, height)?;
+        self.state.stats.set(RenderStats::default());
+        let mut rect = Rect::default();
+        self.draw_clipped(buffer, width, height, stride, |buf, w, h| {
+            self.clear_buffer(buf, w, h, stride);
+            let before: Vec<u8> = (0..h)
+                .flat_map(|y| &buf[y * stride..y * stride + w * 4])
+                .copied()
+                .collect();
+            comp.draw_frame(self, frame as f32, buf, w, h, stride);
+            rect = dirty_rect(&before, buf, w, h, stride);
+        });
+        if let Some(clip) = self.clip.filter(|_| !rect.is_empty()) {
+            rect.x += clip.x;
+            rect.y += clip.y;
+        }
+        Ok(rect)
+    }
+
+    /// Render `frame` of `comp` at its declared size and crop the result to
+    /// the pixels the frame drew, as reported by [`Renderer::render`].
+    ///
+    /// Returns the tightly packed RGBA8888 crop along with its position in
+    /// the full frame. An empty frame yields an empty buffer and rectangle.
+    pub fn render_cropped(
+        &self,
+        comp: &Composition,
+        frame: u32,
+    ) -> Result<(Vec<u8>, Rect), RenderError> {
+        let (width, height) = (comp.width as usize, comp.height as usize);
+        self.check_size(width, height)?;
+        let mut buf = vec![0u8; width * height * 4];
+        let rect = self.render(comp, frame, &mut buf, width, height, width * 4)?;
+        let cropped = (rect.y..rect.y + rect.height)
+            .flat_map(|y| {
+                let start = (y * width + rect.x) * 4;
+                &buf[start..start + rect.width * 4]
+            })
+            .copied()
+            .collect();
+        Ok((cropped, rect))
+    }
+
+    /// Composite `frame` of `comp` over the existing contents of `buffer`.
+    ///
+    /// The buffer is never cleared, whatever [`Renderer::clear`] says, so
+    /// the animation can be drawn over a backdrop already in place.
+    pub fn render_over(
+        &self,
+        comp: &Composition,
+        frame: u32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> Result<(), RenderError> {
+        self.check_size(width, height)?;
+        self.state.stats.set(RenderStats::default());
+        self.draw_clipped(buffer, width, height, stride, |buf, w, h| {
+            comp.draw_frame(self, frame as f32, buf, w, h, stride)
+        });
+        Ok(())
+    }
+
+    /// Render every top-level layer of `comp` into its own transparent
+    /// RGBA8888 plane of `width`×`height`, tightly packed.
+    ///
+    /// Planes come back in layer order, one per layer, with the blend mode
+    /// the layer is stacked with; matte sources and null layers yield empty
+    /// planes and a matted layer's plane already has its matte applied.
+    /// Compositing the planes in order with
+    /// [`crate::renderer::cpu::composite_layer`] at full opacity and each
+    /// plane's mode reproduces a normal render; planes are drawn at full
+    /// composition opacity, which is left to the host to apply when it
+    /// stacks them.
+    pub fn render_planes(
+        &self,
+        comp: &Composition,
+        frame: u32,
+        width: usize,
+        height: usize,
+    ) -> Result<Vec<(LayerId, BlendMode, Vec<u8>)>, RenderError> {
+        self.check_size(width, height)?;
+        self.state.stats.set(RenderStats::default());
+        Ok(comp
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(idx, layer)| {
+                let id = LayerId(idx);
+                let mode = match layer {
+                    Layer::Shape(shape) => shape.blend_mode,
+                    _ => BlendMode::Normal,
+                };
+                let mut plane = vec![0u8; width * height * 4];
+                self.draw_clipped(&mut plane, width, height, width * 4, |buf, w, h| {
+                    comp.draw_layers_where(self, frame as f32, buf, w, h, width * 4, |l| l == id)
+                });
+                (id, mode, plane)
+            })
+            .collect())
+    }
+
+    /// Scale and offset mapping composition space into a `width`×`height`
+    /// buffer, such that `buffer = comp * scale + offset`.
+    pub(crate) fn viewport(&self, comp: &Composition, width: usize, height: usize) -> (Vec2, Vec2) {
+        // A clipped draw sees only the clip rectangle but keeps the
+        // placement of the full buffer, shifted to the clip origin.
+        if let Some((full_w, full_h, shift)) = self.state.clipped.get() {
+            let (scale, offset) = self.full_viewport(comp, full_w, full_h);
+            return (
+                scale,
+                Vec2 {
+                    x: offset.x - shift.x,
+                    y: offset.y - shift.y,
+                },
+            );
+        }
+        self.full_viewport(comp, width, height)
+    }
+
+    fn full_viewport(&self, comp: &Composition, width: usize, height: usize) -> (Vec2, Vec2) {
+        let (scale, offset) = self.y_down_viewport(comp, width, height);
+        if !self.y_up {
+            return (scale, offset);
+        }
+        // Reflect about the middle row of the buffer.
+        (
+            Vec2 {
+                x: scale.x,
+                y: -scale.y,
+            },
+            Vec2 {
+                x: offset.x,
+                y: height as f32 - offset.y,
+            },
+        )
+    }
+
+    fn y_down_viewport(&self, comp: &Composition, width: usize, height: usize) -> (Vec2, Vec2) {
+        let par = self.pixel_aspect.unwrap_or(comp.pixel_aspect);
+        if self.auto_fit {
+            if let Some(fit) = fit_viewport(self.animation_bounds(comp), par, width, height) {
+                return fit;
+            }
+        }
+        let scale = Vec2 {
+            x: width as f32 / comp.width as f32 * par,
+            y: height as f32 / comp.height as f32,
+        };
+        let origin = match self.origin {
+            Origin::TopLeft => Vec2::default(),
+            Origin::Center => Vec2 {
+                x: comp.width as f32 * 0.5,
+                y: comp.height as f32 * 0.5,
+            },
+        };
+        let offset = Vec2 {
+            x: origin.x * scale.x,
+            y: origin.y * scale.y,
+        };
+        (scale, offset)
+    }
+
+    /// Map a point in composition space to buffer pixel space.
+    pub fn to_buffer_space(
+        &self,
+        comp: &Composition,
+        width: usize,
+        height: usize,
+        comp_pt: Vec2,
+    ) -> Vec2 {
+        let (scale, offset) = self.viewport(comp, width, height);
+        Vec2 {
+            x: comp_pt.x * scale.x + offset.x,
+            y: comp_pt.y * scale.y + offset.y,
+        }
+    }
+
+    /// Map a point in buffer pixel space back to composition space.
+    pub fn to_comp_space(
+        &self,
+        comp: &Composition,
+        width: usize,
+        height: usize,
+        buf_pt: Vec2,
+    ) -> Vec2 {
+        let (scale, offset) = self.viewport(comp, width, height);
+        Vec2 {
+            x: (buf_pt.x - offset.x) / scale.x,
+            y: (buf_pt.y - offset.y) / scale.y,
+        }
+    }
+
+    /// Counters gathered during the most recent [`Renderer::render`] call.
+    pub fn stats(&self) -> RenderStats {
+        self.state.stats.get()
+    }
+
+    /// Refuse sizes above `max_dimension`.
+    pub(crate) fn check_size(&self, width: usize, height: usize) -> Result<(), RenderError> {
+        if width > self.max_dimension || height > self.max_dimension {
+            return Err(RenderError::DimensionTooLarge {
+                width,
+                height,
+                max: self.max_dimension,
+            });
+        }
+        Ok(())
+    }
+
+    /// Clear the `width`×`height` pixels of `buffer` to `clear_color` if
+    /// `clear` is set, leaving any row padding alone.
+    pub(crate) fn clear_buffer(
+        &self,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) {
+        if !self.clear {
+            return;
+        }
+        let c = self.clear_color;
+        let premul = |v: u8| (v as u16 * c.a as u16 / 255) as u8;
+        let px = [premul(c.r), premul(c.g), premul(c.b), c.a];
+        for y in 0..height {
+            let Some(row) = buffer.get_mut(y * stride..y * stride + width * 4) else {
+                break;
+            };
+            for chunk in row.chunks_exact_mut(4) {
+                chunk.copy_from_slice(&px);
+            }
+        }
+    }
+
+    /// Run `draw` on the part of `buffer` inside [`Renderer::clip`], or on
+    /// the whole buffer without a clip. `draw` gets the sub-buffer, which
+    /// keeps `stride`, and its width and height.
+    pub(crate) fn draw_clipped(
+        &self,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+        draw: impl FnOnce(&mut [u8], usize, usize),
+    ) {
+        let Some(clip) = self.clip else {
+            draw(buffer, width, height);
+            return;
+        };
+        let (x0, y0) = (clip.x.min(width), clip.y.min(height));
+        let x1 = clip.x.saturating_add(clip.width).min(width);
+        let y1 = clip.y.saturating_add(clip.height).min(height);
+        if x1 == x0 || y1 == y0 {
+            return;
+        }
+        let Some(sub) = buffer.get_mut(y0 * stride + x0 * 4..) else {
+            return;
+        };
+        let shift = Vec2 {
+            x: x0 as f32,
+            y: y0 as f32,
+        };
+        self.state.clipped.set(Some((width, height, shift)));
+        draw(sub, x1 - x0, y1 - y0);
+        self.state.clipped.set(None);
+    }
+
+    /// [`Composition::animation_bounds`] of `comp`, computed once and
+    /// reused for as long as its [`Composition::content_hash`] is unchanged.
+    fn animation_bounds(&self, comp: &Composition) -> (Vec2, Vec2) {
+        let key = comp.content_hash();
+        if let Some((hash, min, max)) = self.state.fit_bounds.get() {
+            if hash == key {
+                return (min, max);
+            }
+        }
+        let (min, max) = comp.animation_bounds(0.2);
+        self.state.fit_bounds.set(Some((key, min, max)));
+        (min, max)
+    }
+
+    pub(crate) fn record(&self, f: impl FnOnce(&mut RenderStats)) {
+        let mut stats = self.state.stats.get();
+        f(&mut stats);
+        self.state.stats.set(stats);
+    }
+}
+
+/// Scale and offset placing the content `bounds` in the middle of a
+/// `width`×`height` buffer, uniform apart from the pixel aspect `par`.
+/// `None` when there is no content.
+fn fit_viewport(
+    (min, max): (Vec2, Vec2),
+    par: f32,
+    width: usize,
+    height: usize,
+) -> Option<(Vec2, Vec2)> {
+    let (w, h) = ((max.x - min.x) * par, max.y - min.y);
+    if w <= 0.0 || h <= 0.0 {
+        return None;
+    }
+    let s = (width as f32 / w).min(height as f32 / h);
+    let scale = Vec2 { x: s * par, y: s };
+    let offset = Vec2 {
+        x: (width as f32 - w * s) * 0.5 - min.x * scale.x,
+        y: (height as f32 - h * s) * 0.5 - min.y * s,
+    };
+    Some((scale, offset))
+}
+
+/// Bounding box of the pixels of `buffer` that differ from `before`, the
+/// same `width`×`height` pixels tightly packed.
+fn dirty_rect(before: &[u8], buffer: &[u8], width: usize, height: usize, stride: usize) -> Rect {
+    let (mut x0, mut y0, mut x1, mut y1) = (usize::MAX, usize::MAX, 0, 0);
+    for y in 0..height {
+        let Some(row) = buffer.get(y * stride..y * stride + width * 4) else {
+            break;
+        };
+        let old = &before[y * width * 4..(y + 1) * width * 4];
+        let mut changed = row
+            .chunks_exact(4)
+            .zip(old.chunks_exact(4))
+            .enumerate()
+            .filter(|(_, (px, was))| px != was);
+        let Some((first, _)) = changed.next() else {
+            continue;
+        };
+        let last = changed.next_back().map_or(first, |(x, _)| x);
+        x0 = x0.min(first);
+        x1 = x1.max(last + 1);
+        y0 = y0.min(y);
+        y1 = y + 1;
+    }
+    if x0 == usize::MAX {
+        return Rect::default();
+    }
+    Rect {
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Path;
+    use crate::types::ShapeLayer;
+    use std::collections::HashMap;
+
+    fn centered_square() -> Composition {
+        let mut square = Path::new();
+        square.move_to(Vec2 { x: -2.0, y: -2.0 });
+        square.line_to(Vec2 { x: 2.0, y: -2.0 });
+        square.line_to(Vec2 { x: 2.0, y: 2.0 });
+        square.line_to(Vec2 { x: -2.0, y: 2.0 });
+        square.close();
+        let shape = ShapeLayer {
+            paths: vec![square],
+            fill: Some(Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            }),
+            ..Default::default()
+        };
+        Composition {
+            width: 16,
+            height: 16,
+            start_frame: 0,
+            end_frame: 0,
+            fps: 30.0,
+            pixel_aspect: 1.0,
+            opacity: 1.0,
+            layers: vec![Layer::Shape(shape)],
+            color_controls: HashMap::new(),
+            markers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn center_origin_moves_shape_to_middle() {
+        let comp = centered_square();
+        let alpha = |buf: &[u8], x: usize, y: usize| buf[(y * 16 + x) * 4 + 3];
+
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        Renderer::new()
+            .render(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+        assert_eq!(alpha(&buf, 0, 0), 255);
+        assert_eq!(alpha(&buf, 8, 8), 0);
+
+        let renderer = Renderer {
+            origin: Origin::Center,
+            ..Default::default()
+        };
+        renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        assert_eq!(alpha(&buf, 0, 0), 0);
+        assert_eq!(alpha(&buf, 8, 8), 255);
+    }
+
+    #[test]
+    fn buffer_space_round_trip() {
+        let mut comp = centered_square();
+        comp.width = 10;
+        comp.height = 20;
+        for origin in [Origin::TopLeft, Origin::Center] {
+            let renderer = Renderer {
+                origin,
+                ..Default::default()
+            };
+            let p = Vec2 { x: 3.25, y: -7.5 };
+            let buf = renderer.to_buffer_space(&comp, 40, 30, p);
+            let back = renderer.to_comp_space(&comp, 40, 30, buf);
+            assert!((back.x - p.x).abs() < 1e-4);
+            assert!((back.y - p.y).abs() < 1e-4);
+        }
+        let renderer = Renderer::new();
+        let corner = renderer.to_buffer_space(&comp, 40, 30, Vec2 { x: 10.0, y: 20.0 });
+        assert_eq!(corner, Vec2 { x: 40.0, y: 30.0 });
+    }
+
+    #[test]
+    fn render_returns_tight_dirty_rect() {
+        // Top-left origin leaves only the quarter of the square at (0..2, 0..2).
+        let mut comp = centered_square();
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        let rect = Renderer::new()
+            .render(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+        assert_eq!(
+            rect,
+            Rect {
+                x: 0,
+                y: 0,
+                width: 2,
+                height: 2
+            }
+        );
+
+        comp.layers.clear();
+        let rect = Renderer::new()
+            .render(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+        assert!(rect.is_empty());
+        assert_eq!(rect, Rect::default());
+    }
+
+    #[test]
+    fn render_over_blends_with_backdrop() {
+        let mut comp = centered_square();
+        if let Layer::Shape(shape) = &mut comp.layers[0] {
+            shape.fill = Some(Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 128,
+            });
+        }
+        let mut buf: Vec<u8> = [0, 0, 255, 255].repeat(16 * 16);
+        Renderer::new()
+            .render_over(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+
+        // Covered: half red over blue. Uncovered: blue untouched.
+        let px = &buf[..4];
+        assert!(px[0].abs_diff(128) <= 1, "{px:?}");
+        assert_eq!(px[1], 0);
+        assert!(px[2].abs_diff(127) <= 1, "{px:?}");
+        assert_eq!(px[3], 255);
+        let off = (8 * 16 + 8) * 4;
+        assert_eq!(&buf[off..off + 4], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn planes_composite_to_full_render() {
+        let mut comp = centered_square();
+        let mut top = comp.layers[0].clone();
+        if let Layer::Shape(shape) = &mut top {
+            shape.paths[0] = shape.paths[0].map_points(|p| Vec2 {
+                x: p.x * 2.0 + 1.0,
+                y: p.y * 2.0 + 1.0,
+            });
+            shape.fill = Some(Color {
+                r: 0,
+                g: 255,
+                b: 0,
+                a: 255,
+            });
+            shape.blend_mode = BlendMode::Multiply;
+        }
+        comp.layers.push(top);
+
+        let planes = Renderer::new().render_planes(&comp, 0, 16, 16).unwrap();
+        assert_eq!(planes.len(), 2);
+        assert_eq!(planes[0].0, LayerId(0));
+        assert_eq!(planes[1].0, LayerId(1));
+        assert_eq!(planes[1].1, BlendMode::Multiply);
+        assert_ne!(planes[0].2, planes[1].2);
+
+        let mut stacked = vec![0u8; 16 * 16 * 4];
+        for (_, mode, plane) in &planes {
+            crate::renderer::cpu::composite_layer(&mut stacked, plane, 1.0, *mode, 16, 16, 16 * 4);
+        }
+        let mut full = vec![0u8; 16 * 16 * 4];
+        comp.render_sync(0, &mut full, 16, 16, 16 * 4);
+        assert_eq!(stacked, full);
+    }
+
+    #[test]
+    fn skipping_clear_keeps_existing_pixels() {
+        let comp = centered_square();
+        let mut buf: Vec<u8> = [0, 255, 0, 255].repeat(16 * 16);
+        let renderer = Renderer {
+            clear: false,
+            ..Renderer::default()
+        };
+        renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        // The square covers the top-left corner; the rest stays green.
+        assert_eq!(&buf[..4], &[255, 0, 0, 255]);
+        let off = (8 * 16 + 8) * 4;
+        assert_eq!(&buf[off..off + 4], &[0, 255, 0, 255]);
+
+        let renderer = Renderer {
+            clear_color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 128,
+            },
+            ..Renderer::default()
+        };
+        renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        assert_eq!(&buf[off..off + 4], &[128, 128, 128, 128]);
+    }
+
+    #[test]
+    fn pixel_aspect_stretches_horizontally() {
+        // Square at 2..6 in composition space.
+        let mut comp = centered_square();
+        if let Layer::Shape(shape) = &mut comp.layers[0] {
+            shape.paths[0] = shape.paths[0].map_points(|p| Vec2 {
+                x: p.x + 4.0,
+                y: p.y + 4.0,
+            });
+        }
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        let square = Renderer::new()
+            .render(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+        assert_eq!((square.x, square.width, square.height), (2, 4, 4));
+
+        comp.pixel_aspect = 2.0;
+        let wide = Renderer::new()
+            .render(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+        assert_eq!((wide.x, wide.width, wide.height), (4, 8, 4));
+
+        let renderer = Renderer {
+            pixel_aspect: Some(1.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap(),
+            square
+        );
+    }
+
+    #[test]
+    fn oversized_requests_fail_before_allocating() {
+        // A 100000² crop would need 40 GB if anything were allocated.
+        let mut comp = centered_square();
+        comp.width = 100_000;
+        comp.height = 100_000;
+        let renderer = Renderer::new();
+        assert_eq!(
+            renderer.render_cropped(&comp, 0),
+            Err(RenderError::DimensionTooLarge {
+                width: 100_000,
+                height: 100_000,
+                max: 16384,
+            })
+        );
+
+        let renderer = Renderer {
+            max_dimension: 8,
+            ..Default::default()
+        };
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        let err = renderer.render(&comp, 0, &mut buf, 16, 4, 16 * 4);
+        assert!(matches!(
+            err,
+            Err(RenderError::DimensionTooLarge { max: 8, .. })
+        ));
+        assert!(buf.iter().all(|&b| b == 0));
+        assert!(renderer.render_planes(&comp, 0, 4, 16).is_err());
+    }
+
+    #[test]
+    fn render_cropped_returns_covered_bounds() {
+        let mut comp = centered_square();
+        let renderer = Renderer {
+            origin: Origin::Center,
+            ..Default::default()
+        };
+        let (pixels, rect) = renderer.render_cropped(&comp, 0).unwrap();
+        assert_eq!(
+            rect,
+            Rect {
+                x: 6,
+                y: 6,
+                width: 4,
+                height: 4
+            }
+        );
+        assert_eq!(pixels.len(), 4 * 4 * 4);
+        assert!(pixels.chunks(4).all(|px| px == [255, 0, 0, 255]));
+
+        comp.layers.clear();
+        let (pixels, rect) = renderer.render_cropped(&comp, 0).unwrap();
+        assert!(pixels.is_empty());
+        assert_eq!(rect, Rect::default());
+    }
+
+    #[test]
+    fn auto_fit_brings_offscreen_content_into_view() {
+        // Declared 16x16, but the square sits at 40..48.
+        let mut comp = centered_square();
+        if let Layer::Shape(shape) = &mut comp.layers[0] {
+            shape.paths[0] = shape.paths[0].map_points(|p| Vec2 {
+                x: p.x + 44.0,
+                y: p.y + 44.0,
+            });
+        }
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        let rect = Renderer::new()
+            .render(&comp, 0, &mut buf, 16, 16, 16 * 4)
+            .unwrap();
+        assert!(rect.is_empty());
+
+        let renderer = Renderer {
+            auto_fit: true,
+            ..Default::default()
+        };
+        let rect = renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        assert_eq!(
+            rect,
+            Rect {
+                x: 0,
+                y: 0,
+                width: 16,
+                height: 16
+            }
+        );
+        let centre = renderer.to_buffer_space(&comp, 16, 16, Vec2 { x: 44.0, y: 44.0 });
+        assert_eq!(centre, Vec2 { x: 8.0, y: 8.0 });
+
+        // The fit is reused between frames but follows edits.
+        if let Layer::Shape(shape) = &mut comp.layers[0] {
+            shape.transform.position = Vec2 { x: -20.0, y: 0.0 };
+        }
+        let centre = renderer.to_buffer_space(&comp, 16, 16, Vec2 { x: 24.0, y: 44.0 });
+        assert_eq!(centre, Vec2 { x: 8.0, y: 8.0 });
+    }
+
+    #[test]
+    fn y_up_mirrors_the_frame_vertically() {
+        let mut comp = centered_square();
+        if let Layer::Shape(shape) = &mut comp.layers[0] {
+            // Move the square into the top half: rows 1..5.
+            shape.transform.position = Vec2 { x: 8.0, y: 3.0 };
+        }
+        let alpha = |buf: &[u8], x: usize, y: usize| buf[(y * 16 + x) * 4 + 3];
+        let mut down = vec![0u8; 16 * 16 * 4];
+        Renderer::new()
+            .render(&comp, 0, &mut down, 16, 16, 16 * 4)
+            .unwrap();
+        assert_eq!(alpha(&down, 8, 2), 255);
+        assert_eq!(alpha(&down, 8, 13), 0);
+
+        let renderer = Renderer {
+            y_up: true,
+            ..Default::default()
+        };
+        let mut up = vec![0u8; 16 * 16 * 4];
+        renderer.render(&comp, 0, &mut up, 16, 16, 16 * 4).unwrap();
+        assert_eq!(alpha(&up, 8, 2), 0);
+        assert_eq!(alpha(&up, 8, 13), 255);
+        for y in 0..16 {
+            assert_eq!(&up[y * 64..][..64], &down[(15 - y) * 64..][..64], "row {y}");
+        }
+        let p = Vec2 { x: 8.0, y: 3.0 };
+        assert_eq!(renderer.to_buffer_space(&comp, 16, 16, p).y, 13.0);
+        assert_eq!(
+            renderer.to_comp_space(&comp, 16, 16, Vec2 { x: 8.0, y: 13.0 }),
+            p
+        );
+    }
+
+    #[test]
+    fn composition_opacity_halves_output_alpha() {
+        let mut comp = centered_square();
+        let mut full = vec![0u8; 16 * 16 * 4];
+        Renderer::new()
+            .render(&comp, 0, &mut full, 16, 16, 16 * 4)
+            .unwrap();
+
+        comp.opacity = 0.5;
+        let mut faded = vec![0u8; 16 * 16 * 4];
+        Renderer::new()
+            .render(&comp, 0, &mut faded, 16, 16, 16 * 4)
+            .unwrap();
+        for (f, h) in full.chunks(4).zip(faded.chunks(4)) {
+            assert!((f[3] as i32 / 2 - h[3] as i32).abs() <= 1);
+        }
+        assert_eq!(faded[3], 128);
+
+        // The renderer's override wins over the composition's own opacity.
+        let renderer = Renderer {
+            opacity: Some(1.0),
+            ..Default::default()
+        };
+        renderer
+            .render(&comp, 0, &mut faded, 16, 16, 16 * 4)
+            .unwrap();
+        assert_eq!(faded, full);
+    }
+
+    #[test]
+    fn clip_restricts_drawing_to_rect() {
+        let comp = centered_square();
+        let renderer = Renderer {
+            origin: Origin::Center,
+            clip: Some(Rect {
+                x: 8,
+                y: 8,
+                width: 6,
+                height: 6,
+            }),
+            ..Default::default()
+        };
+        // The square spans 6..10; the clip holds its lower right quarter.
+        let mut buf = vec![7u8; 16 * 16 * 4];
+        renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        let px = |x: usize, y: usize| &buf[(y * 16 + x) * 4..(y * 16 + x) * 4 + 4];
+        assert_eq!(px(9, 9), [255, 0, 0, 255]);
+        assert_eq!(px(12, 12), [0, 0, 0, 0]);
+        for (x, y) in [(7, 7), (9, 7), (7, 9), (15, 15), (0, 0)] {
+            assert_eq!(px(x, y), [7, 7, 7, 7], "pixel {x},{y}");
+        }
+    }
+
+    #[test]
+    fn dirty_rect_covers_only_what_the_frame_drew() {
+        let comp = centered_square();
+        let clip = Rect {
+            x: 8,
+            y: 8,
+            width: 6,
+            height: 6,
+        };
+        let drawn = Rect {
+            x: 8,
+            y: 8,
+            width: 2,
+            height: 2,
+        };
+        // Accumulating over old contents, clipped.
+        let renderer = Renderer {
+            origin: Origin::Center,
+            clear: false,
+            clip: Some(clip),
+            ..Default::default()
+        };
+        let mut buf = vec![7u8; 16 * 16 * 4];
+        let rect = renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        assert_eq!(rect, drawn);
+
+        // An opaque clear color is the baseline, not drawn content.
+        let renderer = Renderer {
+            clear_color: Color {
+                r: 0,
+                g: 0,
+                b: 255,
+                a: 255,
+            },
+            clear: true,
+            ..renderer
+        };
+        let rect = renderer.render(&comp, 0, &mut buf, 16, 16, 16 * 4).unwrap();
+        assert_eq!(rect, drawn);
+    }
+}
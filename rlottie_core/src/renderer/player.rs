@@ -0,0 +1,109 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+//! Module: pull-based playback
+//! Mirrors: rlottie/inc/rlottie.h (Animation::frameAtPos)
+
+use super::options::{RenderError, Renderer};
+use crate::types::Composition;
+
+/// Plays a composition in real time into an owned RGBA8888 buffer.
+///
+/// The host calls [`Player::advance`] with the time since its last frame
+/// and reads [`Player::current_frame_buffer`]; the buffer is only
+/// re-rendered when the frame under the clock changes.
+#[derive(Debug)]
+pub struct Player {
+    comp: Composition,
+    renderer: Renderer,
+    width: usize,
+    height: usize,
+    buffer: Vec<u8>,
+    clock: f32,
+    frame: u32,
+    /// Restart from the first frame after the last one instead of holding it
+    pub looping: bool,
+}
+
+impl Player {
+    /// Create a looping player rendering `comp` at `width`×`height` and
+    /// draw its first frame.
+    pub fn new(
+        comp: Composition,
+        renderer: Renderer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, RenderError> {
+        renderer.check_size(width, height)?;
+        let mut buffer = vec![0u8; width * height * 4];
+        comp.render_with(&renderer, 0, &mut buffer, width, height, width * 4);
+        Ok(Self {
+            comp,
+            renderer,
+            width,
+            height,
+            buffer,
+            clock: 0.0,
+            frame: 0,
+            looping: true,
+        })
+    }
+
+    /// Move the clock forward by `dt` seconds and render the frame it now
+    /// points at if that changed. Negative or non-finite steps are ignored.
+    pub fn advance(&mut self, dt: f32) {
+        if dt.is_finite() && dt > 0.0 {
+            self.clock += dt;
+        }
+        let frame = self.frame_for_clock();
+        if frame != self.frame {
+            self.frame = frame;
+            let stride = self.width * 4;
+            self.comp.render_with(
+                &self.renderer,
+                frame,
+                &mut self.buffer,
+                self.width,
+                self.height,
+                stride,
+            );
+        }
+    }
+
+    /// Frame shown, counted from the composition's `start_frame`.
+    pub fn current_frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Pixels of the frame shown, tightly packed RGBA8888.
+    pub fn current_frame_buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Seconds of playback since the player was created.
+    pub fn elapsed(&self) -> f32 {
+        self.clock
+    }
+
+    /// Whether a non-looping player has played past its last frame.
+    /// A looping player never finishes.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed_frames() >= self.comp.frame_count() as f32
+    }
+
+    fn elapsed_frames(&self) -> f32 {
+        if self.comp.fps > 0.0 {
+            self.clock * self.comp.fps
+        } else {
+            0.0
+        }
+    }
+
+    fn frame_for_clock(&self) -> u32 {
+        let frames = self.elapsed_frames() as u32;
+        if self.looping {
+            frames % self.comp.frame_count()
+        } else {
+            frames.min(self.comp.frame_count() - 1)
+        }
+    }
+}
@@ -12,10 +12,9 @@ use web_sys::ImageData;
 
 #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
 use crate::{
-    geometry::Path,
     loader::json,
     renderer::cpu,
-    types::{Color, Layer, Paint, PathCommand},
+    types::{Color, Layer, Paint},
 };
 
 #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
@@ -42,8 +41,7 @@ impl RlottieWasm {
     /// Number of frames in the animation.
     #[wasm_bindgen]
     pub fn frames(&self) -> u32 {
-        // Composition does not yet expose duration, so assume single frame.
-        1
+        self.comp.frame_count()
     }
 
     /// Render a specific frame into a new [`ImageData`].
@@ -55,18 +53,9 @@ impl RlottieWasm {
 
         for layer in &self.comp.layers {
             if let Layer::Shape(shape) = layer {
-                for path_cmds in &shape.paths {
-                    let mut path = Path::new();
-                    for cmd in path_cmds {
-                        match *cmd {
-                            PathCommand::MoveTo(p) => path.move_to(p),
-                            PathCommand::LineTo(p) => path.line_to(p),
-                            PathCommand::CubicTo(c1, c2, p) => path.cubic_to(c1, c2, p),
-                            PathCommand::Close => path.close(),
-                        }
-                    }
+                for path in &shape.paths {
                     cpu::draw_path(
-                        &path,
+                        path,
                         Paint::Solid(Color {
                             r: 0,
                             g: 0,
@@ -3,7 +3,10 @@
 //! Module: animation timeline primitives
 //! Mirrors: rlottie/src/lottie/lottiemodel.h
 
-use crate::types::Vec2;
+use crate::types::{Color, Vec2};
+use std::collections::HashMap;
+use std::ops::{Mul, Sub};
+use std::sync::{Arc, Mutex, OnceLock};
 
 const LUT_SIZE: usize = 256;
 const SAMPLE_STEP: f32 = 1.0 / (LUT_SIZE as f32 - 1.0);
@@ -11,34 +14,124 @@ const NEWTON_ITERATIONS: usize = 4;
 const NEWTON_MIN_SLOPE: f32 = 0.02;
 const SUBDIVISION_PRECISION: f32 = 1e-7;
 const SUBDIVISION_MAX_ITERATIONS: usize = 10;
+/// Upper bound on distinct lookup tables kept in [`lut_cache`].
+const LUT_CACHE_CAPACITY: usize = 256;
+
+type Lut = Arc<[f32; LUT_SIZE]>;
+
+/// Lookup tables shared between identical eases. The table depends only on
+/// the x coordinates of the control points, so those bit patterns are the key.
+#[derive(Debug)]
+struct LutCache {
+    capacity: usize,
+    tables: HashMap<(u32, u32), Lut>,
+}
+
+impl LutCache {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tables: HashMap::new(),
+        }
+    }
+
+    /// Fetch the lookup table for `(x1, x2)`, computing and storing it on a
+    /// miss. A full cache first drops the tables no curve uses any more, so
+    /// eases of dropped compositions make room for new ones; if every slot
+    /// is still in use the table is not cached.
+    fn get(&mut self, x1: f32, x2: f32) -> Lut {
+        let key = (x1.to_bits(), x2.to_bits());
+        if let Some(lut) = self.tables.get(&key) {
+            return lut.clone();
+        }
+        let lut = Arc::new(CubicBezier::calc_samples(x1, x2));
+        if self.tables.len() >= self.capacity {
+            self.tables.retain(|_, lut| Arc::strong_count(lut) > 1);
+        }
+        if self.tables.len() < self.capacity {
+            self.tables.insert(key, lut.clone());
+        }
+        lut
+    }
+}
+
+/// The process-wide [`LutCache`] every [`CubicBezier`] draws from.
+fn lut_cache() -> &'static Mutex<LutCache> {
+    static CACHE: OnceLock<Mutex<LutCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(LutCache::with_capacity(LUT_CACHE_CAPACITY)))
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Number of lookup tables computed on the current thread.
+    static LUT_BUILDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
 /// Cubic Bézier easing curve defined by two control points.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CubicBezier {
     /// First control point
     pub c1: Vec2,
     /// Second control point
     pub c2: Vec2,
-    samples: [f32; LUT_SIZE],
+    newton_iterations: usize,
+    subdivision_iterations: usize,
+    subdivision_precision: f32,
+    samples: Lut,
 }
 
 impl CubicBezier {
     /// Create a new cubic Bézier and precompute a lookup table.
     pub fn new(c1: Vec2, c2: Vec2) -> Self {
-        let mut bez = Self {
+        Self::build(
             c1,
             c2,
-            samples: [0.0; LUT_SIZE],
-        };
-        bez.calc_samples();
-        bez
+            NEWTON_ITERATIONS,
+            SUBDIVISION_MAX_ITERATIONS,
+            SUBDIVISION_PRECISION,
+        )
+    }
+
+    /// Create a cubic Bézier with custom solver precision.
+    ///
+    /// `iterations` bounds both the Newton refinement steps and the bisection
+    /// fallback, and `epsilon` is the x error at which bisection stops.
+    /// [`CubicBezier::new`] uses 4 Newton steps, 10 bisection steps and `1e-7`.
+    pub fn with_precision(c1: Vec2, c2: Vec2, iterations: usize, epsilon: f32) -> Self {
+        Self::build(c1, c2, iterations, iterations, epsilon)
+    }
+
+    fn build(
+        c1: Vec2,
+        c2: Vec2,
+        newton_iterations: usize,
+        subdivision_iterations: usize,
+        subdivision_precision: f32,
+    ) -> Self {
+        Self {
+            c1,
+            c2,
+            newton_iterations,
+            subdivision_iterations,
+            subdivision_precision,
+            samples: Self::shared_samples(c1.x, c2.x),
+        }
+    }
+
+    /// Fetch the lookup table for `(x1, x2)` from the shared cache.
+    fn shared_samples(x1: f32, x2: f32) -> Lut {
+        let mut cache = lut_cache().lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(x1, x2)
     }
 
-    fn calc_samples(&mut self) {
-        for i in 0..LUT_SIZE {
-            let t = i as f32 * SAMPLE_STEP;
-            self.samples[i] = Self::calc_bezier(t, self.c1.x, self.c2.x);
+    fn calc_samples(x1: f32, x2: f32) -> [f32; LUT_SIZE] {
+        #[cfg(test)]
+        LUT_BUILDS.with(|n| n.set(n.get() + 1));
+        let mut samples = [0.0; LUT_SIZE];
+        for (i, sample) in samples.iter_mut().enumerate() {
+            *sample = Self::calc_bezier(i as f32 * SAMPLE_STEP, x1, x2);
         }
+        samples
     }
 
     fn calc_bezier(t: f32, a1: f32, a2: f32) -> f32 {
@@ -61,10 +154,10 @@ impl CubicBezier {
 
     fn binary_subdivide(&self, x: f32, mut a: f32, mut b: f32) -> f32 {
         let mut current_t = 0.0;
-        for _ in 0..SUBDIVISION_MAX_ITERATIONS {
+        for _ in 0..self.subdivision_iterations {
             current_t = a + (b - a) / 2.0;
             let current_x = Self::calc_bezier(current_t, self.c1.x, self.c2.x) - x;
-            if current_x.abs() <= SUBDIVISION_PRECISION {
+            if current_x.abs() <= self.subdivision_precision {
                 break;
             }
             if current_x > 0.0 {
@@ -89,7 +182,7 @@ impl CubicBezier {
         let mut guess_t = interval_start + dist * SAMPLE_STEP;
         let initial_slope = Self::get_slope(guess_t, self.c1.x, self.c2.x);
         if initial_slope >= NEWTON_MIN_SLOPE {
-            for _ in 0..NEWTON_ITERATIONS {
+            for _ in 0..self.newton_iterations {
                 let current_x = Self::calc_bezier(guess_t, self.c1.x, self.c2.x) - x;
                 let current_slope = Self::get_slope(guess_t, self.c1.x, self.c2.x);
                 if current_slope == 0.0 {
@@ -130,6 +223,9 @@ pub struct Keyframe<T> {
     pub end_v: T,
     /// Easing curve applied between frames
     pub ease: CubicBezier,
+    /// Hold `start_v` until the end frame instead of interpolating
+    /// (Lottie `"h": 1`)
+    pub hold: bool,
 }
 
 /// Trait for values that can be linearly interpolated.
@@ -153,6 +249,30 @@ impl Lerp for Vec2 {
     }
 }
 
+impl Lerp for Color {
+    /// Interpolate in premultiplied space, so a transparent end's RGB does
+    /// not bleed into the blend, and round each channel back to a byte.
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let aa = self.a as f32 / 255.0;
+        let ba = other.a as f32 / 255.0;
+        let oa = aa + (ba - aa) * t;
+        if oa <= 0.0 {
+            return Color::default();
+        }
+        let channel = |a: u8, b: u8| {
+            let pa = a as f32 * aa;
+            ((pa + (b as f32 * ba - pa) * t) / oa).round().min(255.0) as u8
+        };
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: (oa * 255.0).round() as u8,
+        }
+    }
+}
+
 impl<T: Lerp> Keyframe<T> {
     /// Sample the interpolated value at the given frame as a floating point frame index.
     pub fn sample(&self, frame: f32) -> T {
@@ -162,12 +282,103 @@ impl<T: Lerp> Keyframe<T> {
         if frame >= self.end as f32 {
             return self.end_v;
         }
+        if self.hold {
+            return self.start_v;
+        }
         let progress = (frame - self.start as f32) / (self.end as f32 - self.start as f32);
         let eased = self.ease.value(progress);
         self.start_v.lerp(self.end_v, eased)
     }
 }
 
+/// Position keyframe that moves along a cubic Bézier between its end points
+/// (Lottie `"to"`/`"ti"`) instead of a straight line.
+#[derive(Debug, Clone)]
+pub struct SpatialKeyframe {
+    /// Timing, end points and easing
+    pub key: Keyframe<Vec2>,
+    /// Tangent leaving the start point, relative to it
+    pub out_tangent: Vec2,
+    /// Tangent entering the end point, relative to it
+    pub in_tangent: Vec2,
+}
+
+impl SpatialKeyframe {
+    /// Whether both tangents are zero. Exporters write such tangents for
+    /// plain linear motion, so the keyframe then samples as a straight lerp.
+    pub fn is_linear(&self) -> bool {
+        self.out_tangent == Vec2::default() && self.in_tangent == Vec2::default()
+    }
+
+    /// Sample the position at `frame`. The eased progress is the fraction
+    /// of the curve's length travelled, as in rlottie, so the easing alone
+    /// sets the speed along the path however its tangents bunch up the
+    /// curve parameter.
+    pub fn sample(&self, frame: f32) -> Vec2 {
+        if self.is_linear() || self.key.hold {
+            return self.key.sample(frame);
+        }
+        let k = &self.key;
+        let progress = if frame <= k.start as f32 {
+            return k.start_v;
+        } else if frame >= k.end as f32 {
+            return k.end_v;
+        } else {
+            k.ease
+                .value((frame - k.start as f32) / (k.end as f32 - k.start as f32))
+        };
+        self.point_at(self.param_at_length(progress))
+    }
+
+    /// Point on the motion curve at parameter `t`.
+    fn point_at(&self, t: f32) -> Vec2 {
+        let k = &self.key;
+        let c1 = Vec2 {
+            x: k.start_v.x + self.out_tangent.x,
+            y: k.start_v.y + self.out_tangent.y,
+        };
+        let c2 = Vec2 {
+            x: k.end_v.x + self.in_tangent.x,
+            y: k.end_v.y + self.in_tangent.y,
+        };
+        let u = 1.0 - t;
+        let (w0, w1, w2, w3) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        Vec2 {
+            x: w0 * k.start_v.x + w1 * c1.x + w2 * c2.x + w3 * k.end_v.x,
+            y: w0 * k.start_v.y + w1 * c1.y + w2 * c2.y + w3 * k.end_v.y,
+        }
+    }
+
+    /// Curve parameter at which `fraction` of the curve's length is
+    /// covered, measured along a polyline through evenly spaced parameters.
+    fn param_at_length(&self, fraction: f32) -> f32 {
+        const STEPS: usize = 32;
+        let mut lengths = [0.0f32; STEPS + 1];
+        let mut prev = self.point_at(0.0);
+        for (i, len) in lengths.iter_mut().enumerate().skip(1) {
+            let p = self.point_at(i as f32 / STEPS as f32);
+            *len = (p.x - prev.x).hypot(p.y - prev.y);
+            prev = p;
+        }
+        for i in 1..=STEPS {
+            lengths[i] += lengths[i - 1];
+        }
+        let total = lengths[STEPS];
+        if total <= 0.0 {
+            return fraction;
+        }
+        let target = fraction.clamp(0.0, 1.0) * total;
+        let i = lengths.partition_point(|&l| l < target).clamp(1, STEPS);
+        let span = lengths[i] - lengths[i - 1];
+        let within = if span > 0.0 {
+            (target - lengths[i - 1]) / span
+        } else {
+            0.0
+        };
+        (i as f32 - 1.0 + within) / STEPS as f32
+    }
+}
+
 /// Sequence of [`Keyframe`]s describing an animated property.
 #[derive(Debug, Clone, Default)]
 pub struct Animator<T> {
@@ -198,6 +409,17 @@ impl<T: Lerp + Default> Animator<T> {
     }
 }
 
+impl<T: Lerp + Default + Sub<Output = T> + Mul<f32, Output = T>> Animator<T> {
+    /// Rate of change per frame at `frame`, using a central difference over
+    /// `frame ± dt`. Returns the default value when `dt` is not positive.
+    pub fn velocity(&self, frame: f32, dt: f32) -> T {
+        if dt <= 0.0 {
+            return T::default();
+        }
+        (self.value(frame + dt) - self.value(frame - dt)) * (0.5 / dt)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,11 +432,99 @@ mod tests {
             start_v: 1.0f32,
             end_v: 2.0,
             ease: CubicBezier::new(Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 1.0, y: 1.0 }),
+            hold: false,
         };
         assert_eq!(kf.start, 0);
         assert_eq!(kf.end, 10);
     }
 
+    #[test]
+    fn color_lerp_ignores_transparent_rgb() {
+        let red = Color {
+            r: 255,
+            g: 0,
+            b: 0,
+            a: 255,
+        };
+        let clear_green = Color {
+            r: 0,
+            g: 255,
+            b: 0,
+            a: 0,
+        };
+        let mid = red.lerp(clear_green, 0.5);
+        assert_eq!(mid, Color { a: 128, ..red });
+        assert_eq!(red.lerp(clear_green, 1.0), Color::default());
+    }
+
+    #[test]
+    fn zero_tangents_move_in_a_straight_line() {
+        let key = Keyframe {
+            start: 0,
+            end: 10,
+            start_v: Vec2 { x: 0.0, y: 0.0 },
+            end_v: Vec2 { x: 30.0, y: 10.0 },
+            ease: CubicBezier::new(Vec2 { x: 0.4, y: 0.0 }, Vec2 { x: 0.6, y: 1.0 }),
+            hold: false,
+        };
+        let spatial = SpatialKeyframe {
+            key: key.clone(),
+            out_tangent: Vec2::default(),
+            in_tangent: Vec2::default(),
+        };
+        assert!(spatial.is_linear());
+        for f in 0..=10 {
+            let p = spatial.sample(f as f32);
+            assert_eq!(p, key.sample(f as f32));
+            assert!((p.y * 3.0 - p.x).abs() < 1e-4, "{p:?} off the line");
+        }
+
+        let curved = SpatialKeyframe {
+            out_tangent: Vec2 { x: 0.0, y: 20.0 },
+            ..spatial
+        };
+        assert_eq!(curved.sample(0.0), key.start_v);
+        assert_eq!(curved.sample(10.0), key.end_v);
+        let mid = curved.sample(5.0);
+        assert!(
+            mid.y * 3.0 - mid.x > 1.0,
+            "{mid:?} should bulge off the line"
+        );
+    }
+
+    #[test]
+    fn eased_progress_travels_by_arc_length() {
+        // Both tangents pull toward the start, so equal parameter steps
+        // crowd near it; halfway in time must still be halfway along.
+        let curved = SpatialKeyframe {
+            key: Keyframe {
+                start: 0,
+                end: 10,
+                start_v: Vec2 { x: 0.0, y: 0.0 },
+                end_v: Vec2 { x: 40.0, y: 0.0 },
+                ease: CubicBezier::new(Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 1.0, y: 1.0 }),
+                hold: false,
+            },
+            out_tangent: Vec2 { x: 0.0, y: 20.0 },
+            in_tangent: Vec2 { x: -36.0, y: 20.0 },
+        };
+        let mid = curved.sample(5.0);
+        assert!(mid.y > 5.0, "{mid:?} should leave the straight line");
+
+        let walked = |to: f32| {
+            let mut len = 0.0;
+            let mut prev = curved.sample(0.0);
+            for i in 1..=200 {
+                let p = curved.sample(to * i as f32 / 200.0);
+                len += (p.x - prev.x).hypot(p.y - prev.y);
+                prev = p;
+            }
+            len
+        };
+        let (half, whole) = (walked(5.0), walked(10.0));
+        assert!((half / whole - 0.5).abs() < 0.01, "{half} of {whole}");
+    }
+
     #[test]
     fn bezier_value_matches_cpp() {
         let bez = CubicBezier::new(Vec2 { x: 0.42, y: 0.0 }, Vec2 { x: 0.58, y: 1.0 });
@@ -231,6 +541,7 @@ mod tests {
             start_v: 0.0f32,
             end_v: 1.0,
             ease: CubicBezier::new(Vec2 { x: 0.42, y: 0.0 }, Vec2 { x: 0.58, y: 1.0 }),
+            hold: false,
         };
         let v = kf.sample(2.5);
         assert!((v - 0.129162).abs() < 0.0001);
@@ -244,6 +555,7 @@ mod tests {
             start_v: 0.0f32,
             end_v: 1.0,
             ease: CubicBezier::new(Vec2 { x: 0.42, y: 0.0 }, Vec2 { x: 0.58, y: 1.0 }),
+            hold: false,
         };
         let anim = Animator {
             frames: vec![kf.clone()],
@@ -253,4 +565,113 @@ mod tests {
         assert_eq!(anim.value(-1.0), 0.0);
         assert_eq!(anim.value(20.0), 1.0);
     }
+
+    #[test]
+    fn hold_keyframe_jumps_at_the_boundary() {
+        let json = r#"{"w":8,"h":8,"ip":0,"op":20,"fr":30,"layers":[{"ty":4,"shapes":[
+            {"ty":"st","c":{"k":[1,0,0,1]},"w":{"a":1,"k":[
+                {"t":0,"s":[2],"h":1},{"t":10,"s":[6]}]}},
+            {"ty":"sh","ks":{"d":"m 1 1 l 7 7"}}]}]}"#;
+        let comp = crate::loader::json::from_slice(json.as_bytes()).unwrap();
+        let crate::types::Layer::Shape(shape) = &comp.layers[0] else {
+            panic!("expected shape layer");
+        };
+        let anim = &shape.animators[crate::types::STROKE_WIDTH];
+        assert!(anim.frames[0].hold);
+        assert_eq!(anim.value(0.0), 2.0);
+        assert_eq!(anim.value(5.0), 2.0);
+        assert_eq!(anim.value(9.9), 2.0);
+        assert_eq!(anim.value(10.0), 6.0);
+    }
+
+    #[test]
+    fn bezier_with_precision_converges_closer() {
+        // x'(t) vanishes at t = 0.5, so the solver falls back to bisection there.
+        let c1 = Vec2 { x: 1.0, y: 0.0 };
+        let c2 = Vec2 { x: 0.0, y: 1.0 };
+        let x = 0.4999f32;
+        // Solve x(t) = x analytically by bisection in f64 and evaluate y(t).
+        let bez = |t: f64, a1: f64, a2: f64| {
+            3.0 * a1 * t * (1.0 - t).powi(2) + 3.0 * a2 * t * t * (1.0 - t) + t.powi(3)
+        };
+        let (mut lo, mut hi) = (0.0f64, 1.0f64);
+        for _ in 0..100 {
+            let mid = (lo + hi) / 2.0;
+            if bez(mid, 1.0, 0.0) < x as f64 {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let expected = bez(lo, 0.0, 1.0);
+        let default_err = (CubicBezier::new(c1, c2).value(x) as f64 - expected).abs();
+        let precise = CubicBezier::with_precision(c1, c2, 32, 1e-9);
+        let precise_err = (precise.value(x) as f64 - expected).abs();
+        assert!(precise_err < default_err, "{precise_err} >= {default_err}");
+    }
+
+    #[test]
+    fn animator_velocity_linear() {
+        let anim = Animator {
+            frames: vec![Keyframe {
+                start: 0,
+                end: 10,
+                start_v: Vec2 { x: 0.0, y: 0.0 },
+                end_v: Vec2 { x: 30.0, y: -20.0 },
+                ease: CubicBezier::new(Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 1.0, y: 1.0 }),
+                hold: false,
+            }],
+        };
+        for frame in [2.0, 5.0, 7.5] {
+            let v = anim.velocity(frame, 0.5);
+            assert!((v.x - 3.0).abs() < 1e-4);
+            assert!((v.y + 2.0).abs() < 1e-4);
+        }
+        assert_eq!(anim.velocity(5.0, 0.0), Vec2::default());
+    }
+
+    #[test]
+    fn identical_eases_share_one_lut() {
+        // Control points unique to this test so other tests cannot warm the cache.
+        let key = r#"{"t":0,"s":[0],"o":{"x":[0.3141],"y":[0]},"i":{"x":[0.5926],"y":[1]}}"#;
+        let keys: Vec<String> = (0..50)
+            .map(|i| key.replace(r#""t":0"#, &format!(r#""t":{}"#, i * 2)))
+            .collect();
+        let json = format!(
+            r#"{{"w":8,"h":8,"ip":0,"op":100,"fr":30,"layers":[{{"ty":4,"shapes":[
+                {{"ty":"st","c":{{"k":[1,0,0,1]}},"w":{{"a":1,"k":[{}]}}}},
+                {{"ty":"sh","ks":{{"d":"m 1 1 l 7 7"}}}}]}}]}}"#,
+            keys.join(",")
+        );
+        let before = LUT_BUILDS.with(|n| n.get());
+        let comp = crate::loader::json::from_slice(json.as_bytes()).unwrap();
+        assert_eq!(LUT_BUILDS.with(|n| n.get()) - before, 1);
+
+        let crate::types::Layer::Shape(shape) = &comp.layers[0] else {
+            panic!("expected shape layer");
+        };
+        let anim = &shape.animators[crate::types::STROKE_WIDTH];
+        assert_eq!(anim.frames.len(), 49);
+        let first = &anim.frames[0].ease.samples;
+        assert!(anim
+            .frames
+            .iter()
+            .all(|kf| Arc::ptr_eq(first, &kf.ease.samples)));
+    }
+
+    #[test]
+    fn full_lut_cache_drops_unused_tables() {
+        let mut cache = LutCache::with_capacity(4);
+        let x = |i: usize| 0.7 + i as f32 * 1e-4;
+        let held: Vec<Lut> = (0..4).map(|i| cache.get(x(i), 0.2)).collect();
+        // Every slot is in use, so a new table is handed out uncached.
+        let (a, b) = (cache.get(x(5), 0.2), cache.get(x(5), 0.2));
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.tables.len(), 4);
+
+        drop((held, a, b));
+        let (a, b) = (cache.get(x(6), 0.2), cache.get(x(6), 0.2));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.tables.len(), 1);
+    }
 }
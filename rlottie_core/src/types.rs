@@ -3,10 +3,13 @@
 //! Module: type definitions
 //! Mirrors: rlottie/src/lottie/lottiemodel.h
 
-use crate::timeline::Animator;
+use crate::geometry::{Path, PathSeg};
+use crate::renderer::Renderer;
+use crate::timeline::{Animator, SpatialKeyframe};
 use fontdue::Font;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::{Mul, Sub};
 use std::sync::Arc;
 
 /// 2D vector used throughout the engine.
@@ -18,6 +21,28 @@ pub struct Vec2 {
     pub y: f32,
 }
 
+impl Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
 /// Fixed-point 2D vector using Q16.16 representation for `no_std` builds.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Vec2Fx {
@@ -47,8 +72,67 @@ impl Vec2Fx {
         }
     }
 }
-/// RGBA color in 8-bit per channel.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whole frame index on the composition timeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Frame(pub u32);
+
+/// Fractional frame position on the composition timeline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct FrameF(pub f32);
+
+/// Time in seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Seconds(pub f32);
+
+impl Frame {
+    /// Time at which this frame starts when played at `fps`.
+    pub fn to_seconds(self, fps: f32) -> Seconds {
+        FrameF::from(self).to_seconds(fps)
+    }
+}
+
+impl FrameF {
+    /// Time at this position when played at `fps`.
+    pub fn to_seconds(self, fps: f32) -> Seconds {
+        if fps > 0.0 {
+            Seconds(self.0 / fps)
+        } else {
+            Seconds(0.0)
+        }
+    }
+
+    /// Nearest whole frame, clamped at zero.
+    pub fn round(self) -> Frame {
+        Frame(self.0.max(0.0).round() as u32)
+    }
+}
+
+impl Seconds {
+    /// Fractional frame reached after this much time at `fps`.
+    pub fn to_frame_f(self, fps: f32) -> FrameF {
+        FrameF(self.0 * fps)
+    }
+
+    /// Nearest whole frame reached after this much time at `fps`.
+    pub fn to_frame(self, fps: f32) -> Frame {
+        self.to_frame_f(fps).round()
+    }
+}
+
+impl From<u32> for Frame {
+    fn from(frame: u32) -> Self {
+        Self(frame)
+    }
+}
+
+impl From<Frame> for FrameF {
+    fn from(frame: Frame) -> Self {
+        Self(frame.0 as f32)
+    }
+}
+
+/// RGBA color in 8-bit per channel. The default is transparent black.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Color {
     /// Red channel
     pub r: u8,
@@ -69,6 +153,27 @@ pub struct GradientStop {
     pub color: Color,
 }
 
+/// An opacity stop of a gradient, ramped independently of the color stops.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpacityStop {
+    /// Offset along the gradient 0..1
+    pub offset: f32,
+    /// Opacity at this stop, 0..1
+    pub opacity: f32,
+}
+
+/// Color space that gradient stops are interpolated in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// Straight sRGB channel interpolation, matching rlottie.
+    #[default]
+    Rgb,
+    /// Hue, saturation and lightness, taking the shorter way around the hue circle.
+    Hsl,
+    /// Perceptual OKLab; hue changes keep their chroma instead of passing through gray.
+    OkLab,
+}
+
 /// Linear gradient parameters.
 #[derive(Debug, Clone)]
 pub struct LinearGradient {
@@ -78,6 +183,11 @@ pub struct LinearGradient {
     pub end: Vec2,
     /// Color stops sorted by offset
     pub stops: Vec<GradientStop>,
+    /// Opacity stops sorted by offset, multiplied into the color stops'
+    /// alpha; none leaves the colors as they are
+    pub opacity_stops: Vec<OpacityStop>,
+    /// Space the stops are interpolated in
+    pub space: ColorSpace,
 }
 
 /// Radial gradient parameters.
@@ -87,13 +197,23 @@ pub struct RadialGradient {
     pub center: Vec2,
     /// Radius of the gradient
     pub radius: f32,
+    /// Focal point where offset 0 sits, if not the center; must lie inside
+    /// the circle
+    pub focus: Option<Vec2>,
     /// Color stops sorted by offset
     pub stops: Vec<GradientStop>,
+    /// Opacity stops sorted by offset, multiplied into the color stops'
+    /// alpha; none leaves the colors as they are
+    pub opacity_stops: Vec<OpacityStop>,
+    /// Space the stops are interpolated in
+    pub space: ColorSpace,
 }
 
 /// Paint style for filling paths.
 #[derive(Debug, Clone)]
 pub enum Paint {
+    /// Explicitly disabled paint; drawing with it is a no-op
+    None,
     /// Solid color fill
     Solid(Color),
     /// Linear gradient fill
@@ -102,6 +222,180 @@ pub enum Paint {
     Radial(RadialGradient),
 }
 
+impl Paint {
+    /// This paint with gradient geometry mapped through `m`, so it stays
+    /// attached to a shape drawn through the same matrix.
+    ///
+    /// Linear gradients are exact under any invertible matrix: the new end
+    /// point is chosen so the bands of equal color are the mapped bands,
+    /// which skews and uneven scales leave no longer perpendicular to the
+    /// mapped start-end line. A radial radius scales by the matrix's
+    /// average scale factor, so uneven scales keep the gradient circular.
+    pub fn transformed(&self, m: &Affine) -> Paint {
+        match self {
+            Paint::None | Paint::Solid(_) => self.clone(),
+            Paint::Linear(g) => {
+                let start = m.transform_point(g.start);
+                let v = g.end - g.start;
+                let len2 = v.x * v.x + v.y * v.y;
+                let det = m.a * m.d - m.b * m.c;
+                // Gradient of t in buffer space: the inverse transpose of
+                // the linear part applied to v / |v|².
+                let grad = Vec2 {
+                    x: (m.d * v.x - m.b * v.y) / (det * len2),
+                    y: (m.a * v.y - m.c * v.x) / (det * len2),
+                };
+                let g2 = grad.x * grad.x + grad.y * grad.y;
+                let end = if g2.is_finite() && g2 > 0.0 {
+                    Vec2 {
+                        x: start.x + grad.x / g2,
+                        y: start.y + grad.y / g2,
+                    }
+                } else {
+                    m.transform_point(g.end)
+                };
+                Paint::Linear(LinearGradient {
+                    start,
+                    end,
+                    ..g.clone()
+                })
+            }
+            Paint::Radial(g) => Paint::Radial(RadialGradient {
+                center: m.transform_point(g.center),
+                radius: g.radius * (m.a * m.d - m.b * m.c).abs().sqrt(),
+                focus: g.focus.map(|f| m.transform_point(f)),
+                ..g.clone()
+            }),
+        }
+    }
+
+    /// This paint with every color's alpha scaled by `opacity`.
+    pub fn faded(&self, opacity: f32) -> Paint {
+        let fade = |c: Color| Color {
+            a: (c.a as f32 * opacity).round() as u8,
+            ..c
+        };
+        let fade_stops = |stops: &[GradientStop]| {
+            stops
+                .iter()
+                .map(|s| GradientStop {
+                    color: fade(s.color),
+                    ..*s
+                })
+                .collect()
+        };
+        match self {
+            Paint::None => Paint::None,
+            Paint::Solid(c) => Paint::Solid(fade(*c)),
+            Paint::Linear(g) => Paint::Linear(LinearGradient {
+                stops: fade_stops(&g.stops),
+                ..g.clone()
+            }),
+            Paint::Radial(g) => Paint::Radial(RadialGradient {
+                stops: fade_stops(&g.stops),
+                ..g.clone()
+            }),
+        }
+    }
+}
+
+/// Shape drawn at the open ends of a stroked path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// Stroke ends flush with the path end point.
+    #[default]
+    Butt,
+    /// Stroke ends with a half circle around the end point.
+    Round,
+    /// Stroke ends with a half square extending past the end point.
+    Square,
+}
+
+/// Shape drawn where two stroked segments meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Extend the outer edges until they meet, bounded by a miter limit.
+    #[default]
+    Miter,
+    /// Fill the corner with a circular arc.
+    Round,
+    /// Cut the corner with a straight line.
+    Bevel,
+}
+
+/// Cap, join and miter limit of a stroke.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    /// Shape drawn at the open ends of each sub-path (Lottie `"lc"`)
+    pub cap: LineCap,
+    /// Shape drawn where two segments meet (Lottie `"lj"`)
+    pub join: LineJoin,
+    /// Longest miter, as a multiple of the stroke width, before a miter
+    /// join is cut to a bevel (Lottie `"ml"`)
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            cap: LineCap::default(),
+            join: LineJoin::default(),
+            miter_limit: crate::geometry::DEFAULT_MITER_LIMIT,
+        }
+    }
+}
+
+/// Rule deciding which regions of a compound path are inside the fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Inside where the winding number is non-zero (Lottie `"r": 1`).
+    #[default]
+    NonZero,
+    /// Inside where an odd number of edges is crossed (Lottie `"r": 2`).
+    EvenOdd,
+}
+
+/// Which of a shape's paints its trim (Lottie `"tm"`) cuts short.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrimTarget {
+    /// Fill and stroke both follow the trimmed path.
+    #[default]
+    FillAndStroke,
+    /// Only the stroke is trimmed, drawing on over a whole fill, as when
+    /// the fill sits above the trim in the shape stack.
+    Stroke,
+    /// Only the fill is trimmed; the stroke sits above the trim.
+    Fill,
+    /// Neither paint is trimmed, as both sit above the trim.
+    None,
+}
+
+impl TrimTarget {
+    /// Whether the fill follows the trimmed path.
+    pub fn trims_fill(self) -> bool {
+        matches!(self, TrimTarget::FillAndStroke | TrimTarget::Fill)
+    }
+
+    /// Whether the stroke follows the trimmed path.
+    pub fn trims_stroke(self) -> bool {
+        matches!(self, TrimTarget::FillAndStroke | TrimTarget::Stroke)
+    }
+}
+
+/// How a layer mask combines with the masks before it (Lottie `"mode"`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MaskMode {
+    /// Union with the coverage so far (`"a"`).
+    #[default]
+    Add,
+    /// Remove the mask's area from the coverage so far (`"s"`).
+    Subtract,
+    /// Keep only the area also covered by the mask (`"i"`).
+    Intersect,
+    /// Ignore the mask (`"n"`).
+    None,
+}
+
 /// Type of matte compositing to apply with the previous mask layer.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MatteType {
@@ -111,6 +405,40 @@ pub enum MatteType {
     AlphaInv,
 }
 
+/// Named segment of the timeline (Lottie `"markers"`), such as a loop a
+/// host plays on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    /// Marker name (`"cm"`)
+    pub name: String,
+    /// Composition frame the segment starts at (`"tm"`)
+    pub start: u32,
+    /// Length of the segment in frames (`"dr"`)
+    pub duration: u32,
+}
+
+/// How a layer's colors combine with what is already drawn beneath it
+/// (Lottie `"bm"`). Modes other than `Normal` blend each channel before the
+/// usual source-over alpha composite.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The layer's color replaces the backdrop (`0`).
+    #[default]
+    Normal,
+    /// Product of the two colors, never lighter than either (`1`).
+    Multiply,
+    /// Inverse product of the inverses, never darker than either (`2`).
+    Screen,
+    /// Multiply on dark backdrops and screen on light ones (`3`).
+    Overlay,
+    /// Darker of the two colors per channel (`4`).
+    Darken,
+    /// Lighter of the two colors per channel (`5`).
+    Lighten,
+    /// Sum of the two colors, clamped to white (`16`).
+    Add,
+}
+
 /// Transform parameters for a layer or object.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transform {
@@ -127,6 +455,10 @@ pub struct Transform {
     /// Property animations keyed by name
     #[serde(skip)]
     pub animators: HashMap<&'static str, Animator<f32>>,
+    /// Curved position keyframes; when present they drive the position in
+    /// place of the [`POSITION_X`]/[`POSITION_Y`] animators
+    #[serde(skip)]
+    pub motion: Vec<SpatialKeyframe>,
 }
 
 impl Default for Transform {
@@ -138,44 +470,361 @@ impl Default for Transform {
             rotation: 0.0,
             opacity: 1.0,
             animators: HashMap::new(),
+            motion: Vec::new(),
         }
     }
 }
 
-/// Path drawing commands.
-#[derive(Debug, Clone)]
-pub enum PathCommand {
-    /// Move to absolute position
-    MoveTo(Vec2),
-    /// Line to absolute position
-    LineTo(Vec2),
-    /// Cubic Bezier curve
-    CubicTo(Vec2, Vec2, Vec2),
-    /// Close current sub-path
-    Close,
+/// Key in [`Transform::animators`] for the animated x position.
+pub const POSITION_X: &str = "position_x";
+/// Key in [`Transform::animators`] for the animated y position.
+pub const POSITION_Y: &str = "position_y";
+/// Key in [`Transform::animators`] for the animated x anchor.
+pub const ANCHOR_X: &str = "anchor_x";
+/// Key in [`Transform::animators`] for the animated y anchor.
+pub const ANCHOR_Y: &str = "anchor_y";
+/// Key in [`Transform::animators`] for the animated x scale, 1 = 100%.
+pub const SCALE_X: &str = "scale_x";
+/// Key in [`Transform::animators`] for the animated y scale, 1 = 100%.
+pub const SCALE_Y: &str = "scale_y";
+/// Key in [`Transform::animators`] for the animated rotation.
+pub const ROTATION: &str = "rotation";
+/// Key in [`Transform::animators`] for the animated opacity, 0..1.
+pub const OPACITY: &str = "opacity";
+
+impl Transform {
+    /// Static transform with every animated property sampled at `frame`.
+    pub fn at(&self, frame: f32) -> Transform {
+        let sample = |key: &str, v: f32| self.animators.get(key).map_or(v, |a| a.value(frame));
+        let motion = self
+            .motion
+            .iter()
+            .find(|k| frame < k.key.end as f32)
+            .or(self.motion.last());
+        Transform {
+            anchor: Vec2 {
+                x: sample(ANCHOR_X, self.anchor.x),
+                y: sample(ANCHOR_Y, self.anchor.y),
+            },
+            position: match motion {
+                Some(k) => k.sample(frame),
+                None => Vec2 {
+                    x: sample(POSITION_X, self.position.x),
+                    y: sample(POSITION_Y, self.position.y),
+                },
+            },
+            scale: Vec2 {
+                x: sample(SCALE_X, self.scale.x),
+                y: sample(SCALE_Y, self.scale.y),
+            },
+            rotation: sample(ROTATION, self.rotation),
+            opacity: sample(OPACITY, self.opacity),
+            animators: HashMap::new(),
+            motion: Vec::new(),
+        }
+    }
+
+    /// Map `p` into the parent space: scale and rotate around the anchor,
+    /// then move the anchor onto the position. Opacity is not involved.
+    pub fn apply(&self, p: Vec2) -> Vec2 {
+        Affine::from_transform(self).transform_point(p)
+    }
+}
+
+/// 2D affine matrix `[a c e; b d f]`, mapping `(x, y)` to
+/// `(a*x + c*y + e, b*x + d*y + f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine {
+    /// x scale / rotation
+    pub a: f32,
+    /// y shear / rotation
+    pub b: f32,
+    /// x shear / rotation
+    pub c: f32,
+    /// y scale / rotation
+    pub d: f32,
+    /// x translation
+    pub e: f32,
+    /// y translation
+    pub f: f32,
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine {
+    /// The matrix leaving every point in place.
+    pub const IDENTITY: Affine = Affine {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        e: 0.0,
+        f: 0.0,
+    };
+
+    /// Scale by `scale`, then translate by `offset`.
+    pub fn scale_translate(scale: Vec2, offset: Vec2) -> Affine {
+        Affine {
+            a: scale.x,
+            d: scale.y,
+            e: offset.x,
+            f: offset.y,
+            ..Affine::IDENTITY
+        }
+    }
+
+    /// Matrix of a layer or group transform: move the anchor to the origin,
+    /// scale, rotate clockwise on screen, then move to the position.
+    pub fn from_transform(tr: &Transform) -> Affine {
+        let (sin, cos) = tr.rotation.to_radians().sin_cos();
+        let (a, b) = (cos * tr.scale.x, sin * tr.scale.x);
+        let (c, d) = (-sin * tr.scale.y, cos * tr.scale.y);
+        Affine {
+            a,
+            b,
+            c,
+            d,
+            e: tr.position.x - (a * tr.anchor.x + c * tr.anchor.y),
+            f: tr.position.y - (b * tr.anchor.x + d * tr.anchor.y),
+        }
+    }
+
+    /// Inverse matrix, or `None` when the matrix collapses the plane.
+    pub fn invert(&self) -> Option<Affine> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Affine {
+            a,
+            b,
+            c,
+            d,
+            e: -(a * self.e + c * self.f),
+            f: -(b * self.e + d * self.f),
+        })
+    }
+
+    /// Apply the matrix to `p`.
+    pub fn transform_point(&self, p: Vec2) -> Vec2 {
+        Vec2 {
+            x: self.a * p.x + self.c * p.y + self.e,
+            y: self.b * p.x + self.d * p.y + self.f,
+        }
+    }
+}
+
+/// `parent * child` applies `child` first, so nested transforms stack by
+/// multiplying from the outermost inwards.
+impl Mul for Affine {
+    type Output = Affine;
+
+    fn mul(self, rhs: Affine) -> Affine {
+        Affine {
+            a: self.a * rhs.a + self.c * rhs.b,
+            b: self.b * rhs.a + self.d * rhs.b,
+            c: self.a * rhs.c + self.c * rhs.d,
+            d: self.b * rhs.c + self.d * rhs.d,
+            e: self.a * rhs.e + self.c * rhs.f + self.e,
+            f: self.b * rhs.e + self.d * rhs.f + self.f,
+        }
+    }
+}
+
+/// Effect applied to a layer's rendered pixels, from the layer's `"ef"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerEffect {
+    /// Gaussian blur; `radius` is the blurriness in composition units (type 29)
+    GaussianBlur {
+        /// Blurriness, taken as two standard deviations
+        radius: f32,
+    },
+    /// Remap luminance onto a `black`..`white` ramp (type 20)
+    Tint {
+        /// Color that black is mapped to
+        black: Color,
+        /// Color that white is mapped to
+        white: Color,
+        /// Blend between the original and tinted color, 0..1
+        amount: f32,
+    },
+    /// Offset and softened copy of the layer's alpha drawn beneath it (type 25)
+    DropShadow {
+        /// Shadow color
+        color: Color,
+        /// Shadow opacity, 0..1
+        opacity: f32,
+        /// Direction in degrees, clockwise from straight up
+        direction: f32,
+        /// Offset in composition units
+        distance: f32,
+        /// Blur radius in composition units
+        softness: f32,
+    },
 }
 
 /// Vector shape layer.
 #[derive(Debug, Clone, Default)]
 pub struct ShapeLayer {
     /// Collection of paths within the shape
-    pub paths: Vec<Vec<PathCommand>>,
+    pub paths: Vec<Path>,
     /// Fill color if present
     pub fill: Option<Color>,
+    /// Keyframes of an animated fill color, sampled instead of `fill`
+    pub fill_animator: Option<Animator<Color>>,
+    /// Gradient fill (Lottie `"gf"`) in layer space, drawn instead of `fill`
+    pub fill_paint: Option<Paint>,
+    /// Name of the "Color Control" effect an expression links `fill` to
+    pub fill_control: Option<String>,
+    /// Rule used when filling all paths together as one compound path
+    pub fill_rule: FillRule,
     /// Stroke color if present
     pub stroke: Option<Color>,
     /// Stroke width in pixels
     pub stroke_width: f32,
+    /// Caps, joins and miter limit of the stroke
+    pub stroke_style: StrokeStyle,
+    /// Alternating dash and gap lengths of the stroke (Lottie `"d"`)
+    pub dash: Option<Vec<f32>>,
+    /// Distance into the dash pattern at which the stroke starts
+    pub dash_offset: f32,
+    /// Animation of each dash or gap length, index-aligned with `dash`
+    pub dash_animators: Vec<Option<Animator<f32>>>,
     /// Optional mask paths to clip this shape
-    pub mask: Option<Vec<Vec<PathCommand>>>,
+    pub mask: Option<Vec<Path>>,
+    /// Expansion of each mask path (Lottie `"x"`), index-aligned with `mask`
+    pub mask_expansion: Vec<f32>,
+    /// Combine mode of each mask path, index-aligned with `mask`
+    pub mask_modes: Vec<MaskMode>,
+    /// Whether each mask covers the outside of its path (Lottie `"inv"`),
+    /// index-aligned with `mask`
+    pub mask_inverted: Vec<bool>,
+    /// Opacity of each mask, 0..1 (Lottie `"o"`), index-aligned with `mask`;
+    /// missing entries are opaque
+    pub mask_opacity: Vec<f32>,
+    /// Whether the layer's masks are active (Lottie `hasMask`)
+    pub has_mask: bool,
     /// Optional trim start/end fractions
     pub trim: Option<(f32, f32)>,
+    /// Paints the trim applies to
+    pub trim_target: TrimTarget,
     /// Animations for fill or stroke properties
     pub animators: HashMap<&'static str, Animator<f32>>,
     /// If true this layer acts as a matte for the next layer
     pub is_mask: bool,
     /// Matte mode applied using the previous mask layer
     pub matte: Option<MatteType>,
+    /// How the rendered layer blends with the layers beneath it
+    pub blend_mode: BlendMode,
+    /// Shape groups with a non-normal blend mode (Lottie group `"bm"`),
+    /// each drawn on its own and blended over the layer's other paints
+    /// with its `blend_mode`; paths are in layer space
+    pub blend_groups: Vec<ShapeLayer>,
+    /// Effects applied in order to the rendered layer
+    pub effects: Vec<LayerEffect>,
+    /// Layer transform (Lottie `"ks"`); opacity scales fill and stroke alpha
+    pub transform: Transform,
+    /// Layer index (Lottie `"ind"`) that other layers name as their parent
+    pub index: Option<u32>,
+    /// Index of the layer whose transform this one inherits (`"parent"`)
+    pub parent: Option<u32>,
+    /// Time remap (Lottie `"tm"`): seconds of layer time shown at each
+    /// composition frame, used to sample this layer's own animators
+    pub time_remap: Option<Animator<f32>>,
+    /// Start time (Lottie `"st"`) in frames, subtracted from the
+    /// composition frame when there is no time remap
+    pub start_time: f32,
+}
+
+/// Key in [`ShapeLayer::animators`] for an animated stroke width.
+pub const STROKE_WIDTH: &str = "stroke_width";
+/// Key in [`ShapeLayer::animators`] for an animated dash offset.
+pub const DASH_OFFSET: &str = "dash_offset";
+/// Key in [`ShapeLayer::animators`] for an animated fill opacity, in
+/// percent. The stored fill color leaves it out.
+pub const FILL_OPACITY: &str = "fill_opacity";
+
+impl ShapeLayer {
+    /// Paint the shape is filled with: the gradient if there is one,
+    /// otherwise the solid fill color.
+    pub fn effective_fill(&self) -> Option<Paint> {
+        self.fill_paint.clone().or(self.fill.map(Paint::Solid))
+    }
+
+    /// Fill color at `frame`, sampling its color and opacity animators when
+    /// keyframed.
+    pub fn fill_at(&self, frame: f32) -> Option<Color> {
+        let color = match &self.fill_animator {
+            Some(anim) => Some(anim.value(frame)),
+            None => self.fill,
+        }?;
+        Some(match self.animators.get(FILL_OPACITY) {
+            Some(anim) => {
+                let opacity = (anim.value(frame) / 100.0).clamp(0.0, 1.0);
+                Color {
+                    a: (color.a as f32 * opacity).round() as u8,
+                    ..color
+                }
+            }
+            None => color,
+        })
+    }
+
+    /// Paint the shape is filled with at `frame`, as [`Self::effective_fill`]
+    /// with the fill color sampled by [`Self::fill_at`].
+    pub fn effective_fill_at(&self, frame: f32) -> Option<Paint> {
+        self.fill_paint
+            .clone()
+            .or(self.fill_at(frame).map(Paint::Solid))
+    }
+
+    /// Frame at which this layer samples its transform and animators when
+    /// the composition, running at `fps`, shows `frame`.
+    pub fn local_frame(&self, frame: f32, fps: f32) -> f32 {
+        layer_frame(self.time_remap.as_ref(), self.start_time, frame, fps)
+    }
+
+    /// Stroke width at `frame`, sampling its animator when keyframed.
+    pub fn stroke_width_at(&self, frame: f32) -> f32 {
+        self.animators
+            .get(STROKE_WIDTH)
+            .map_or(self.stroke_width, |a| a.value(frame))
+    }
+
+    /// Dash pattern and offset at `frame`, sampling keyframed lengths, or
+    /// `None` for a solid stroke.
+    pub fn dash_at(&self, frame: f32) -> Option<(Vec<f32>, f32)> {
+        let pattern = self
+            .dash
+            .as_ref()?
+            .iter()
+            .enumerate()
+            .map(|(i, len)| {
+                self.dash_animators
+                    .get(i)
+                    .and_then(Option::as_ref)
+                    .map_or(*len, |a| a.value(frame))
+            })
+            .collect();
+        let offset = self
+            .animators
+            .get(DASH_OFFSET)
+            .map_or(self.dash_offset, |a| a.value(frame));
+        Some((pattern, offset))
+    }
+
+    /// Stroke color and width at `frame`, or `None` when the stroke would
+    /// not produce any pixels (no color, zero alpha or non-positive width).
+    pub fn visible_stroke(&self, frame: f32) -> Option<(Color, f32)> {
+        let color = self.stroke.filter(|c| c.a > 0)?;
+        let width = self.stroke_width_at(frame);
+        (width > 0.0).then_some((color, width))
+    }
 }
 
 /// Bitmap image layer decoded from assets.
@@ -187,11 +836,92 @@ pub struct ImageLayer {
     pub height: u32,
     /// Raw RGBA8888 pixel data
     pub pixels: Vec<u8>,
+    /// Layer transform (Lottie `"ks"`); default for image sequence frames
+    pub transform: Transform,
+    /// Time remap (Lottie `"tm"`) used to sample `transform`
+    pub time_remap: Option<Animator<f32>>,
+    /// Start time (Lottie `"st"`) in frames
+    pub start_time: f32,
+}
+
+impl ImageLayer {
+    /// Frame at which this layer samples its transform when the
+    /// composition, running at `fps`, shows `frame`.
+    pub fn local_frame(&self, frame: f32, fps: f32) -> f32 {
+        layer_frame(self.time_remap.as_ref(), self.start_time, frame, fps)
+    }
 }
+
+/// Map a composition `frame` to a layer's own frame. A time remap gives
+/// seconds of layer time, converted back to a frame at `fps`; without one
+/// the layer's start time `st` is subtracted. Never negative.
+fn layer_frame(remap: Option<&Animator<f32>>, st: f32, frame: f32, fps: f32) -> f32 {
+    remap.map_or(frame - st, |r| r.value(frame) * fps).max(0.0)
+}
+/// Sequence of bitmap frames played back over time.
+#[derive(Debug, Clone)]
+pub struct ImageSequenceLayer {
+    /// Decoded frames in playback order
+    pub frames: Vec<ImageLayer>,
+    /// Playback rate of the sequence in frames per second
+    pub fps: f32,
+}
+
+impl ImageSequenceLayer {
+    /// Select the bitmap shown at `local_frame` of a composition running at
+    /// `comp_fps`. Playback holds the last image once the sequence ends.
+    pub fn frame_at(&self, local_frame: f32, comp_fps: f32) -> Option<&ImageLayer> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let ratio = if comp_fps > 0.0 {
+            self.fps / comp_fps
+        } else {
+            1.0
+        };
+        let idx = (local_frame * ratio).floor().max(0.0) as usize;
+        self.frames.get(idx.min(self.frames.len() - 1))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PreCompLayer {
-    /// Nested composition to render
+    /// Nested composition to render, sized by the layer's `"w"`/`"h"`
     pub comp: Box<Composition>,
+    /// Layer index (Lottie `"ind"`)
+    pub index: Option<u32>,
+    /// Index of the parent layer
+    pub parent: Option<u32>,
+    /// Layer transform (Lottie `"ks"`) placing the nested composition
+    pub transform: Transform,
+    /// Collapse transformations (Lottie `"ct": 1`). Collapsed, the nested
+    /// layers are drawn as vectors through the layer's matrix. Otherwise
+    /// the nested composition is rasterized at the parent's scale, clipped
+    /// to its size, and that raster is placed by the layer's matrix, so a
+    /// scale or rotation on the layer resamples pixels.
+    pub collapse_transform: bool,
+    /// Time remap (Lottie `"tm"`): seconds of nested time shown at each
+    /// frame of the parent
+    pub time_remap: Option<Animator<f32>>,
+    /// Start time (Lottie `"st"`) in frames, delaying the nested
+    /// composition when there is no time remap
+    pub start_time: f32,
+}
+
+impl PreCompLayer {
+    /// Frame of the nested composition, counted from its `start_frame`,
+    /// shown at the parent's absolute `frame`. It follows the same rule as
+    /// [`ShapeLayer::local_frame`] and keeps its fraction, so remapped
+    /// content moves smoothly instead of stepping whole frames.
+    pub fn local_frame(&self, frame: f32) -> f32 {
+        let own = layer_frame(
+            self.time_remap.as_ref(),
+            self.start_time,
+            frame,
+            self.comp.fps,
+        );
+        (own - self.comp.start_frame as f32).max(0.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -206,19 +936,160 @@ pub struct TextLayer {
     pub position: Vec2,
     /// Font used for rasterization
     pub font: Arc<Font>,
+    /// Draw glyph outlines through the path pipeline instead of coverage
+    /// bitmaps; `None` keeps the bitmap rendering
+    pub outlines: Option<GlyphOutlines>,
+}
+
+/// Vector rendering of a [`TextLayer`], which unlike bitmaps can take
+/// gradient fills and strokes.
+#[derive(Debug, Clone)]
+pub struct GlyphOutlines {
+    /// TrueType or OpenType data of the layer's font
+    pub font_data: Arc<Vec<u8>>,
+    /// Paint filling the glyphs, in output pixel coordinates
+    pub fill: Paint,
+    /// Stroke color and width in pixels
+    pub stroke: Option<(Color, f32)>,
 }
 
 /// Animation layer variants.
 #[derive(Debug, Clone)]
+// Shape layers dominate real files, so boxing them would only add indirection.
+#[allow(clippy::large_enum_variant)]
 pub enum Layer {
     /// Vector shape layer
     Shape(ShapeLayer),
     /// Bitmap image layer
     Image(ImageLayer),
+    /// Bitmap image sequence layer
+    ImageSequence(ImageSequenceLayer),
     /// Pre-composed layer
     PreComp(PreCompLayer),
     /// Text layer
     Text(TextLayer),
+    /// Null layer: draws nothing, only moves the layers parented to it
+    Null(NullLayer),
+}
+
+/// Position of a top-level layer in [`Composition::layers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LayerId(pub usize);
+
+/// Invisible layer (Lottie `"ty": 3`) used as a parent for other layers.
+#[derive(Debug, Clone, Default)]
+pub struct NullLayer {
+    /// Layer index (Lottie `"ind"`)
+    pub index: Option<u32>,
+    /// Index of this layer's own parent
+    pub parent: Option<u32>,
+    /// Transform handed down to the children
+    pub transform: Transform,
+}
+
+impl Layer {
+    /// Index, parent and transform of layers that take part in parenting.
+    fn parenting(&self) -> Option<(Option<u32>, Option<u32>, &Transform)> {
+        match self {
+            Layer::Shape(s) => Some((s.index, s.parent, &s.transform)),
+            Layer::Null(n) => Some((n.index, n.parent, &n.transform)),
+            Layer::PreComp(p) => Some((p.index, p.parent, &p.transform)),
+            _ => None,
+        }
+    }
+
+    /// Index and parent slots of layers that take part in parenting.
+    fn parenting_mut(&mut self) -> Option<(&mut Option<u32>, &mut Option<u32>)> {
+        match self {
+            Layer::Shape(s) => Some((&mut s.index, &mut s.parent)),
+            Layer::Null(n) => Some((&mut n.index, &mut n.parent)),
+            Layer::PreComp(p) => Some((&mut p.index, &mut p.parent)),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `tr` never moves or fades what it is applied to.
+fn is_static_identity(tr: &Transform) -> bool {
+    tr.animators.is_empty()
+        && tr.motion.is_empty()
+        && tr.opacity >= 1.0
+        && Affine::from_transform(tr) == Affine::IDENTITY
+}
+
+/// Matrix of the parent chain starting at `parent`, at `frame`. The chain
+/// stops at a missing index or after visiting every layer once, so cycles
+/// cannot loop forever.
+pub(crate) fn parent_matrix(layers: &[Layer], parent: Option<u32>, frame: f32) -> Affine {
+    let mut matrix = Affine::IDENTITY;
+    let mut next = parent;
+    for _ in 0..layers.len() {
+        let Some(ind) = next else { break };
+        let Some((_, up, tr)) = layers
+            .iter()
+            .filter_map(Layer::parenting)
+            .find(|(index, _, _)| *index == Some(ind))
+        else {
+            break;
+        };
+        matrix = Affine::from_transform(&tr.at(frame)) * matrix;
+        next = up;
+    }
+    matrix
+}
+
+/// Largest width or height accepted by [`Composition::validate`].
+const MAX_DIMENSION: u32 = 16384;
+
+/// Structural problem reported by [`Composition::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Width or height is zero or larger than the supported maximum.
+    Dimensions {
+        /// Declared width
+        width: u32,
+        /// Declared height
+        height: u32,
+    },
+    /// The first frame comes after the last frame.
+    FrameRange {
+        /// Declared start frame
+        start: u32,
+        /// Declared end frame
+        end: u32,
+    },
+    /// A layer contains a NaN or infinite coordinate.
+    NonFiniteCoordinate {
+        /// Index of the offending layer
+        layer: usize,
+    },
+    /// A gradient fill has no color stops to sample.
+    EmptyGradientStops {
+        /// Index of the offending layer
+        layer: usize,
+    },
+    /// A matte source layer is not followed by a layer consuming it.
+    DanglingMatte {
+        /// Index of the matte source layer
+        layer: usize,
+    },
+}
+
+/// Reason [`Composition::remove_layer`] left the layers untouched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoveLayerError {
+    /// No layer exists at the requested index.
+    OutOfRange {
+        /// Requested index
+        index: usize,
+        /// Number of layers in the composition
+        len: usize,
+    },
+    /// Other layers are parented to a layer that would be removed.
+    HasChildren {
+        /// Index of the parent layer
+        layer: usize,
+    },
 }
 
 /// Root composition loaded from JSON.
@@ -234,259 +1105,1273 @@ pub struct Composition {
     pub end_frame: u32,
     /// Frames per second
     pub fps: f32,
+    /// Width of a composition pixel relative to its height (`1.0` is square)
+    pub pixel_aspect: f32,
+    /// Opacity multiplied into the whole rendered frame (`1.0` is opaque).
+    /// Only the composition being rendered applies it, not nested precomps.
+    pub opacity: f32,
     /// Flattened layer list
     pub layers: Vec<Layer>,
+    /// Values of the "Color Control" effects, by effect name, that fills
+    /// linked through [`ShapeLayer::fill_control`] follow
+    pub color_controls: HashMap<String, Color>,
+    /// Named timeline segments, in file order
+    pub markers: Vec<Marker>,
 }
 
 impl Composition {
+    /// Set the "Color Control" effect `name` to `color` and recolor every
+    /// fill linked to it, including those in precomps. Linked fills keep
+    /// their own alpha. Returns `false`, changing nothing, when the
+    /// composition has no such control.
+    pub fn set_effect_color(&mut self, name: &str, color: Color) -> bool {
+        let Some(control) = self.color_controls.get_mut(name) else {
+            return false;
+        };
+        *control = color;
+        link_fills(&mut self.layers, name, color);
+        true
+    }
+
+    /// Apply every color control to the fills linked to it.
+    pub(crate) fn apply_color_controls(&mut self) {
+        for (name, color) in &self.color_controls {
+            link_fills(&mut self.layers, name, *color);
+        }
+    }
+
+    /// Human-readable names of the features in the Lottie JSON `data` that
+    /// will not render, such as 3D layers or expressions; see
+    /// [`crate::loader::unsupported_features`]. A document that is not
+    /// valid JSON yields a single entry saying so.
+    pub fn unsupported_features(data: &[u8]) -> Vec<String> {
+        crate::loader::unsupported_features(data)
+            .unwrap_or_else(|err| vec![format!("unreadable JSON ({err})")])
+    }
+
+    /// Number of frames from `start_frame` to `end_frame`, both included.
+    pub fn frame_count(&self) -> u32 {
+        self.end_frame.saturating_sub(self.start_frame) + 1
+    }
+
+    /// Playback time of [`Composition::frame_count`] frames in seconds, or
+    /// `0.0` when the frame rate is not positive.
+    pub fn duration_secs(&self) -> f32 {
+        if self.fps > 0.0 {
+            self.frame_count() as f32 / self.fps
+        } else {
+            0.0
+        }
+    }
+
     /// Calculate the actual frame index after applying start/end offsets and looping.
-    pub fn frame_at(&self, frame: u32) -> u32 {
-        let total = self.end_frame.saturating_sub(self.start_frame) + 1;
-        let local = frame % total;
-        self.start_frame + local
+    pub fn frame_at(&self, frame: impl Into<Frame>) -> Frame {
+        let local = frame.into().0 % self.frame_count();
+        Frame(self.start_frame + local)
+    }
+
+    /// Inline precomp layers into this composition's layer list.
+    ///
+    /// Nested precomps are flattened first. A precomp stays nested when
+    /// inlining would change the rendered result: when its timing or size
+    /// differs from this composition, when it is transformed, faded or
+    /// parented, when it directly follows a matte source, or when it ends
+    /// with a matte source of its own. Inlined layers are given fresh
+    /// `index` values, with their `parent` links renumbered to match, so
+    /// they cannot be mistaken for this composition's layers.
+    pub fn flatten_precomps(&mut self) {
+        let layers = std::mem::take(&mut self.layers);
+        // Inlined layers get indices past every index in use here, so they
+        // cannot collide with this composition's own parenting.
+        let mut next_index = layers
+            .iter()
+            .filter_map(|l| l.parenting().and_then(|(index, _, _)| index))
+            .max()
+            .map_or(1, |max| max + 1);
+        let mut out = Vec::with_capacity(layers.len());
+        for layer in layers {
+            let Layer::PreComp(mut pre) = layer else {
+                out.push(layer);
+                continue;
+            };
+            pre.comp.flatten_precomps();
+            let is_matte = |l: Option<&Layer>| matches!(l, Some(Layer::Shape(s)) if s.is_mask);
+            if self.shares_space(&pre.comp)
+                && pre.time_remap.is_none()
+                && pre.start_time == 0.0
+                && pre.parent.is_none()
+                && is_static_identity(&pre.transform)
+                && !is_matte(out.last())
+                && !is_matte(pre.comp.layers.last())
+            {
+                let mut remap = HashMap::new();
+                for layer in &pre.comp.layers {
+                    if let Some((Some(index), _, _)) = layer.parenting() {
+                        remap.entry(index).or_insert_with(|| {
+                            next_index += 1;
+                            next_index - 1
+                        });
+                    }
+                }
+                for mut layer in pre.comp.layers {
+                    if let Some((index, parent)) = layer.parenting_mut() {
+                        *index = index.and_then(|i| remap.get(&i).copied());
+                        // A parent missing from the precomp never moved the
+                        // layer, so it is dropped rather than left to match
+                        // an index out here.
+                        *parent = parent.and_then(|p| remap.get(&p).copied());
+                    }
+                    out.push(layer);
+                }
+            } else {
+                out.push(Layer::PreComp(pre));
+            }
+        }
+        self.layers = out;
+    }
+
+    /// Whether `other` renders with the same frame mapping and viewport.
+    fn shares_space(&self, other: &Composition) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.start_frame == other.start_frame
+            && self.end_frame == other.end_frame
+            && self.fps == other.fps
+            && self.pixel_aspect == other.pixel_aspect
+    }
+
+    /// Remove the layer at `index` and return every layer removed with it,
+    /// in stacking order.
+    ///
+    /// Matte pairs are kept intact: removing a matte source clears the matte
+    /// of the layer it applied to, and removing a matted layer also drops its
+    /// matte source, which would otherwise matte the next layer instead. The
+    /// source is then returned ahead of the matted layer.
+    ///
+    /// A layer that other layers are parented to is not removed, since its
+    /// children would silently lose its transform; remove or re-parent the
+    /// children first.
+    pub fn remove_layer(&mut self, index: usize) -> Result<Vec<Layer>, RemoveLayerError> {
+        let layer = self.layers.get(index).ok_or(RemoveLayerError::OutOfRange {
+            index,
+            len: self.layers.len(),
+        })?;
+        let source = match layer {
+            Layer::Shape(shape) if shape.matte.is_some() => {
+                index.checked_sub(1).filter(|&i| self.is_matte_source(i))
+            }
+            _ => None,
+        };
+        let removed = [source, Some(index)];
+        let parent = removed.into_iter().flatten().find(|&i| {
+            let own = self.layers[i].parenting().and_then(|(ind, _, _)| ind);
+            own.is_some()
+                && self
+                    .layers
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| !removed.contains(&Some(*j)))
+                    .filter_map(|(_, l)| l.parenting())
+                    .any(|(_, parent, _)| parent == own)
+        });
+        if let Some(layer) = parent {
+            return Err(RemoveLayerError::HasChildren { layer });
+        }
+        let layer = self.layers.remove(index);
+        if let Layer::Shape(shape) = &layer {
+            if shape.is_mask {
+                if let Some(Layer::Shape(next)) = self.layers.get_mut(index) {
+                    next.matte = None;
+                }
+            }
+        }
+        Ok(match source {
+            Some(i) => vec![self.layers.remove(i), layer],
+            None => vec![layer],
+        })
+    }
+
+    /// Insert `layer` at `index`, returning the index it ended up at.
+    ///
+    /// A layer is never placed between a matte source and the layer it
+    /// mattes; such an insert is moved up to sit above the matte source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > layers.len()`.
+    pub fn insert_layer(&mut self, index: usize, layer: Layer) -> usize {
+        assert!(index <= self.layers.len(), "layer index out of range");
+        let splits_pair = index
+            .checked_sub(1)
+            .is_some_and(|above| self.is_matte_source(above))
+            && matches!(self.layers.get(index), Some(Layer::Shape(s)) if s.matte.is_some());
+        let index = if splits_pair { index - 1 } else { index };
+        self.layers.insert(index, layer);
+        index
+    }
+
+    fn is_matte_source(&self, index: usize) -> bool {
+        matches!(self.layers.get(index), Some(Layer::Shape(s)) if s.is_mask)
+    }
+
+    /// Call `f` on every paint in the composition, recursing into precomps.
+    ///
+    /// Gradient fills are presented as they are. Other shape fills and
+    /// strokes and text colors are stored as plain colors, so they are
+    /// presented as [`Paint::Solid`]. Setting [`Paint::None`] removes a fill
+    /// or stroke; a gradient assigned to a plain color slot is ignored and
+    /// the previous color is kept.
+    pub fn visit_paints_mut(&mut self, mut f: impl FnMut(&mut Paint)) {
+        self.visit_paints_dyn(&mut f);
+    }
+
+    fn visit_paints_dyn(&mut self, f: &mut dyn FnMut(&mut Paint)) {
+        for layer in &mut self.layers {
+            match layer {
+                Layer::Shape(shape) => {
+                    match &mut shape.fill_paint {
+                        Some(paint) => {
+                            f(paint);
+                            if let Paint::None = paint {
+                                shape.fill_paint = None;
+                            }
+                        }
+                        None => {
+                            // A replaced color stops following the keyframes.
+                            let before = shape.fill;
+                            visit_color_slot(&mut shape.fill, f);
+                            if shape.fill != before {
+                                shape.fill_animator = None;
+                            }
+                        }
+                    }
+                    visit_color_slot(&mut shape.stroke, f);
+                }
+                Layer::Text(text) => {
+                    let mut slot = Some(text.color);
+                    visit_color_slot(&mut slot, f);
+                    if let Some(c) = slot {
+                        text.color = c;
+                    }
+                }
+                Layer::PreComp(pre) => pre.comp.visit_paints_dyn(f),
+                Layer::Image(_) | Layer::ImageSequence(_) | Layer::Null(_) => {}
+            }
+        }
+    }
+
+    /// Check the composition for structural problems before rendering.
+    /// All detected problems are returned, not just the first one.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if self.width == 0
+            || self.height == 0
+            || self.width > MAX_DIMENSION
+            || self.height > MAX_DIMENSION
+        {
+            errors.push(ValidationError::Dimensions {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if self.start_frame > self.end_frame {
+            errors.push(ValidationError::FrameRange {
+                start: self.start_frame,
+                end: self.end_frame,
+            });
+        }
+        validate_layers(&self.layers, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Deterministic hash of everything that affects rendering: size, frame
+    /// range, and every layer's geometry, paint, transform and animation.
+    ///
+    /// The value is stable within one build of this crate: identical input
+    /// hashes equally across runs, so it can key caches of rendered frames.
+    /// Fields are fed through their `Debug` form, which another crate or
+    /// compiler version may spell differently, so caches that outlive the
+    /// build should be keyed by the build as well.
+    pub fn content_hash(&self) -> u64 {
+        let mut h = ContentHasher::new();
+        hash_composition(&mut h, self);
+        h.0
     }
 
     /// Render a frame into the provided RGBA8888 buffer.
     pub fn render_sync(
         &self,
+        frame: impl Into<Frame>,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) {
+        self.render_with(
+            &Renderer::default(),
+            frame.into().0,
+            buffer,
+            width,
+            height,
+            stride,
+        );
+    }
+
+    /// The first marker called `name`.
+    pub fn marker(&self, name: &str) -> Option<&Marker> {
+        self.markers.iter().find(|m| m.name == name)
+    }
+
+    /// Render frame `local_frame` of the segment marked `name`, counted
+    /// from the marker's start and clamped to the segment's last frame, one
+    /// before the marker's start plus its duration. Returns `false`,
+    /// leaving `buffer` untouched, when there is no such marker.
+    pub fn render_segment(
+        &self,
+        name: &str,
+        local_frame: u32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) -> bool {
+        let Some(marker) = self.marker(name) else {
+            return false;
+        };
+        let last = marker.duration.saturating_sub(1);
+        let frame = marker.start.saturating_sub(self.start_frame) + local_frame.min(last);
+        self.render_sync(frame, buffer, width, height, stride);
+        true
+    }
+
+    /// Cross-fade two rendered frames into `buffer`: `t = 0` yields
+    /// `frame_a`, `t = 1` yields `frame_b`. This blends pixels rather than
+    /// sampling animators at fractional frames, so it is only a cheap
+    /// approximation for smoothing slow playback.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_interpolated(
+        &self,
+        frame_a: impl Into<Frame>,
+        frame_b: impl Into<Frame>,
+        t: f32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) {
+        let t = t.clamp(0.0, 1.0);
+        self.render_sync(frame_a, buffer, width, height, stride);
+        let mut other = vec![0u8; buffer.len()];
+        self.render_sync(frame_b, &mut other, width, height, stride);
+        for (a, b) in buffer.iter_mut().zip(&other) {
+            *a = (*a as f32 + (*b as f32 - *a as f32) * t).round() as u8;
+        }
+    }
+
+    /// Bounding box `(min, max)` of everything visible at `frame`, in
+    /// composition space. Curves are flattened with `tolerance` and strokes
+    /// add half their width. Returns `None` when nothing would be drawn.
+    pub fn content_bounds(&self, frame: u32, tolerance: f32) -> Option<(Vec2, Vec2)> {
+        let frame_no = self.frame_at(frame).0;
+        let mut bounds: Option<(Vec2, Vec2)> = None;
+        let mut add = |min: Vec2, max: Vec2| {
+            bounds = Some(match bounds {
+                Some(b) => union_bounds(b, (min, max)),
+                None => (min, max),
+            });
+        };
+        for layer in &self.layers {
+            match layer {
+                Layer::Shape(shape) => {
+                    if shape.is_mask {
+                        continue;
+                    }
+                    let frame = frame_no as f32;
+                    let own = shape.local_frame(frame, self.fps);
+                    let matrix = parent_matrix(&self.layers, shape.parent, frame)
+                        * Affine::from_transform(&shape.transform.at(own));
+                    add_shape_bounds(shape, own, matrix, tolerance, &mut add);
+                }
+                Layer::Text(text) => {
+                    let mut cursor_x = text.position.x;
+                    for ch in text.text.chars() {
+                        let m = text.font.metrics(ch, text.size);
+                        if m.width > 0 && m.height > 0 {
+                            let x0 = cursor_x + m.xmin as f32;
+                            let y0 = text.position.y - m.height as f32 - m.ymin as f32;
+                            add(
+                                Vec2 { x: x0, y: y0 },
+                                Vec2 {
+                                    x: x0 + m.width as f32,
+                                    y: y0 + m.height as f32,
+                                },
+                            );
+                        }
+                        cursor_x += m.advance_width;
+                    }
+                }
+                Layer::PreComp(pre) => {
+                    let inner = pre.local_frame(frame_no as f32).round() as u32;
+                    let Some((mut min, mut max)) = pre.comp.content_bounds(inner, tolerance) else {
+                        continue;
+                    };
+                    if !pre.collapse_transform {
+                        // The raster is clipped to the nested composition.
+                        let size = Vec2 {
+                            x: pre.comp.width as f32,
+                            y: pre.comp.height as f32,
+                        };
+                        (min, max) = (
+                            Vec2 {
+                                x: min.x.max(0.0),
+                                y: min.y.max(0.0),
+                            },
+                            Vec2 {
+                                x: max.x.min(size.x),
+                                y: max.y.min(size.y),
+                            },
+                        );
+                        if min.x >= max.x || min.y >= max.y {
+                            continue;
+                        }
+                    }
+                    let frame = frame_no as f32;
+                    let matrix = parent_matrix(&self.layers, pre.parent, frame)
+                        * Affine::from_transform(&pre.transform.at(frame));
+                    for (x, y) in [
+                        (min.x, min.y),
+                        (max.x, min.y),
+                        (min.x, max.y),
+                        (max.x, max.y),
+                    ] {
+                        let p = matrix.transform_point(Vec2 { x, y });
+                        add(p, p);
+                    }
+                }
+                Layer::ImageSequence(seq) => {
+                    let local = frame_no.saturating_sub(self.start_frame) as f32;
+                    if let Some(img) = seq.frame_at(local, self.fps) {
+                        add(
+                            Vec2::default(),
+                            Vec2 {
+                                x: img.width as f32,
+                                y: img.height as f32,
+                            },
+                        );
+                    }
+                }
+                Layer::Image(img) => {
+                    let own = img.local_frame(frame_no as f32, self.fps);
+                    let matrix = Affine::from_transform(&img.transform.at(own));
+                    let (w, h) = (img.width as f32, img.height as f32);
+                    for (x, y) in [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)] {
+                        let p = matrix.transform_point(Vec2 { x, y });
+                        add(p, p);
+                    }
+                }
+                Layer::Null(_) => {}
+            }
+        }
+        bounds
+    }
+
+    /// Union of [`Composition::content_bounds`] over every frame from
+    /// `start_frame` to `end_frame`. An animation with no visible content
+    /// yields a zero-sized box at the origin.
+    pub fn animation_bounds(&self, tolerance: f32) -> (Vec2, Vec2) {
+        (0..self.frame_count())
+            .filter_map(|frame| self.content_bounds(frame, tolerance))
+            .reduce(union_bounds)
+            .unwrap_or_default()
+    }
+
+    /// Report, for every frame from `start_frame` to `end_frame`, whether it
+    /// has any visible pixel when rendered at `width`×`height`.
+    pub fn content_frames(&self, width: usize, height: usize) -> Vec<bool> {
+        let mut buf = vec![0u8; width * height * 4];
+        (0..self.frame_count())
+            .map(|frame| {
+                self.render_sync(frame, &mut buf, width, height, width * 4);
+                buf.chunks_exact(4).any(|px| px[3] != 0)
+            })
+            .collect()
+    }
+
+    /// Render a frame using the options of `renderer`.
+    pub(crate) fn render_with(
+        &self,
+        renderer: &Renderer,
         frame: u32,
         buffer: &mut [u8],
         width: usize,
         height: usize,
         stride: usize,
     ) {
-        use crate::geometry::Path;
+        renderer.draw_clipped(buffer, width, height, stride, |buf, w, h| {
+            renderer.clear_buffer(buf, w, h, stride);
+            self.draw_frame(renderer, frame as f32, buf, w, h, stride);
+        });
+    }
+
+    /// Composite all layers over `buffer` as [`Composition::draw_layers`]
+    /// does, faded as a whole by the composition opacity or the one
+    /// `renderer` overrides it with.
+    pub(crate) fn draw_frame(
+        &self,
+        renderer: &Renderer,
+        frame: f32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) {
+        let opacity = renderer.opacity.unwrap_or(self.opacity).clamp(0.0, 1.0);
+        if opacity >= 1.0 {
+            self.draw_layers(renderer, frame, buffer, width, height, stride);
+        } else if opacity > 0.0 {
+            let mut frame_buf = vec![0u8; buffer.len()];
+            self.draw_layers(renderer, frame, &mut frame_buf, width, height, stride);
+            crate::renderer::cpu::composite_layer(
+                buffer,
+                &frame_buf,
+                opacity,
+                BlendMode::Normal,
+                width,
+                height,
+                stride,
+            );
+        }
+    }
+
+    /// Composite all layers over the current contents of `buffer`.
+    /// `frame` counts from `start_frame` and may fall between frames.
+    pub(crate) fn draw_layers(
+        &self,
+        renderer: &Renderer,
+        frame: f32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+    ) {
+        self.draw_layers_where(renderer, frame, buffer, width, height, stride, |_| true);
+    }
+
+    /// Composite the layers for which `keep` returns `true`. Matte sources
+    /// are always rasterized so a kept `tt` layer is still matted.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn draw_layers_where(
+        &self,
+        renderer: &Renderer,
+        frame: f32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+        keep: impl Fn(LayerId) -> bool,
+    ) {
+        let (scale, offset) = renderer.viewport(self, width, height);
+        let view = Affine::scale_translate(scale, offset);
+        self.draw_layers_in(renderer, view, frame, buffer, width, height, stride, &keep);
+    }
+
+    /// Composite the layers for which `keep` returns `true`, with `view`
+    /// mapping composition space to buffer pixels.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_layers_in(
+        &self,
+        renderer: &Renderer,
+        view: Affine,
+        frame: f32,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        stride: usize,
+        keep: &dyn Fn(LayerId) -> bool,
+    ) {
         use crate::renderer::cpu::{
-            blend_masked, draw_mask, draw_path, draw_path_masked, draw_stroke, draw_stroke_masked,
-            draw_text,
+            blend_masked, composite_layer, draw_image, draw_mask, draw_raster, draw_text,
         };
-        use crate::types::{Color, Paint, Vec2};
+        use crate::renderer::effects::apply_effects;
 
-        let _frame_no = self.frame_at(frame);
-        buffer.fill(0);
-        let sx = width as f32 / self.width as f32;
-        let sy = height as f32 / self.height as f32;
+        let frame = frame.max(0.0);
+        let frame_no = self.frame_at(frame as u32).0 as f32 + frame.fract();
+        let map = |p: Vec2| view.transform_point(p);
+        // Pixels per composition unit, for lengths that are not axis-aligned.
+        let unit = (view.a * view.d - view.b * view.c).abs().sqrt();
 
-        let mut mask_buf = vec![0u8; width * height * 4];
-        let mut layer_buf = vec![0u8; buffer.len()];
-        let mut have_mask = false;
+        // Track matte buffers are only needed when a matte source is present.
+        let uses_matte = self
+            .layers
+            .iter()
+            .any(|l| matches!(l, Layer::Shape(s) if s.is_mask));
+        let mut mask_buf = if uses_matte {
+            renderer.record(|s| s.mask_buffers += 1);
+            vec![0u8; width * height]
+        } else {
+            Vec::new()
+        };
 
-        for layer in &self.layers {
+        for (idx, layer) in self.layers.iter().enumerate() {
+            let is_matte_source = matches!(layer, Layer::Shape(s) if s.is_mask);
+            if !is_matte_source && !keep(LayerId(idx)) {
+                continue;
+            }
             match layer {
                 Layer::Shape(shape) => {
+                    // Parents sample at the composition frame; only this
+                    // layer's own properties follow its time remap.
+                    let own_frame = shape.local_frame(frame_no, self.fps);
+                    let local = shape.transform.at(own_frame);
+                    let layer = view
+                        * parent_matrix(&self.layers, shape.parent, frame_no)
+                        * Affine::from_transform(&local);
+                    let map = |p: Vec2| layer.transform_point(p);
                     if shape.is_mask {
                         mask_buf.fill(0);
-                        for cmds in &shape.paths {
-                            let mut path = Path::new();
-                            for cmd in cmds {
-                                match *cmd {
-                                    PathCommand::MoveTo(p) => path.move_to(Vec2 {
-                                        x: p.x * sx,
-                                        y: p.y * sy,
-                                    }),
-                                    PathCommand::LineTo(p) => path.line_to(Vec2 {
-                                        x: p.x * sx,
-                                        y: p.y * sy,
-                                    }),
-                                    PathCommand::CubicTo(c1, c2, p) => path.cubic_to(
-                                        Vec2 {
-                                            x: c1.x * sx,
-                                            y: c1.y * sy,
-                                        },
-                                        Vec2 {
-                                            x: c2.x * sx,
-                                            y: c2.y * sy,
-                                        },
-                                        Vec2 {
-                                            x: p.x * sx,
-                                            y: p.y * sy,
-                                        },
-                                    ),
-                                    PathCommand::Close => path.close(),
-                                }
-                            }
-                            draw_mask(&path, &mut mask_buf, width, height);
+                        for path in &shape.paths {
+                            draw_mask(&path.map_points(map), &mut mask_buf, width, height);
                         }
-                        have_mask = true;
                         continue;
                     }
+                    // A `tt` layer is matted by the layer directly above it in
+                    // the file; a matte source anywhere else is ignored.
+                    let have_mask = shape.matte.is_some()
+                        && idx
+                            .checked_sub(1)
+                            .and_then(|above| self.layers.get(above))
+                            .is_some_and(|l| matches!(l, Layer::Shape(s) if s.is_mask));
 
-                    let mut local_mask = None;
-                    if let Some(mask_paths) = &shape.mask {
-                        let mut buf_m = vec![0u8; buffer.len()];
-                        for cmds in mask_paths {
-                            let mut mask_path = Path::new();
-                            for cmd in cmds {
-                                match *cmd {
-                                    PathCommand::MoveTo(p) => mask_path.move_to(Vec2 {
-                                        x: p.x * sx,
-                                        y: p.y * sy,
-                                    }),
-                                    PathCommand::LineTo(p) => mask_path.line_to(Vec2 {
-                                        x: p.x * sx,
-                                        y: p.y * sy,
-                                    }),
-                                    PathCommand::CubicTo(c1, c2, p) => mask_path.cubic_to(
-                                        Vec2 {
-                                            x: c1.x * sx,
-                                            y: c1.y * sy,
-                                        },
-                                        Vec2 {
-                                            x: c2.x * sx,
-                                            y: c2.y * sy,
-                                        },
-                                        Vec2 {
-                                            x: p.x * sx,
-                                            y: p.y * sy,
-                                        },
-                                    ),
-                                    PathCommand::Close => mask_path.close(),
+                    let opacity = local.opacity.clamp(0.0, 1.0);
+                    let coverage = shape.mask.as_ref().filter(|_| shape.has_mask).map(|paths| {
+                        renderer.record(|s| s.mask_buffers += 1);
+                        mask_coverage(shape, paths, map, unit, width, height)
+                    });
+
+                    // Layers with masks, a matte, effects, partial opacity or
+                    // a blend mode are drawn on their own first, so those
+                    // apply to the whole layer and its fill and stroke fade
+                    // and blend together.
+                    let mut own = (have_mask
+                        || coverage.is_some()
+                        || !shape.effects.is_empty()
+                        || opacity < 1.0
+                        || shape.blend_mode != BlendMode::Normal)
+                        .then(|| vec![0u8; buffer.len()]);
+                    let target: &mut [u8] = match own.as_mut() {
+                        Some(own) => own,
+                        None => buffer,
+                    };
+
+                    draw_shape_paints(
+                        renderer, shape, own_frame, layer, unit, target, width, height, stride,
+                    );
+
+                    let Some(mut own) = own else {
+                        continue;
+                    };
+                    apply_effects(&shape.effects, &mut own, width, height, stride, unit);
+                    let mut coverage = coverage;
+                    if have_mask {
+                        let inverted = shape.matte == Some(MatteType::AlphaInv);
+                        let cov = coverage.get_or_insert_with(|| vec![255; width * height]);
+                        for (c, &m) in cov.iter_mut().zip(&mask_buf) {
+                            let m = if inverted { 255 - m } else { m };
+                            *c = (*c as u16 * m as u16 / 255) as u8;
+                        }
+                        mask_buf.fill(0);
+                    }
+                    match coverage {
+                        Some(cov) if shape.blend_mode != BlendMode::Normal => {
+                            // Fold the coverage into the layer itself, so the
+                            // blend sees it as the layer's alpha.
+                            for y in 0..height {
+                                for x in 0..width {
+                                    let m = cov[y * width + x] as u16;
+                                    let o = y * stride + x * 4;
+                                    for c in &mut own[o..o + 4] {
+                                        *c = (*c as u16 * m / 255) as u8;
+                                    }
                                 }
                             }
-                            draw_path(
-                                &mask_path,
-                                Paint::Solid(Color {
-                                    r: 0,
-                                    g: 0,
-                                    b: 0,
-                                    a: 255,
-                                }),
-                                &mut buf_m,
+                            composite_layer(
+                                buffer,
+                                &own,
+                                opacity,
+                                shape.blend_mode,
                                 width,
                                 height,
                                 stride,
-                            );
-                        }
-                        local_mask = Some(buf_m);
-                    }
-
-                    for cmds in &shape.paths {
-                        let mut path = Path::new();
-                        for cmd in cmds {
-                            match *cmd {
-                                PathCommand::MoveTo(p) => path.move_to(Vec2 {
-                                    x: p.x * sx,
-                                    y: p.y * sy,
-                                }),
-                                PathCommand::LineTo(p) => path.line_to(Vec2 {
-                                    x: p.x * sx,
-                                    y: p.y * sy,
-                                }),
-                                PathCommand::CubicTo(c1, c2, p) => path.cubic_to(
-                                    Vec2 {
-                                        x: c1.x * sx,
-                                        y: c1.y * sy,
-                                    },
-                                    Vec2 {
-                                        x: c2.x * sx,
-                                        y: c2.y * sy,
-                                    },
-                                    Vec2 {
-                                        x: p.x * sx,
-                                        y: p.y * sy,
-                                    },
-                                ),
-                                PathCommand::Close => path.close(),
-                            }
-                        }
-                        let render_path = if let Some((s, e)) = shape.trim {
-                            path.trim(s, e, 0.2)
-                        } else {
-                            path.clone()
-                        };
-
-                        if let Some(fill) = shape.fill {
-                            if have_mask && shape.matte.is_some() {
-                                draw_path(
-                                    &render_path,
-                                    Paint::Solid(fill),
-                                    &mut layer_buf,
-                                    width,
-                                    height,
-                                    stride,
-                                );
-                            } else if let Some(mask) = local_mask.as_ref() {
-                                draw_path_masked(
-                                    &render_path,
-                                    Paint::Solid(fill),
-                                    mask,
-                                    buffer,
-                                    width,
-                                    height,
-                                    stride,
-                                );
-                            } else {
-                                draw_path(
-                                    &render_path,
-                                    Paint::Solid(fill),
-                                    buffer,
-                                    width,
-                                    height,
-                                    stride,
-                                );
-                            }
+                            )
                         }
-
-                        if let Some(stroke) = shape.stroke {
-                            if have_mask && shape.matte.is_some() {
-                                draw_stroke(
-                                    &render_path,
-                                    shape.stroke_width,
-                                    Paint::Solid(stroke),
-                                    &mut layer_buf,
-                                    width,
-                                    height,
-                                    stride,
-                                );
-                            } else if let Some(mask) = local_mask.as_ref() {
-                                draw_stroke_masked(
-                                    &render_path,
-                                    shape.stroke_width,
-                                    Paint::Solid(stroke),
-                                    mask,
-                                    buffer,
-                                    width,
-                                    height,
-                                    stride,
-                                );
-                            } else {
-                                draw_stroke(
-                                    &render_path,
-                                    shape.stroke_width,
-                                    Paint::Solid(stroke),
-                                    buffer,
-                                    width,
-                                    height,
-                                    stride,
-                                );
+                        Some(mut cov) => {
+                            if opacity < 1.0 {
+                                for c in &mut cov {
+                                    *c = (*c as f32 * opacity).round() as u8;
+                                }
                             }
+                            blend_masked(
+                                buffer,
+                                &own,
+                                &cov,
+                                MatteType::Alpha,
+                                width,
+                                height,
+                                stride,
+                            )
                         }
-                    }
-
-                    if have_mask {
-                        if let Some(m) = shape.matte {
-                            blend_masked(buffer, &layer_buf, &mask_buf, m, width, height, stride);
-                        }
-                        layer_buf.fill(0);
-                        mask_buf.fill(0);
-                        have_mask = false;
+                        None => composite_layer(
+                            buffer,
+                            &own,
+                            opacity,
+                            shape.blend_mode,
+                            width,
+                            height,
+                            stride,
+                        ),
                     }
                 }
                 Layer::Text(text) => {
                     let mut tl = text.clone();
-                    tl.position.x *= sx;
-                    tl.position.y *= sy;
+                    tl.position = map(tl.position);
                     draw_text(&tl, buffer, width, height, stride);
                 }
                 Layer::PreComp(pre) => {
-                    pre.comp.render_sync(frame, buffer, width, height, stride);
+                    let inner = pre.local_frame(frame_no);
+                    let local = pre.transform.at(frame_no);
+                    let opacity = local.opacity.clamp(0.0, 1.0);
+                    if opacity <= 0.0 {
+                        continue;
+                    }
+                    let placed = parent_matrix(&self.layers, pre.parent, frame_no)
+                        * Affine::from_transform(&local);
+                    // Partial opacity fades the nested layers as one.
+                    let mut own = (opacity < 1.0).then(|| vec![0u8; buffer.len()]);
+                    let target: &mut [u8] = match own.as_mut() {
+                        Some(own) => own,
+                        None => buffer,
+                    };
+                    let unclipped = placed == Affine::IDENTITY
+                        && (pre.comp.width, pre.comp.height) == (self.width, self.height);
+                    if pre.collapse_transform || unclipped {
+                        pre.comp.draw_layers_in(
+                            renderer,
+                            view * placed,
+                            inner,
+                            target,
+                            width,
+                            height,
+                            stride,
+                            &|_| true,
+                        );
+                    } else {
+                        // Rasterize at the parent's scale, then place the
+                        // pixels with the layer's own matrix.
+                        let raster_scale = Vec2 {
+                            x: view.a.hypot(view.b),
+                            y: view.c.hypot(view.d),
+                        };
+                        let size = (
+                            (pre.comp.width as f32 * raster_scale.x).ceil() as usize,
+                            (pre.comp.height as f32 * raster_scale.y).ceil() as usize,
+                        );
+                        if size.0 == 0 || size.1 == 0 {
+                            continue;
+                        }
+                        let mut raster = vec![0u8; size.0 * size.1 * 4];
+                        let to_raster = Affine::scale_translate(raster_scale, Vec2::default());
+                        pre.comp.draw_layers_in(
+                            renderer,
+                            to_raster,
+                            inner,
+                            &mut raster,
+                            size.0,
+                            size.1,
+                            size.0 * 4,
+                            &|_| true,
+                        );
+                        let unscale = Affine::scale_translate(
+                            Vec2 {
+                                x: 1.0 / raster_scale.x,
+                                y: 1.0 / raster_scale.y,
+                            },
+                            Vec2::default(),
+                        );
+                        draw_raster(
+                            &raster,
+                            size,
+                            view * placed * unscale,
+                            target,
+                            width,
+                            height,
+                            stride,
+                        );
+                    }
+                    if let Some(own) = own {
+                        composite_layer(
+                            buffer,
+                            &own,
+                            opacity,
+                            BlendMode::Normal,
+                            width,
+                            height,
+                            stride,
+                        );
+                    }
+                }
+                Layer::ImageSequence(seq) => {
+                    let local = (frame_no - self.start_frame as f32).max(0.0);
+                    if let Some(img) = seq.frame_at(local, self.fps) {
+                        draw_image(img, view, buffer, width, height, stride);
+                    }
+                }
+                Layer::Image(img) => {
+                    let local = img.transform.at(img.local_frame(frame_no, self.fps));
+                    let matrix = view * Affine::from_transform(&local);
+                    draw_image(img, matrix, buffer, width, height, stride);
+                }
+                Layer::Null(_) => {}
+            }
+        }
+    }
+}
+
+/// Fill and stroke `shape`'s paths, placed by `layer`, into `target`, then
+/// draw each of its blend groups on its own and blend it over them.
+#[allow(clippy::too_many_arguments)]
+fn draw_shape_paints(
+    renderer: &Renderer,
+    shape: &ShapeLayer,
+    own_frame: f32,
+    layer: Affine,
+    unit: f32,
+    target: &mut [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+) {
+    use crate::renderer::cpu::{composite_layer, draw_fill, draw_stroke};
+
+    let map = |p: Vec2| layer.transform_point(p);
+    let stroke = shape.visible_stroke(own_frame);
+    let whole: Vec<Path> = shape
+        .paths
+        .iter()
+        .map(|path| path.map_points(map))
+        .filter(|path| !path.is_empty())
+        .collect();
+    let trimmed: Option<Vec<Path>> = shape.trim.map(|(s, e)| {
+        whole
+            .iter()
+            .map(|path| path.trim(s, e, 0.2))
+            .filter(|path| !path.is_empty())
+            .collect()
+    });
+    let trimmed_for = |trims: bool| match &trimmed {
+        Some(paths) if trims => paths,
+        _ => &whole,
+    };
+    let stroke_paths = trimmed_for(shape.trim_target.trims_stroke());
+    let fill_paths = trimmed_for(shape.trim_target.trims_fill());
+
+    // All paths share one fill, so they are filled together as a compound
+    // path and holes follow the fill rule.
+    if let Some(fill) = shape
+        .effective_fill_at(own_frame)
+        .filter(|_| !fill_paths.is_empty())
+    {
+        let mut compound = Path::new();
+        for path in fill_paths {
+            compound.segments.extend(path.segments.iter().cloned());
+        }
+        renderer.record(|s| s.paths_drawn += 1);
+        draw_fill(
+            &compound,
+            shape.fill_rule,
+            fill.transformed(&layer),
+            target,
+            width,
+            height,
+            stride,
+        );
+    }
+    if let Some((stroke, stroke_width)) = stroke {
+        // Dash lengths are composition units along the path.
+        let dash = shape.dash_at(own_frame).map(|(pattern, offset)| {
+            let pattern: Vec<f32> = pattern.iter().map(|len| len * unit).collect();
+            (pattern, offset * unit)
+        });
+        for render_path in stroke_paths {
+            let dashed = dash
+                .as_ref()
+                .map(|(d, offset)| render_path.dash(d, *offset, 0.2));
+            let stroked = dashed.as_ref().unwrap_or(render_path);
+            if stroked.is_empty() {
+                continue;
+            }
+            renderer.record(|s| s.paths_drawn += 1);
+            draw_stroke(
+                stroked,
+                stroke_width,
+                shape.stroke_style,
+                Paint::Solid(stroke),
+                target,
+                width,
+                height,
+                stride,
+            );
+        }
+    }
+    for group in &shape.blend_groups {
+        let mut own = vec![0u8; target.len()];
+        draw_shape_paints(
+            renderer, group, own_frame, layer, unit, &mut own, width, height, stride,
+        );
+        composite_layer(target, &own, 1.0, group.blend_mode, width, height, stride);
+    }
+}
+
+/// Combine the masks of `shape` into one coverage buffer of `width * height`
+/// bytes. Each mask is inverted and faded by its `"inv"` and `"o"` before it
+/// is combined. When the first active mask subtracts or intersects, the
+/// coverage starts out full, so such a mask on its own clips as expected.
+fn mask_coverage(
+    shape: &ShapeLayer,
+    paths: &[Path],
+    map: impl Fn(Vec2) -> Vec2 + Copy,
+    unit: f32,
+    width: usize,
+    height: usize,
+) -> Vec<u8> {
+    use crate::renderer::cpu::draw_mask;
+
+    let mode = |i: usize| shape.mask_modes.get(i).copied().unwrap_or_default();
+    let first = (0..paths.len()).map(mode).find(|m| *m != MaskMode::None);
+    let start = if matches!(first, Some(MaskMode::Subtract | MaskMode::Intersect)) {
+        255
+    } else {
+        0
+    };
+    let mut coverage = vec![start; width * height];
+    let mut single = vec![0u8; width * height];
+    for (i, path) in paths.iter().enumerate() {
+        let mode = mode(i);
+        if mode == MaskMode::None {
+            continue;
+        }
+        let mut path = path.map_points(map);
+        if let Some(&x) = shape.mask_expansion.get(i).filter(|x| **x != 0.0) {
+            // Expansion is given in composition units.
+            path = path.offset(x * unit);
+        }
+        single.fill(0);
+        draw_mask(&path, &mut single, width, height);
+        let inverted = shape.mask_inverted.get(i).copied().unwrap_or(false);
+        let opacity = shape.mask_opacity.get(i).map_or(1.0, |o| o.clamp(0.0, 1.0));
+        if inverted || opacity < 1.0 {
+            for m in &mut single {
+                let covered = if inverted { 255 - *m } else { *m };
+                *m = (covered as f32 * opacity).round() as u8;
+            }
+        }
+        for (c, &m) in coverage.iter_mut().zip(&single) {
+            *c = match mode {
+                MaskMode::Add => (*c).max(m),
+                MaskMode::Subtract => (*c).min(255 - m),
+                MaskMode::Intersect => (*c).min(m),
+                MaskMode::None => *c,
+            };
+        }
+    }
+    coverage
+}
+
+/// Present a solid-only color slot to `f` as a [`Paint`] and store the result.
+fn visit_color_slot(slot: &mut Option<Color>, f: &mut dyn FnMut(&mut Paint)) {
+    let Some(color) = *slot else {
+        return;
+    };
+    let mut paint = Paint::Solid(color);
+    f(&mut paint);
+    match paint {
+        Paint::Solid(c) => *slot = Some(c),
+        Paint::None => *slot = None,
+        Paint::Linear(_) | Paint::Radial(_) => {}
+    }
+}
+
+/// Give fills linked to the color control `name` the color's RGB.
+fn link_fills(layers: &mut [Layer], name: &str, color: Color) {
+    for layer in layers {
+        match layer {
+            Layer::Shape(shape) if shape.fill_control.as_deref() == Some(name) => {
+                let a = shape.fill.map_or(255, |c| c.a);
+                shape.fill = Some(Color { a, ..color });
+                shape.fill_animator = None;
+            }
+            Layer::PreComp(pre) => link_fills(&mut pre.comp.layers, name, color),
+            _ => {}
+        }
+    }
+}
+
+/// Smallest box containing both `(min, max)` boxes.
+fn union_bounds(a: (Vec2, Vec2), b: (Vec2, Vec2)) -> (Vec2, Vec2) {
+    (
+        Vec2 {
+            x: a.0.x.min(b.0.x),
+            y: a.0.y.min(b.0.y),
+        },
+        Vec2 {
+            x: a.1.x.max(b.1.x),
+            y: a.1.y.max(b.1.y),
+        },
+    )
+}
+
+/// Extend `add` by the extent of `shape`'s visible paints and its blend
+/// groups at `frame`, placed by `matrix`.
+fn add_shape_bounds<F: FnMut(Vec2, Vec2)>(
+    shape: &ShapeLayer,
+    frame: f32,
+    matrix: Affine,
+    tolerance: f32,
+    add: &mut F,
+) {
+    for group in &shape.blend_groups {
+        add_shape_bounds(group, frame, matrix, tolerance, add);
+    }
+    let stroke = shape.visible_stroke(frame);
+    let no_fill = shape.fill.is_none() && shape.fill_paint.is_none();
+    if no_fill && stroke.is_none() {
+        return;
+    }
+    let pad = stroke.map_or(0.0, |(_, width)| width * 0.5);
+    // An untrimmed paint covers the whole path anyway.
+    let trim = shape.trim.filter(|_| {
+        (no_fill || shape.trim_target.trims_fill())
+            && (stroke.is_none() || shape.trim_target.trims_stroke())
+    });
+    for path in &shape.paths {
+        let mut path = path.map_points(|p| matrix.transform_point(p));
+        if let Some((s, e)) = trim {
+            path = path.trim(s, e, tolerance);
+        }
+        for seg in path.flatten(tolerance) {
+            for p in [seg.from, seg.to] {
+                add(
+                    Vec2 {
+                        x: p.x - pad,
+                        y: p.y - pad,
+                    },
+                    Vec2 {
+                        x: p.x + pad,
+                        y: p.y + pad,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn validate_layers(layers: &[Layer], errors: &mut Vec<ValidationError>) {
+    for (idx, layer) in layers.iter().enumerate() {
+        match layer {
+            Layer::Shape(shape) => {
+                let finite = shape
+                    .paths
+                    .iter()
+                    .chain(shape.mask.iter().flatten())
+                    .flat_map(|path| &path.segments)
+                    .all(segment_is_finite);
+                if !finite || !shape.stroke_width.is_finite() {
+                    errors.push(ValidationError::NonFiniteCoordinate { layer: idx });
+                }
+                if shape.fill_paint.as_ref().is_some_and(has_no_stops) {
+                    errors.push(ValidationError::EmptyGradientStops { layer: idx });
+                }
+                if shape.is_mask {
+                    let consumed = matches!(
+                        layers.get(idx + 1),
+                        Some(Layer::Shape(next)) if next.matte.is_some()
+                    );
+                    if !consumed {
+                        errors.push(ValidationError::DanglingMatte { layer: idx });
+                    }
+                }
+            }
+            Layer::Text(text) => {
+                if !text.position.x.is_finite() || !text.position.y.is_finite() {
+                    errors.push(ValidationError::NonFiniteCoordinate { layer: idx });
+                }
+                if text
+                    .outlines
+                    .as_ref()
+                    .is_some_and(|o| has_no_stops(&o.fill))
+                {
+                    errors.push(ValidationError::EmptyGradientStops { layer: idx });
                 }
-                Layer::Image(_) => {}
             }
+            Layer::PreComp(pre) => validate_layers(&pre.comp.layers, errors),
+            Layer::Image(_) | Layer::ImageSequence(_) | Layer::Null(_) => {}
         }
     }
 }
 
+/// Whether `paint` is a gradient without any color stops.
+fn has_no_stops(paint: &Paint) -> bool {
+    match paint {
+        Paint::Linear(g) => g.stops.is_empty(),
+        Paint::Radial(g) => g.stops.is_empty(),
+        Paint::None | Paint::Solid(_) => false,
+    }
+}
+
+/// FNV-1a, used instead of `DefaultHasher` because its output is fixed by
+/// specification rather than seeded per process.
+struct ContentHasher(u64);
+
+impl ContentHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Feed the `Debug` form of `value`, which spells out every field.
+    fn debug(&mut self, value: &impl std::fmt::Debug) {
+        use std::fmt::Write;
+        let _ = write!(self, "{value:?};");
+    }
+
+    /// Feed animators sorted by key; map iteration order differs per run.
+    fn animators(&mut self, animators: &HashMap<&'static str, Animator<f32>>) {
+        let mut sorted: Vec<_> = animators.iter().collect();
+        sorted.sort_unstable_by_key(|(key, _)| **key);
+        self.debug(&sorted);
+    }
+
+    fn transform(&mut self, tr: &Transform) {
+        self.debug(&(tr.anchor, tr.position, tr.scale, tr.rotation, tr.opacity));
+        self.debug(&tr.motion);
+        self.animators(&tr.animators);
+    }
+
+    fn shape(&mut self, s: &ShapeLayer) {
+        self.debug(&(
+            "shape",
+            &s.paths,
+            s.fill,
+            &s.fill_animator,
+            &s.fill_paint,
+            &s.fill_control,
+            s.fill_rule,
+            s.stroke,
+            s.stroke_width,
+            s.stroke_style,
+            &s.dash,
+            s.dash_offset,
+        ));
+        self.debug(&s.dash_animators);
+        self.debug(&(&s.mask, &s.mask_expansion, &s.mask_modes, s.has_mask));
+        self.debug(&(&s.mask_inverted, &s.mask_opacity));
+        self.debug(&(
+            s.trim,
+            s.trim_target,
+            s.is_mask,
+            s.matte,
+            s.blend_mode,
+            &s.effects,
+        ));
+        self.debug(&(s.index, s.parent));
+        self.animators(&s.animators);
+        self.transform(&s.transform);
+        self.debug(&(&s.time_remap, s.start_time));
+        for group in &s.blend_groups {
+            self.shape(group);
+        }
+    }
+
+    fn image(&mut self, img: &ImageLayer) {
+        self.debug(&(img.width, img.height));
+        self.bytes(&img.pixels);
+        self.transform(&img.transform);
+        self.debug(&(&img.time_remap, img.start_time));
+    }
+}
+
+impl std::fmt::Write for ContentHasher {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.bytes(s.as_bytes());
+        Ok(())
+    }
+}
+
+fn hash_composition(h: &mut ContentHasher, comp: &Composition) {
+    h.debug(&(comp.width, comp.height, comp.start_frame, comp.end_frame));
+    h.debug(&(comp.fps, comp.pixel_aspect, comp.opacity, comp.layers.len()));
+    let mut controls: Vec<_> = comp.color_controls.iter().collect();
+    controls.sort_by_key(|(name, _)| *name);
+    h.debug(&controls);
+    h.debug(&comp.markers);
+    for layer in &comp.layers {
+        match layer {
+            Layer::Shape(s) => h.shape(s),
+            Layer::Image(img) => {
+                h.debug(&"image");
+                h.image(img);
+            }
+            Layer::ImageSequence(seq) => {
+                h.debug(&("sequence", seq.fps, seq.frames.len()));
+                for frame in &seq.frames {
+                    h.image(frame);
+                }
+            }
+            Layer::PreComp(pre) => {
+                h.debug(&(
+                    "precomp",
+                    pre.collapse_transform,
+                    &pre.time_remap,
+                    pre.start_time,
+                ));
+                h.debug(&(pre.index, pre.parent));
+                h.transform(&pre.transform);
+                hash_composition(h, &pre.comp);
+            }
+            Layer::Text(text) => {
+                h.debug(&("text", &text.text, text.color, text.size, text.position));
+                h.debug(&text.font.file_hash());
+                if let Some(outlines) = &text.outlines {
+                    h.bytes(&outlines.font_data);
+                    h.debug(&(&outlines.fill, outlines.stroke));
+                }
+            }
+            Layer::Null(null) => {
+                h.debug(&("null", null.index, null.parent));
+                h.transform(&null.transform);
+            }
+        }
+    }
+}
+
+fn segment_is_finite(seg: &PathSeg) -> bool {
+    let finite = |v: &Vec2| v.x.is_finite() && v.y.is_finite();
+    match seg {
+        PathSeg::MoveTo(p) | PathSeg::LineTo(p) => finite(p),
+        PathSeg::Cubic(c1, c2, p) => finite(c1) && finite(c2) && finite(p),
+        PathSeg::Arc { center, radii, .. } => finite(center) && finite(radii),
+        PathSeg::Close => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -500,10 +2385,122 @@ mod tests {
         assert!((v.y - v2.y).abs() < 0.0001);
     }
 
+    #[test]
+    fn time_units_convert_through_fps() {
+        assert_eq!(Seconds(1.0).to_frame(30.0), Frame(30));
+        assert_eq!(Seconds(0.5).to_frame_f(24.0), FrameF(12.0));
+        assert_eq!(Frame(15).to_seconds(30.0), Seconds(0.5));
+        assert_eq!(Frame(15).to_seconds(0.0), Seconds(0.0));
+    }
+
+    #[test]
+    fn affine_rotates_about_anchor() {
+        let tr = Transform {
+            anchor: Vec2 { x: 10.0, y: 10.0 },
+            position: Vec2 { x: 10.0, y: 10.0 },
+            rotation: 90.0,
+            ..Transform::default()
+        };
+        let m = Affine::from_transform(&tr);
+        let p = m.transform_point(Vec2 { x: 20.0, y: 10.0 });
+        assert!(
+            (p.x - 10.0).abs() < 1e-4 && (p.y - 20.0).abs() < 1e-4,
+            "{p:?}"
+        );
+        assert_eq!(m.transform_point(tr.anchor), tr.position);
+
+        let moved = Affine::scale_translate(Vec2 { x: 2.0, y: 2.0 }, Vec2 { x: 1.0, y: 0.0 });
+        let p = (moved * m).transform_point(Vec2 { x: 20.0, y: 10.0 });
+        assert!(
+            (p.x - 21.0).abs() < 1e-4 && (p.y - 40.0).abs() < 1e-4,
+            "{p:?}"
+        );
+    }
+
     #[test]
     fn transform_default_animators() {
         let t = Transform::default();
         assert!(t.animators.is_empty());
         assert_eq!(t.scale, Vec2 { x: 1.0, y: 1.0 });
     }
+
+    #[test]
+    fn validate_reports_all_problems() {
+        let broken = ShapeLayer {
+            paths: vec![Path {
+                segments: vec![
+                    PathSeg::MoveTo(Vec2 { x: 0.0, y: 0.0 }),
+                    PathSeg::LineTo(Vec2 {
+                        x: f32::NAN,
+                        y: 1.0,
+                    }),
+                ],
+            }],
+            fill_paint: Some(Paint::Linear(LinearGradient {
+                start: Vec2::default(),
+                end: Vec2 { x: 4.0, y: 0.0 },
+                stops: Vec::new(),
+                opacity_stops: Vec::new(),
+                space: ColorSpace::Rgb,
+            })),
+            is_mask: true,
+            ..Default::default()
+        };
+        let comp = Composition {
+            width: 0,
+            height: 64,
+            start_frame: 10,
+            end_frame: 5,
+            fps: 30.0,
+            pixel_aspect: 1.0,
+            opacity: 1.0,
+            layers: vec![Layer::Shape(broken)],
+            color_controls: HashMap::new(),
+            markers: Vec::new(),
+        };
+        let errors = comp.validate().unwrap_err();
+        assert!(errors.contains(&ValidationError::Dimensions {
+            width: 0,
+            height: 64
+        }));
+        assert!(errors.contains(&ValidationError::FrameRange { start: 10, end: 5 }));
+        assert!(errors.contains(&ValidationError::NonFiniteCoordinate { layer: 0 }));
+        assert!(errors.contains(&ValidationError::EmptyGradientStops { layer: 0 }));
+        assert!(errors.contains(&ValidationError::DanglingMatte { layer: 0 }));
+    }
+
+    #[test]
+    fn linear_gradient_bands_survive_skew() {
+        let grad = LinearGradient {
+            start: Vec2 { x: 1.0, y: 2.0 },
+            end: Vec2 { x: 5.0, y: 3.0 },
+            stops: Vec::new(),
+            opacity_stops: Vec::new(),
+            space: ColorSpace::Rgb,
+        };
+        let m = Affine {
+            a: 2.0,
+            b: 0.5,
+            c: 1.0,
+            d: 0.75,
+            e: 3.0,
+            f: -1.0,
+        };
+        let t = |g: &LinearGradient, p: Vec2| {
+            let (v, w) = (g.end - g.start, p - g.start);
+            (v.x * w.x + v.y * w.y) / (v.x * v.x + v.y * v.y)
+        };
+        let Paint::Linear(mapped) = Paint::Linear(grad.clone()).transformed(&m) else {
+            unreachable!();
+        };
+        for p in [
+            Vec2 { x: 0.0, y: 0.0 },
+            Vec2 { x: 4.0, y: -2.0 },
+            Vec2 { x: 3.0, y: 7.0 },
+        ] {
+            let expected = t(&grad, p);
+            let got = t(&mapped, m.transform_point(p));
+            assert!((expected - got).abs() < 1e-4, "{expected} vs {got}");
+        }
+    }
 }
@@ -0,0 +1,95 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::{BlendMode, Composition, Layer};
+
+fn load() -> Composition {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/blend_multiply.json");
+    json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn render(comp: &Composition) -> Vec<u8> {
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    buf
+}
+
+fn pixel(buf: &[u8], x: usize, y: usize) -> [u8; 4] {
+    buf[(y * 8 + x) * 4..][..4].try_into().unwrap()
+}
+
+#[test]
+fn multiply_keeps_white_and_black_backdrops() {
+    let comp = load();
+    let Layer::Shape(shape) = &comp.layers[2] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.blend_mode, BlendMode::Multiply);
+    let c = shape.fill.unwrap();
+
+    let buf = render(&comp);
+    // Multiplying by white leaves the layer's own color.
+    assert_eq!(pixel(&buf, 1, 4), [c.r, c.g, c.b, 255]);
+    // Black stays black.
+    assert_eq!(pixel(&buf, 6, 4), [0, 0, 0, 255]);
+}
+
+#[test]
+fn screen_keeps_white_and_lightens_black() {
+    let mut comp = load();
+    let Layer::Shape(shape) = &mut comp.layers[2] else {
+        panic!("expected shape layer");
+    };
+    shape.blend_mode = BlendMode::Screen;
+    let c = shape.fill.unwrap();
+    let buf = render(&comp);
+    // Screening white stays white; black shows the layer's own color.
+    assert_eq!(pixel(&buf, 1, 4), [255, 255, 255, 255]);
+    assert_eq!(pixel(&buf, 6, 4), [c.r, c.g, c.b, 255]);
+}
+
+#[test]
+fn unsupported_blend_code_draws_normally() {
+    let doc = r#"{"fr":30,"ip":0,"op":10,"w":8,"h":8,"layers":[{"ty":4,"bm":7,"shapes":[]}]}"#;
+    let comp = json::from_slice(doc.as_bytes()).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.blend_mode, BlendMode::Normal);
+}
+
+#[test]
+fn screen_group_brightens_the_layers_beneath() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/blend_group.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let Layer::Shape(shape) = &comp.layers[1] else {
+        panic!("expected shape layer");
+    };
+    // Only the screen group blends; the layer and its other group do not.
+    assert_eq!(shape.blend_mode, BlendMode::Normal);
+    assert_eq!(shape.blend_groups.len(), 1);
+    assert_eq!(shape.blend_groups[0].blend_mode, BlendMode::Screen);
+
+    let buf = render(&comp);
+    let backdrop = pixel(&buf, 7, 4);
+    let screened = pixel(&buf, 2, 4);
+    // Screening only lightens: red rises, green and blue keep the backdrop.
+    assert!(screened[0] > backdrop[0], "{screened:?} over {backdrop:?}");
+    assert_eq!(screened[1..], backdrop[1..]);
+    // The normal group covers the backdrop with its own color.
+    assert_eq!(pixel(&buf, 5, 4), [0, 128, 0, 255]);
+}
+
+#[test]
+fn blend_group_takes_the_fill_listed_after_it() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/blend_group_outer_fill.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    // The multiply group has no paint of its own; the magenta fill after it
+    // in the layer's stack applies, the blue one before it does not.
+    let buf = render(&comp);
+    assert_eq!(pixel(&buf, 2, 4), [255, 0, 0, 255]);
+    assert_eq!(pixel(&buf, 6, 4), [255, 255, 0, 255]);
+}
@@ -0,0 +1,23 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn animation_bounds_cover_every_frame() {
+    // A horizontal line from (2, 8) to (14, 8) whose stroke grows 1 → 8.
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/stroke_width_anim.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let (min0, max0) = comp.content_bounds(0, 0.1).unwrap();
+    assert_eq!((min0.y, max0.y), (7.5, 8.5));
+
+    let (min, max) = comp.animation_bounds(0.1);
+    assert_eq!((min.x, max.x), (-2.0, 18.0));
+    assert_eq!((min.y, max.y), (4.0, 12.0));
+    for frame in 0..=10 {
+        let (lo, hi) = comp.content_bounds(frame, 0.1).unwrap();
+        assert!(lo.x >= min.x && lo.y >= min.y && hi.x <= max.x && hi.y <= max.y);
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Color;
+
+#[test]
+fn color_control_drives_linked_fill() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/color_control.json");
+    let mut comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let center = |comp: &rlottie_core::types::Composition| {
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        comp.render_sync(0, &mut buf, 16, 16, 16 * 4);
+        buf[(8 * 16 + 8) * 4..(8 * 16 + 8) * 4 + 4].to_vec()
+    };
+    // The fill's own red is replaced by the control's green on load.
+    assert_eq!(center(&comp), [0, 255, 0, 255]);
+
+    let blue = Color {
+        r: 0,
+        g: 0,
+        b: 255,
+        a: 255,
+    };
+    assert!(comp.set_effect_color("Fill Color", blue));
+    assert_eq!(center(&comp), [0, 0, 255, 255]);
+    assert!(!comp.set_effect_color("Stroke Color", blue));
+}
@@ -0,0 +1,15 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn leading_empty_frames_reported() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/late_content.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let frames = comp.content_frames(16, 16);
+    assert_eq!(frames.len(), 20);
+    assert!(frames[..10].iter().all(|&f| !f), "{frames:?}");
+    assert!(frames[10..].iter().all(|&f| f), "{frames:?}");
+}
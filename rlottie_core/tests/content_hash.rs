@@ -0,0 +1,23 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Layer;
+
+#[test]
+fn content_hash_tracks_rendered_content() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/star.json");
+    let data = std::fs::read(path).unwrap();
+    let a = json::from_slice(&data).unwrap();
+    let b = json::from_slice(&data).unwrap();
+    assert_eq!(a.content_hash(), b.content_hash());
+
+    let mut recolored = b.clone();
+    if let Layer::Shape(shape) = &mut recolored.layers[0] {
+        shape.fill.as_mut().unwrap().g = 0;
+    }
+    assert_ne!(a.content_hash(), recolored.content_hash());
+
+    let mut shortened = b;
+    shortened.end_frame -= 1;
+    assert_ne!(a.content_hash(), shortened.content_hash());
+}
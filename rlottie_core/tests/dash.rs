@@ -0,0 +1,38 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn dashed_line_alternates_runs() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/dash.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let mut buf = vec![0u8; 32 * 8 * 4];
+    comp.render_sync(0, &mut buf, 32, 8, 32 * 4);
+    // The line has a vertex at x = 10, inside the second dash, so the dash
+    // walk has to carry across segments to keep the 4 px rhythm.
+    let row: Vec<u8> = (0..32).map(|x| buf[(3 * 32 + x) * 4 + 3]).collect();
+    for (x, &alpha) in row.iter().enumerate() {
+        let on = (x / 4) % 2 == 0;
+        assert_eq!(alpha > 0, on, "pixel {x} in {row:?}");
+    }
+}
+
+#[test]
+fn animated_dash_pattern_follows_its_keyframes() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/dash_animated.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let mut buf = vec![0u8; 32 * 8 * 4];
+    // The gap grows from 4 to 12 and the offset from 0 to 8 over frames
+    // 0..8, so frame 4 shows 4 px dashes, 8 px gaps, shifted by 4 px.
+    for (frame, gap, offset) in [(0, 4, 0), (4, 8, 4)] {
+        comp.render_sync(frame, &mut buf, 32, 8, 32 * 4);
+        let row: Vec<u8> = (0..32).map(|x| buf[(3 * 32 + x) * 4 + 3]).collect();
+        for (x, &alpha) in row.iter().enumerate() {
+            let on = (x + offset) % (4 + gap) < 4;
+            assert_eq!(alpha > 0, on, "frame {frame}, pixel {x} in {row:?}");
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::{Layer, LayerEffect};
+
+#[test]
+fn effects_apply_in_authored_order() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/effects.json");
+    let data = std::fs::read(path).unwrap();
+    let mut comp = json::from_slice(&data).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    // The disabled blur is dropped; the rest keep their "ef" order.
+    assert!(matches!(
+        shape.effects.as_slice(),
+        [LayerEffect::Tint { .. }, LayerEffect::DropShadow { .. }]
+    ));
+
+    let shadow_px = |comp: &rlottie_core::types::Composition| {
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        comp.render_sync(0, &mut buf, 16, 16, 16 * 4);
+        // (10, 6) is only covered by the shadow, offset 4 to the right.
+        let off = (6 * 16 + 10) * 4;
+        buf[off..off + 4].to_vec()
+    };
+
+    // Tint first: the shadow is cast afterwards and stays black.
+    assert_eq!(shadow_px(&comp), [0, 0, 0, 255]);
+
+    // Shadow first: the tint then maps the black shadow to blue.
+    if let Layer::Shape(shape) = &mut comp.layers[0] {
+        shape.effects.reverse();
+    }
+    assert_eq!(shadow_px(&comp), [0, 0, 255, 255]);
+}
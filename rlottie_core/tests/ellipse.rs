@@ -0,0 +1,20 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn ellipse_fills_inside_its_bounds_only() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/ellipse.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut buf = vec![0u8; 32 * 32 * 4];
+    comp.render_sync(0, &mut buf, 32, 32, 32 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+    // 20×12 ellipse around (16, 16): spans x 6..26 and y 10..22.
+    assert_eq!(px(16, 16), &[255, 0, 0, 255]);
+    assert_eq!(px(16, 8), &[0, 0, 0, 0]);
+    assert_eq!(px(3, 16), &[0, 0, 0, 0]);
+    // The bounding box corner lies outside the curve.
+    assert_eq!(px(7, 11), &[0, 0, 0, 0]);
+}
@@ -0,0 +1,42 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Layer;
+
+#[test]
+fn animated_fill_blends_red_to_blue() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_color_anim.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert!(shape.fill_animator.is_some());
+
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    let mut color_at = |frame: u32| {
+        comp.render_sync(frame, &mut buf, 8, 8, 8 * 4);
+        <[u8; 4]>::try_from(&buf[(4 * 8 + 4) * 4..][..4]).unwrap()
+    };
+    assert_eq!(color_at(0), [255, 0, 0, 255]);
+    assert_eq!(color_at(15), [128, 0, 128, 255]);
+    assert_eq!(color_at(30), [0, 0, 255, 255]);
+}
+
+#[test]
+fn fill_opacity_fades_solid_fills() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_opacity.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    // A red fill at a static 50% on the left, a blue one fading in over
+    // ten frames on the right.
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    let mut colors_at = |frame: u32| {
+        comp.render_sync(frame, &mut buf, 8, 8, 8 * 4);
+        let px = |x: usize| <[u8; 4]>::try_from(&buf[(4 * 8 + x) * 4..][..4]).unwrap();
+        (px(2), px(6))
+    };
+    assert_eq!(colors_at(0), ([128, 0, 0, 128], [0, 0, 0, 0]));
+    assert_eq!(colors_at(5).1, [0, 0, 128, 128]);
+    assert_eq!(colors_at(10).1, [0, 0, 255, 255]);
+}
@@ -1,6 +1,7 @@
 // Copyright © SoftOboros Technology, Inc.
 // SPDX-License-Identifier: MIT
 use rlottie_core::loader::json;
+use rlottie_core::types::FillRule;
 
 #[test]
 fn render_fill_and_stroke() {
@@ -14,6 +15,147 @@ fn render_fill_and_stroke() {
     let inside = 4 * 8 * 4 + 4 * 4;
     assert_eq!(&buf[inside..inside + 4], &[0, 0, 255, 255]);
     // border pixel should be red stroke
-    let border = 1 * 8 * 4 + 1 * 4;
+    let border = 8 * 4 + 4;
     assert_eq!(&buf[border..border + 4], &[255, 0, 0, 255]);
 }
+
+#[test]
+fn parse_fill_stroke_around_unknown_items() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/shape_items.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let rlottie_core::types::Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    let fill = shape.fill.expect("fill");
+    assert_eq!((fill.r, fill.g, fill.b, fill.a), (0, 0, 255, 255));
+    let stroke = shape.stroke.expect("stroke from group");
+    assert_eq!((stroke.r, stroke.g, stroke.b, stroke.a), (255, 0, 0, 255));
+    assert_eq!(shape.stroke_width, 2.0);
+    assert_eq!(shape.paths.len(), 2);
+}
+
+#[test]
+fn compound_fill_even_odd_leaves_hole() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_rule.json");
+    let data = std::fs::read(path).unwrap();
+    let mut comp = json::from_slice(&data).unwrap();
+    let rlottie_core::types::Layer::Shape(shape) = &mut comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.fill_rule, FillRule::EvenOdd);
+
+    let mut buf = vec![0u8; 10 * 10 * 4];
+    comp.render_sync(0, &mut buf, 10, 10, 10 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 10 + x) * 4..(y * 10 + x) * 4 + 4];
+    // inner square is a hole
+    assert_eq!(px(5, 5), &[0, 0, 0, 0]);
+    // ring between the squares is filled exactly once
+    assert_eq!(px(2, 5), px(5, 1));
+    assert_eq!(px(2, 5)[3], 128);
+
+    // under non-zero the same-direction inner square is filled, still once
+    if let rlottie_core::types::Layer::Shape(shape) = &mut comp.layers[0] {
+        shape.fill_rule = FillRule::NonZero;
+    }
+    comp.render_sync(0, &mut buf, 10, 10, 10 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 10 + x) * 4..(y * 10 + x) * 4 + 4];
+    assert_eq!(px(5, 5), px(2, 5));
+}
+
+#[test]
+fn donut_hole_stays_empty_under_even_odd() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/donut.json");
+    let data = std::fs::read(path).unwrap();
+    let mut comp = json::from_slice(&data).unwrap();
+    let rlottie_core::types::Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.fill_rule, FillRule::EvenOdd);
+    assert_eq!(shape.paths.len(), 2);
+
+    let mut buf = vec![0u8; 20 * 20 * 4];
+    comp.render_sync(0, &mut buf, 20, 20, 20 * 4);
+    let alpha = |buf: &[u8], x: usize, y: usize| buf[(y * 20 + x) * 4 + 3];
+    assert_eq!(alpha(&buf, 10, 10), 0);
+    assert_eq!(alpha(&buf, 4, 10), 255);
+    assert_eq!(alpha(&buf, 10, 15), 255);
+    assert_eq!(alpha(&buf, 0, 0), 0);
+
+    // Both circles run the same way, so non-zero fills the hole.
+    if let rlottie_core::types::Layer::Shape(shape) = &mut comp.layers[0] {
+        shape.fill_rule = FillRule::NonZero;
+    }
+    comp.render_sync(0, &mut buf, 20, 20, 20 * 4);
+    assert_eq!(alpha(&buf, 10, 10), 255);
+}
+
+#[test]
+fn invisible_stroke_is_skipped() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let with_shape = |f: &dyn Fn(&mut rlottie_core::types::ShapeLayer)| {
+        let mut c = comp.clone();
+        if let rlottie_core::types::Layer::Shape(shape) = &mut c.layers[0] {
+            f(shape);
+        }
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        c.render_sync(0, &mut buf, 8, 8, 8 * 4);
+        buf
+    };
+    let fill_only = with_shape(&|s| s.stroke = None);
+    let zero_width = with_shape(&|s| s.stroke_width = 0.0);
+    let clear_stroke = with_shape(&|s| s.stroke.as_mut().unwrap().a = 0);
+    assert_eq!(zero_width, fill_only);
+    assert_eq!(clear_stroke, fill_only);
+    // the border pixel shows the fill instead of the red stroke
+    let border = 8 * 4 + 4;
+    assert_eq!(&zero_width[border..border + 4], &[0, 0, 255, 255]);
+}
+
+#[test]
+fn loaded_paths_render_like_hand_built_ones() {
+    use rlottie_core::geometry::Path;
+    use rlottie_core::types::{Layer, Vec2};
+
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+    let data = std::fs::read(path).unwrap();
+    let loaded = json::from_slice(&data).unwrap();
+
+    let mut square = Path::new();
+    square.move_to(Vec2 { x: 1.0, y: 1.0 });
+    square.line_to(Vec2 { x: 7.0, y: 1.0 });
+    square.line_to(Vec2 { x: 7.0, y: 7.0 });
+    square.line_to(Vec2 { x: 1.0, y: 7.0 });
+    square.close();
+    let mut built = loaded.clone();
+    let Layer::Shape(shape) = &mut built.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.paths[0].segments, square.segments);
+    shape.paths = vec![square];
+
+    let mut a = vec![0u8; 8 * 8 * 4];
+    let mut b = vec![0u8; 8 * 8 * 4];
+    loaded.render_sync(0, &mut a, 8, 8, 8 * 4);
+    built.render_sync(0, &mut b, 8, 8, 8 * 4);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn frame_count_and_duration() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+    let mut comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    // ip 0 to op 10, both played, at 30 fps.
+    assert_eq!(comp.frame_count(), 11);
+    assert!((comp.duration_secs() - 11.0 / 30.0).abs() < 1e-6);
+
+    comp.fps = 0.0;
+    assert_eq!(comp.duration_secs(), 0.0);
+}
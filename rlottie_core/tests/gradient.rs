@@ -1,6 +1,7 @@
 use rlottie_core::geometry::Path;
+use rlottie_core::loader::json;
 use rlottie_core::renderer::cpu::draw_path;
-use rlottie_core::types::{Color, GradientStop, LinearGradient, Paint, Vec2};
+use rlottie_core::types::{Color, ColorSpace, GradientStop, LinearGradient, Paint, Vec2};
 
 #[test]
 fn linear_gradient_rect() {
@@ -33,6 +34,8 @@ fn linear_gradient_rect() {
                 },
             },
         ],
+        opacity_stops: Vec::new(),
+        space: ColorSpace::Rgb,
     };
     let mut buf = vec![0u8; 8 * 8 * 4];
     draw_path(&path, Paint::Linear(grad), &mut buf, 8, 8, 8 * 4);
@@ -41,3 +44,121 @@ fn linear_gradient_rect() {
     assert!(buf[left] > buf[right]);
     assert!(buf[right + 2] > buf[left + 2]);
 }
+
+/// Render a red→green ramp across 8 pixels and return the pixel at `t = 0.5`.
+fn red_green_midpoint(space: ColorSpace) -> [u8; 4] {
+    let mut path = Path::new();
+    path.move_to(Vec2 { x: 0.0, y: 0.0 });
+    path.line_to(Vec2 { x: 8.0, y: 0.0 });
+    path.line_to(Vec2 { x: 8.0, y: 1.0 });
+    path.line_to(Vec2 { x: 0.0, y: 1.0 });
+    path.close();
+    let stop = |offset, r, g| GradientStop {
+        offset,
+        color: Color { r, g, b: 0, a: 255 },
+    };
+    let grad = LinearGradient {
+        start: Vec2 { x: 0.0, y: 0.0 },
+        end: Vec2 { x: 7.0, y: 0.0 },
+        stops: vec![stop(0.0, 255, 0), stop(1.0, 0, 255)],
+        opacity_stops: Vec::new(),
+        space,
+    };
+    let mut buf = vec![0u8; 8 * 4];
+    draw_path(&path, Paint::Linear(grad), &mut buf, 8, 1, 8 * 4);
+    // pixel 3 is centered at x = 3.5, halfway along the ramp
+    buf[12..16].try_into().unwrap()
+}
+
+#[test]
+fn oklab_gradient_midpoint_is_more_saturated() {
+    // Chroma as the spread between the strongest and weakest channel.
+    let chroma = |px: [u8; 4]| px[..3].iter().max().unwrap() - px[..3].iter().min().unwrap();
+    let rgb = red_green_midpoint(ColorSpace::Rgb);
+    let oklab = red_green_midpoint(ColorSpace::OkLab);
+    let hsl = red_green_midpoint(ColorSpace::Hsl);
+    assert_eq!(&rgb[..3], &[128, 128, 0]);
+    assert!(chroma(oklab) > chroma(rgb), "{oklab:?} vs {rgb:?}");
+    assert_eq!(&hsl[..3], &[255, 255, 0]);
+}
+
+#[test]
+fn gradient_fill_item_ramps_across_rect() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/gradient_fill.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let mut buf = vec![0u8; 64 * 8 * 4];
+    comp.render_sync(0, &mut buf, 64, 8, 64 * 4);
+    let row: Vec<&[u8]> = buf[4 * 64 * 4..5 * 64 * 4].chunks(4).collect();
+    assert!(row[0][0] > 250 && row[0][2] < 5, "{:?}", row[0]);
+    assert!(row[63][2] > 250 && row[63][0] < 5, "{:?}", row[63]);
+    for pair in row.windows(2) {
+        assert!(pair[1][0] <= pair[0][0] && pair[1][2] >= pair[0][2]);
+        assert_eq!(pair[1][3], 255);
+    }
+}
+
+#[test]
+fn opacity_stops_fade_independently_of_color() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/gradient_fade.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let mut buf = vec![0u8; 64 * 8 * 4];
+    comp.render_sync(0, &mut buf, 64, 8, 64 * 4);
+    let alpha: Vec<u8> = (0..64).map(|x| buf[(4 * 64 + x) * 4 + 3]).collect();
+    // Opaque up to the first opacity stop at a quarter, then fading out.
+    assert!(alpha[..15].iter().all(|&a| a == 255), "{alpha:?}");
+    assert!(alpha[63] < 8, "{alpha:?}");
+    assert!(alpha[16..].windows(2).all(|w| w[1] <= w[0]), "{alpha:?}");
+}
+
+#[test]
+fn radial_highlight_moves_bright_spot_to_focus() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/gradient_focus.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let mut buf = vec![0u8; 32 * 32 * 4];
+    comp.render_sync(0, &mut buf, 32, 32, 32 * 4);
+    let red = |x: usize, y: usize| buf[(y * 32 + x) * 4];
+    // Half the radius along the start-end line puts the focus at 24,16.
+    let brightest = (0..32).max_by_key(|&x| red(x, 16)).unwrap();
+    assert!((23..=24).contains(&brightest), "brightest at {brightest}");
+    assert!(red(24, 16) > red(16, 16));
+    // The outer circle is still black on both sides.
+    assert!(red(0, 16) < 8 && red(31, 16) < 24);
+}
+
+#[test]
+fn gradient_rotates_with_its_shape() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/gradient_rotated.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let mut buf = vec![0u8; 32 * 32 * 4];
+    comp.render_sync(0, &mut buf, 32, 32, 32 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+    // A quarter turn makes the left-to-right ramp run top to bottom.
+    assert!(px(16, 0)[0] > 240 && px(16, 31)[2] > 240);
+    for y in 0..32 {
+        assert_eq!(px(0, y), px(31, y), "row {y} should be flat");
+    }
+    for y in 1..32 {
+        assert!(px(16, y)[2] >= px(16, y - 1)[2]);
+    }
+}
+
+#[test]
+fn gradient_scales_with_its_group() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/gradient_group.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let mut buf = vec![0u8; 64 * 8 * 4];
+    comp.render_sync(0, &mut buf, 64, 8, 64 * 4);
+    let row: Vec<&[u8]> = buf[4 * 64 * 4..5 * 64 * 4].chunks(4).collect();
+    // The group doubles a 32 px rect and its ramp, so the ramp still spans
+    // the whole stretched rect instead of ending halfway.
+    assert!(row[0][0] > 250 && row[63][2] > 250);
+    assert!((100..156).contains(&row[32][0]), "{:?}", row[32]);
+    for pair in row.windows(2) {
+        assert!(pair[1][0] <= pair[0][0] && pair[1][2] >= pair[0][2]);
+    }
+}
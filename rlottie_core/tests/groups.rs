@@ -0,0 +1,37 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn nested_group_transforms_compose() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/nested_groups.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut buf = vec![0u8; 16 * 16 * 4];
+    comp.render_sync(0, &mut buf, 16, 16, 16 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 16 + x) * 4..(y * 16 + x) * 4 + 4];
+    // Unit square scaled ×2, moved by (1, 1), then by (8, 8): covers 9..11.
+    assert_eq!(px(9, 9), &[255, 0, 0, 255]);
+    assert_eq!(px(10, 10), &[255, 0, 0, 255]);
+    assert_eq!(px(0, 0), &[0, 0, 0, 0]);
+    assert_eq!(px(11, 11), &[0, 0, 0, 0]);
+}
+
+#[test]
+fn group_translation_and_opacity_reach_children() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/group_offset.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut buf = vec![0u8; 32 * 16 * 4];
+    comp.render_sync(0, &mut buf, 32, 16, 32 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+    // 8×8 rectangle at x 2..10, moved right by 10 and drawn at 50% opacity.
+    assert_eq!(px(4, 8), &[0, 0, 0, 0]);
+    assert_eq!(px(12, 8), &[128, 0, 0, 128]);
+    assert_eq!(px(19, 8), &[128, 0, 0, 128]);
+    assert_eq!(px(21, 8), &[0, 0, 0, 0]);
+}
@@ -0,0 +1,141 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::renderer::Renderer;
+use rlottie_core::types::{Layer, MaskMode};
+
+#[test]
+fn disabled_mask_is_skipped() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/has_mask.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert!(!shape.has_mask);
+    assert!(shape.mask.is_some());
+
+    let renderer = Renderer::new();
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    renderer.render(&comp, 0, &mut buf, 8, 8, 8 * 4).unwrap();
+    assert_eq!(renderer.stats().mask_buffers, 0);
+
+    let mut unmasked = comp.clone();
+    if let Layer::Shape(shape) = &mut unmasked.layers[0] {
+        shape.mask = None;
+    }
+    let mut expected = vec![0u8; 8 * 8 * 4];
+    unmasked.render_sync(0, &mut expected, 8, 8, 8 * 4);
+    assert_eq!(buf, expected);
+
+    let mut enabled = comp.clone();
+    if let Layer::Shape(shape) = &mut enabled.layers[0] {
+        shape.has_mask = true;
+    }
+    renderer.render(&enabled, 0, &mut buf, 8, 8, 8 * 4).unwrap();
+    assert_eq!(renderer.stats().mask_buffers, 1);
+    assert_ne!(buf, expected);
+}
+
+#[test]
+fn positive_expansion_grows_mask() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/mask_expansion.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.mask_expansion, vec![2.0]);
+
+    let alpha = |buf: &[u8], x: usize, y: usize| buf[(y * 10 + x) * 4 + 3];
+    let mut buf = vec![0u8; 10 * 10 * 4];
+    comp.render_sync(0, &mut buf, 10, 10, 10 * 4);
+    // original mask covers 4..6; expanded by 2 it reaches 2..8
+    assert_eq!(alpha(&buf, 5, 5), 255);
+    assert_eq!(alpha(&buf, 2, 5), 255);
+    assert_eq!(alpha(&buf, 7, 7), 255);
+    assert_eq!(alpha(&buf, 1, 5), 0);
+
+    let mut plain = comp.clone();
+    if let Layer::Shape(shape) = &mut plain.layers[0] {
+        shape.mask_expansion = vec![0.0];
+    }
+    plain.render_sync(0, &mut buf, 10, 10, 10 * 4);
+    assert_eq!(alpha(&buf, 5, 5), 255);
+    assert_eq!(alpha(&buf, 2, 5), 0);
+}
+
+#[test]
+fn inset_past_the_mask_size_hides_everything() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/mask_expansion.json");
+    let mut comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    // The 2 px mask shrunk by 3 px on every side has no inside left,
+    // rather than turning into an inverted square.
+    let Layer::Shape(shape) = &mut comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    shape.mask_expansion = vec![-3.0];
+    assert!(shape.mask.as_ref().unwrap()[0].offset(-3.0).is_empty());
+    let mut buf = vec![0u8; 10 * 10 * 4];
+    comp.render_sync(0, &mut buf, 10, 10, 10 * 4);
+    assert!(buf.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn intersect_mask_clips_fill_and_stroke_alike() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/mask_intersect.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.mask_modes, vec![MaskMode::Add, MaskMode::Intersect]);
+
+    let mut masked = vec![0u8; 16 * 16 * 4];
+    comp.render_sync(0, &mut masked, 16, 16, 16 * 4);
+    let mut unmasked = comp.clone();
+    if let Layer::Shape(shape) = &mut unmasked.layers[0] {
+        shape.has_mask = false;
+    }
+    let mut full = vec![0u8; 16 * 16 * 4];
+    unmasked.render_sync(0, &mut full, 16, 16, 16 * 4);
+
+    // The left half intersected with the top half leaves the top-left quarter.
+    for y in 0..16 {
+        for x in 0..16 {
+            let o = (y * 16 + x) * 4;
+            let expected = if x < 8 && y < 8 {
+                &full[o..o + 4]
+            } else {
+                &[0; 4]
+            };
+            assert_eq!(&masked[o..o + 4], expected, "pixel {x},{y}");
+        }
+    }
+    // Both the stroke and the fill survive inside the mask.
+    assert_eq!(&masked[(5 * 16 + 2) * 4..][..4], &[255, 0, 0, 255]);
+    assert_eq!(&masked[(5 * 16 + 5) * 4..][..4], &[0, 0, 255, 255]);
+}
+
+#[test]
+fn inverted_mask_with_opacity_fades_the_outside() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/mask_inverted.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.mask_inverted, vec![true]);
+    assert_eq!(shape.mask_opacity, vec![0.5]);
+
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+    assert_eq!(px(4, 4), &[0, 0, 0, 0]);
+    assert_eq!(px(0, 0), &[0, 128, 0, 128]);
+    assert_eq!(px(7, 4), &[0, 128, 0, 128]);
+}
@@ -0,0 +1,29 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Layer;
+
+#[test]
+fn image_layer_draws_at_its_position() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/image_positioned.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let Layer::Image(img) = &comp.layers[0] else {
+        panic!("expected image layer");
+    };
+    let texel = img.pixels.clone();
+    assert_eq!(texel[3], 255);
+
+    let mut buf = vec![0u8; 4 * 4 * 4];
+    comp.render_sync(0, &mut buf, 4, 4, 4 * 4);
+    for (i, px) in buf.chunks(4).enumerate() {
+        let expected: &[u8] = if i == 4 + 2 { &texel } else { &[0; 4] };
+        assert_eq!(px, expected, "pixel {}", i);
+    }
+
+    // At twice the size the texel covers a 2x2 block at (4, 2).
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    let covered: Vec<_> = (0..64).filter(|i| buf[i * 4 + 3] != 0).collect();
+    assert_eq!(covered, vec![2 * 8 + 4, 2 * 8 + 5, 3 * 8 + 4, 3 * 8 + 5]);
+}
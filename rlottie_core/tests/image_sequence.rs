@@ -0,0 +1,23 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Layer;
+
+#[test]
+fn sequence_swaps_image_at_midpoint() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/image_sequence.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let Layer::ImageSequence(seq) = &comp.layers[0] else {
+        panic!("expected image sequence layer");
+    };
+    assert_eq!(seq.frames.len(), 2);
+
+    let mut before = vec![0u8; 4 * 4 * 4];
+    comp.render_sync(4, &mut before, 4, 4, 4 * 4);
+    let mut after = vec![0u8; 4 * 4 * 4];
+    comp.render_sync(5, &mut after, 4, 4, 4 * 4);
+    assert_eq!(&before[0..4], &[255, 0, 0, 255]);
+    assert_eq!(&after[0..4], &[0, 0, 255, 255]);
+}
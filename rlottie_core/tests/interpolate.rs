@@ -0,0 +1,29 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn interpolated_frames_cross_fade() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/stroke_width_anim.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut a = vec![0u8; 16 * 16 * 4];
+    let mut b = vec![0u8; 16 * 16 * 4];
+    comp.render_sync(0, &mut a, 16, 16, 16 * 4);
+    comp.render_sync(10, &mut b, 16, 16, 16 * 4);
+
+    let mut buf = vec![0u8; 16 * 16 * 4];
+    comp.render_interpolated(0, 10, 0.0, &mut buf, 16, 16, 16 * 4);
+    assert_eq!(buf, a);
+    comp.render_interpolated(0, 10, 1.0, &mut buf, 16, 16, 16 * 4);
+    assert_eq!(buf, b);
+
+    // (8, 5) is only covered by the thick stroke of frame 10.
+    let off = (5 * 16 + 8) * 4;
+    assert_eq!(&a[off..off + 4], &[0, 0, 0, 0]);
+    assert_eq!(&b[off..off + 4], &[255, 0, 0, 255]);
+    comp.render_interpolated(0, 10, 0.5, &mut buf, 16, 16, 16 * 4);
+    assert_eq!(&buf[off..off + 4], &[128, 0, 0, 128]);
+}
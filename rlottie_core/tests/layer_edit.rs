@@ -0,0 +1,88 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::{Composition, Layer, RemoveLayerError};
+
+fn load(name: &str) -> Composition {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data")
+        .join(name);
+    json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn removed_layer_is_not_rendered() {
+    let mut comp = load("nested_groups.json");
+    let mut buf = vec![0u8; 16 * 16 * 4];
+    comp.render_sync(0, &mut buf, 16, 16, 16 * 4);
+    assert!(buf.chunks(4).any(|px| px[3] != 0));
+
+    assert!(matches!(
+        comp.remove_layer(0).as_deref(),
+        Ok([Layer::Shape(_)])
+    ));
+    assert_eq!(
+        comp.remove_layer(0).err(),
+        Some(RemoveLayerError::OutOfRange { index: 0, len: 0 })
+    );
+    comp.render_sync(0, &mut buf, 16, 16, 16 * 4);
+    assert!(buf.chunks(4).all(|px| px[3] == 0));
+}
+
+#[test]
+fn removing_matte_source_clears_dependent_matte() {
+    let mut comp = load("matte_export.json");
+    comp.remove_layer(0).unwrap();
+    let Layer::Shape(red) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(red.matte, None);
+    assert!(comp.validate().is_ok());
+
+    // Without its matte the red layer fills its whole half.
+    let mut buf = vec![0u8; 20 * 10 * 4];
+    comp.render_sync(0, &mut buf, 20, 10, 20 * 4);
+    assert_eq!(&buf[..4], &[255, 0, 0, 255]);
+}
+
+#[test]
+fn matte_pairs_stay_together() {
+    let mut comp = load("matte_export.json");
+    // Removing the inverted-matte layer drops its source as well.
+    let removed = comp.remove_layer(3).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert!(matches!(&removed[0], Layer::Shape(s) if s.is_mask));
+    assert!(matches!(&removed[1], Layer::Shape(s) if s.matte.is_some()));
+    assert_eq!(comp.layers.len(), 2);
+    assert!(comp.validate().is_ok());
+
+    // Inserting between the remaining pair lands above the source instead.
+    let extra = comp.layers[1].clone();
+    let Layer::Shape(mut extra) = extra else {
+        panic!("expected shape layer");
+    };
+    extra.matte = None;
+    assert_eq!(comp.insert_layer(1, Layer::Shape(extra)), 0);
+    assert!(comp.validate().is_ok());
+}
+
+#[test]
+fn parent_with_children_is_kept() {
+    let mut comp = load("null_parent.json");
+    assert_eq!(
+        comp.remove_layer(0).err(),
+        Some(RemoveLayerError::HasChildren { layer: 0 })
+    );
+    assert!(matches!(comp.layers[0], Layer::Null(_)));
+
+    // Once the child is gone the parent can go too.
+    assert!(matches!(
+        comp.remove_layer(1).as_deref(),
+        Ok([Layer::Shape(_)])
+    ));
+    assert!(matches!(
+        comp.remove_layer(0).as_deref(),
+        Ok([Layer::Null(_)])
+    ));
+    assert!(comp.layers.is_empty());
+}
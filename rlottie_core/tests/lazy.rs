@@ -0,0 +1,46 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json::{self, LazyComposition};
+
+#[test]
+fn lazy_layers_parse_on_demand() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/matte_export.json");
+    let data = std::fs::read(path).unwrap();
+
+    let mut lazy = LazyComposition::from_slice(&data).unwrap();
+    assert_eq!((lazy.width, lazy.height), (20, 10));
+    assert_eq!((lazy.start_frame, lazy.end_frame), (0, 10));
+    assert_eq!(lazy.fps, 30.0);
+    assert_eq!(lazy.layer_count(), 4);
+    assert_eq!(lazy.parsed_count(), 0);
+
+    assert!(lazy.layer(2).is_some());
+    assert_eq!(lazy.parsed_count(), 1);
+    assert!(lazy.layer(4).is_none());
+
+    let eager = json::from_slice(&data).unwrap();
+    let mut expected = vec![0u8; 20 * 10 * 4];
+    eager.render_sync(0, &mut expected, 20, 10, 20 * 4);
+    let mut buf = vec![0u8; 20 * 10 * 4];
+    lazy.render_sync(0, &mut buf, 20, 10, 20 * 4);
+    assert_eq!(buf, expected);
+    assert_eq!(lazy.parsed_count(), 4);
+    assert!(lazy.layer(2).is_some());
+}
+
+#[test]
+fn lazy_images_decode_with_their_layer() {
+    let data = br#"{"fr":30,"ip":0,"op":10,"w":4,"h":4,
+        "assets":[{"id":"img","w":1,"h":1,"u":"missing/","p":"img.png"}],
+        "layers":[
+            {"ty":3,"ks":{}},
+            {"ty":2,"refId":"img","ks":{}}
+        ]}"#;
+    assert!(json::from_slice(data).is_err());
+
+    let lazy = LazyComposition::from_slice(data).unwrap();
+    assert!(lazy.layer(0).is_some());
+    assert!(lazy.layer(1).is_none());
+    assert_eq!(lazy.parsed_count(), 2);
+}
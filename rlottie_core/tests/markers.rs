@@ -0,0 +1,51 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Marker;
+
+fn load() -> rlottie_core::types::Composition {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/segments.json");
+    json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn parse_markers() {
+    let comp = load();
+    assert_eq!(comp.markers.len(), 2);
+    assert_eq!(
+        comp.marker("loading"),
+        Some(&Marker {
+            name: "loading".into(),
+            start: 40,
+            duration: 10,
+        })
+    );
+    assert!(comp.marker("outro").is_none());
+}
+
+#[test]
+fn segment_frames_count_from_the_marker() {
+    let comp = load();
+    let render = |frame: u32| {
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        comp.render_sync(frame, &mut buf, 8, 8, 8 * 4);
+        buf
+    };
+    let segment = |local: u32| {
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        assert!(comp.render_segment("loading", local, &mut buf, 8, 8, 8 * 4));
+        buf
+    };
+    // Frames passed to render_sync count from the composition's `ip` (10),
+    // so the segment starting at frame 40 begins at 30.
+    assert_eq!(segment(0), render(30));
+    assert_eq!(segment(4), render(34));
+    assert_ne!(segment(0), segment(4));
+    // Past its end the segment holds its last frame, 40 + 10 - 1 absolute.
+    assert_eq!(segment(9), render(39));
+    assert_eq!(segment(25), render(39));
+
+    let mut buf = vec![7u8; 8 * 8 * 4];
+    assert!(!comp.render_segment("outro", 0, &mut buf, 8, 8, 8 * 4));
+    assert!(buf.iter().all(|&b| b == 7));
+}
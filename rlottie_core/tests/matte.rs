@@ -0,0 +1,25 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn exporter_matte_order_mattes_layer_below() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/matte_export.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    assert!(comp.validate().is_ok());
+
+    let mut buf = vec![0u8; 20 * 10 * 4];
+    comp.render_sync(0, &mut buf, 20, 10, 20 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 20 + x) * 4..(y * 20 + x) * 4 + 4];
+
+    // Alpha matte: red survives only inside the 2..8 square.
+    assert_eq!(px(5, 5), &[255, 0, 0, 255]);
+    assert_eq!(px(0, 0), &[0, 0, 0, 0]);
+    assert_eq!(px(9, 9), &[0, 0, 0, 0]);
+    // Inverted matte: blue survives only outside the 12..18 square.
+    assert_eq!(px(15, 5), &[0, 0, 0, 0]);
+    assert_eq!(px(10, 0), &[0, 0, 255, 255]);
+    assert_eq!(px(19, 9), &[0, 0, 255, 255]);
+}
@@ -0,0 +1,41 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::Layer;
+
+#[test]
+fn shape_follows_animated_null_parent() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/null_parent.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    assert_eq!(comp.layers.len(), 2);
+    let Layer::Null(null) = &comp.layers[0] else {
+        panic!("expected null layer");
+    };
+    assert_eq!(null.index, Some(1));
+    let Layer::Shape(shape) = &comp.layers[1] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.parent, Some(1));
+
+    let alpha = |buf: &[u8], x: usize, y: usize| buf[(y * 32 + x) * 4 + 3];
+    let mut buf = vec![0u8; 32 * 32 * 4];
+    // Frame 0: the null sits at the origin, so the square covers 2..6.
+    comp.render_sync(0, &mut buf, 32, 32, 32 * 4);
+    assert_eq!(alpha(&buf, 3, 3), 255);
+    assert_eq!(alpha(&buf, 23, 13), 0);
+    assert_eq!(buf.chunks(4).filter(|px| px[3] != 0).count(), 16);
+
+    // Frame 10: the null moved by (20, 10) and the square with it.
+    comp.render_sync(10, &mut buf, 32, 32, 32 * 4);
+    assert_eq!(alpha(&buf, 3, 3), 0);
+    assert_eq!(alpha(&buf, 23, 13), 255);
+    assert_eq!(alpha(&buf, 22, 12), 255);
+    assert_eq!(alpha(&buf, 25, 15), 255);
+    assert_eq!(alpha(&buf, 26, 13), 0);
+
+    // Halfway the linear ease puts it at (10, 5).
+    comp.render_sync(5, &mut buf, 32, 32, 32 * 4);
+    assert_eq!(alpha(&buf, 12, 7), 255);
+    assert_eq!(alpha(&buf, 11, 7), 0);
+}
@@ -0,0 +1,42 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::types::{Layer, Paint};
+
+fn load(name: &str) -> rlottie_core::types::Composition {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data")
+        .join(name);
+    json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn visit_counts_every_paint() {
+    // root fill, precomp fill, nested matte-consumer fill and root stroke
+    let mut comp = load("precomp_matte.json");
+    let mut count = 0;
+    comp.visit_paints_mut(|_| count += 1);
+    assert_eq!(count, 4);
+}
+
+#[test]
+fn visit_doubles_fill_alpha() {
+    let mut comp = load("fill_stroke.json");
+    if let Layer::Shape(shape) = &mut comp.layers[0] {
+        shape.stroke = None;
+        shape.fill.as_mut().unwrap().a = 100;
+    }
+    let alpha = |comp: &rlottie_core::types::Composition| {
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+        buf[(4 * 8 + 4) * 4 + 3]
+    };
+    let before = alpha(&comp);
+    comp.visit_paints_mut(|paint| {
+        if let Paint::Solid(c) = paint {
+            c.a = c.a.saturating_mul(2);
+        }
+    });
+    assert_eq!(before, 100);
+    assert_eq!(alpha(&comp), 200);
+}
@@ -0,0 +1,37 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::renderer::{Player, Renderer};
+
+fn player() -> Player {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/precomp_remap.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    Player::new(comp, Renderer::new(), 320, 10).unwrap()
+}
+
+#[test]
+fn one_second_at_30fps_reaches_frame_30() {
+    let mut player = player();
+    let first = player.current_frame_buffer().to_vec();
+    player.advance(1.0);
+    assert_eq!(player.current_frame(), 30);
+    let buf = player.current_frame_buffer();
+    assert_eq!(buf.len(), 320 * 10 * 4);
+    assert!(buf.chunks(4).any(|px| px[3] > 0));
+    assert_ne!(buf, first.as_slice());
+    assert!(!player.is_finished());
+}
+
+#[test]
+fn non_looping_player_holds_last_frame() {
+    let mut player = player();
+    player.looping = false;
+    player.advance(0.5);
+    assert!(!player.is_finished());
+    player.advance(5.0);
+    // The fixture's last frame is its `op` of 31.
+    assert_eq!(player.current_frame(), 31);
+    assert!(player.is_finished());
+}
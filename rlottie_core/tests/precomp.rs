@@ -12,3 +12,142 @@ fn render_precomp_layer() {
     let off = 4 * 8 * 4 + 4 * 4;
     assert_eq!(&buf[off..off + 4], &[0, 0, 255, 255]);
 }
+
+#[test]
+fn render_nested_precomp_by_id() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/precomp_nested.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    // layer drawn before the precomp must survive it
+    let before = 2 * 8 * 4 + 2 * 4;
+    assert_eq!(&buf[before..before + 4], &[255, 0, 0, 255]);
+    // nested layers resolved through two asset references
+    let nested = 5 * 8 * 4 + 5 * 4;
+    assert_eq!(&buf[nested..nested + 4], &[0, 0, 255, 255]);
+}
+
+#[test]
+fn flatten_precomps_renders_identically() {
+    for name in ["precomp_nested.json", "precomp_matte.json"] {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/data")
+            .join(name);
+        let data = std::fs::read(path).unwrap();
+        let comp = json::from_slice(&data).unwrap();
+        let mut flat = comp.clone();
+        flat.flatten_precomps();
+        assert!(
+            !flat
+                .layers
+                .iter()
+                .any(|l| matches!(l, rlottie_core::types::Layer::PreComp(_))),
+            "{name} kept a precomp"
+        );
+        let (w, h) = (comp.width as usize, comp.height as usize);
+        for frame in [0, 5] {
+            let mut before = vec![0u8; w * h * 4];
+            let mut after = vec![0u8; w * h * 4];
+            comp.render_sync(frame, &mut before, w, h, w * 4);
+            flat.render_sync(frame, &mut after, w, h, w * 4);
+            assert_eq!(before, after, "{name} frame {frame}");
+        }
+    }
+}
+
+#[test]
+fn missing_root_size_falls_back_to_precomp_asset() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/precomp_sized.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    assert_eq!((comp.width, comp.height), (12, 6));
+    assert_eq!(comp.fps, 24.0);
+    let mut buf = vec![0u8; 12 * 6 * 4];
+    comp.render_sync(0, &mut buf, 12, 6, 12 * 4);
+    let off = (3 * 12 + 6) * 4;
+    assert_eq!(&buf[off..off + 4], &[0, 0, 255, 255]);
+}
+
+#[test]
+fn collapsed_precomp_stays_crisp_when_scaled() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/precomp_collapse.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut renders = Vec::new();
+    for (i, collapse) in [(0, true), (1, false)] {
+        let rlottie_core::types::Layer::PreComp(pre) = &comp.layers[i] else {
+            panic!("expected precomp layer");
+        };
+        assert_eq!(pre.collapse_transform, collapse);
+        assert_eq!((pre.comp.width, pre.comp.height), (8, 8));
+        let mut single = comp.clone();
+        single.layers = vec![comp.layers[i].clone()];
+        // 16x16 composition drawn twice as large, holding an 8x8 precomp
+        // scaled 200%.
+        let mut buf = vec![0u8; 32 * 32 * 4];
+        single.render_sync(0, &mut buf, 32, 32, 32 * 4);
+        renders.push(buf);
+    }
+    let alpha = |buf: &[u8], x: usize, y: usize| buf[(y * 32 + x) * 4 + 3];
+    // Edges at 1.5 and 6.25 scale by 4 to 6 and 25.
+    let collapsed = &renders[0];
+    for y in 0..32 {
+        for x in 0..32 {
+            let inside = (6..25).contains(&x) && (6..25).contains(&y);
+            assert_eq!(
+                alpha(collapsed, x, y),
+                if inside { 255 } else { 0 },
+                "({x}, {y})"
+            );
+        }
+    }
+    // The plain precomp scales a 16x16 raster, so its edges snap to
+    // multiples of 2 and cannot land on 25.
+    let raster = &renders[1];
+    assert_eq!(alpha(raster, 6, 6), 255);
+    assert_eq!(alpha(raster, 24, 16), alpha(raster, 25, 16));
+    assert_ne!(renders[0], renders[1]);
+}
+
+#[test]
+fn precomp_layer_is_placed_faded_and_parented() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/precomp_transform.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+    // The 2x2 square moves by its own (1, 1) and its null parent's (2, 0).
+    assert_eq!(px(3, 1), &[128, 0, 0, 128]);
+    assert_eq!(px(4, 2), &[128, 0, 0, 128]);
+    assert_eq!(px(0, 0), &[0, 0, 0, 0]);
+    assert_eq!(px(2, 1), &[0, 0, 0, 0]);
+    assert_eq!(px(5, 1), &[0, 0, 0, 0]);
+}
+
+#[test]
+fn flatten_precomps_renumbers_colliding_indices() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/precomp_collide.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let mut flat = comp.clone();
+    flat.flatten_precomps();
+    assert_eq!(flat.layers.len(), 4);
+
+    let mut before = vec![0u8; 8 * 8 * 4];
+    let mut after = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut before, 8, 8, 8 * 4);
+    flat.render_sync(0, &mut after, 8, 8, 8 * 4);
+    assert_eq!(before, after);
+    // Both `"ind": 1` nulls keep moving only their own children.
+    let px = |x: usize, y: usize| &after[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+    assert_eq!(px(4, 0), &[255, 0, 0, 255]);
+    assert_eq!(px(0, 4), &[0, 0, 255, 255]);
+}
@@ -0,0 +1,24 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::renderer::{Rect, Renderer};
+
+#[test]
+fn builder_methods_configure_a_renderer() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let renderer = Renderer::new().with_clear(false).with_clip(Rect {
+        x: 0,
+        y: 0,
+        width: 4,
+        height: 8,
+    });
+    assert!(!renderer.clear);
+    let mut buf = vec![7u8; 8 * 8 * 4];
+    renderer.render(&comp, 0, &mut buf, 8, 8, 8 * 4).unwrap();
+    let px = |x: usize, y: usize| &buf[(y * 8 + x) * 4..][..4];
+    // Drawn inside the clip, untouched outside it.
+    assert_ne!(px(2, 4), &[7, 7, 7, 7]);
+    assert_eq!(px(6, 4), &[7, 7, 7, 7]);
+}
@@ -9,7 +9,33 @@ fn render_repeater() {
     let comp = json::from_slice(&data).unwrap();
     let mut buf = vec![0u8; 8 * 4 * 4];
     comp.render_sync(0, &mut buf, 8, 4, 8 * 4);
-    // third copy should affect pixel around x=5,y=1
-    let idx = 1 * 8 * 4 + 5 * 4;
-    assert_eq!(&buf[idx..idx + 4], &[0, 0, 0, 255]);
+    let px = |x: usize, y: usize| &buf[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+    // One-pixel square at x=1 stamped three times, 2 pixels apart.
+    for x in [1, 3, 5] {
+        assert_eq!(px(x, 1), &[0, 0, 0, 255], "copy at x={x}");
+    }
+    for x in [0, 2, 4, 6, 7] {
+        assert_eq!(px(x, 1), &[0, 0, 0, 0], "gap at x={x}");
+    }
+}
+
+#[test]
+fn repeater_only_copies_shapes_before_it_in_its_group() {
+    let data = br#"{"v":"5.5","fr":30,"ip":0,"op":10,"w":8,"h":4,"layers":[{"ty":4,"shapes":[
+        {"ty":"fl","c":{"k":[0,0,0,1]}},
+        {"ty":"sh","ks":{"d":"m 0 2 l 1 2 l 1 3 l 0 3 o"}},
+        {"ty":"gr","it":[
+            {"ty":"sh","ks":{"d":"m 1 0 l 2 0 l 2 1 l 1 1 o"}},
+            {"ty":"rp","c":{"k":3},"tr":{"p":{"k":[2,0]}}},
+            {"ty":"sh","ks":{"d":"m 7 3 l 8 3 l 8 4 l 7 4 o"}}
+        ]}
+    ]}]}"#;
+    let comp = json::from_slice(data).unwrap();
+    let mut buf = vec![0u8; 8 * 4 * 4];
+    comp.render_sync(0, &mut buf, 8, 4, 8 * 4);
+    let alpha = |x: usize, y: usize| buf[(y * 8 + x) * 4 + 3];
+    assert_eq!([alpha(1, 0), alpha(3, 0), alpha(5, 0)], [255; 3]);
+    // The shape outside the group and the one after the repeater stay single.
+    assert_eq!([alpha(0, 2), alpha(2, 2)], [255, 0]);
+    assert_eq!([alpha(7, 3), alpha(5, 3)], [255, 0]);
 }
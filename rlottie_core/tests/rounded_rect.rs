@@ -0,0 +1,22 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn rounded_rect_leaves_corners_empty() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/rounded_rect.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut buf = vec![0u8; 32 * 32 * 4];
+    comp.render_sync(0, &mut buf, 32, 32, 32 * 4);
+    let px = |x: usize, y: usize| &buf[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4];
+    // 24×16 around (16, 16) spans x 4..28 and y 8..24, corner radius 6.
+    assert_eq!(px(16, 16), &[0, 0, 255, 255]);
+    assert_eq!(px(16, 8), &[0, 0, 255, 255]);
+    assert_eq!(px(4, 16), &[0, 0, 255, 255]);
+    for (x, y) in [(4, 8), (27, 8), (4, 23), (27, 23)] {
+        assert_eq!(px(x, y), &[0, 0, 0, 0], "corner ({x}, {y})");
+    }
+}
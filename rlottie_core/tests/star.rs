@@ -0,0 +1,26 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+#[test]
+fn star_has_five_filled_spokes() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/star.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+
+    let mut buf = vec![0u8; 64 * 64 * 4];
+    comp.render_sync(0, &mut buf, 64, 64, 64 * 4);
+    let alpha_at = |deg: f32, r: f32| {
+        let a = (deg - 90.0).to_radians();
+        let x = (32.0 + r * a.cos()) as usize;
+        let y = (32.0 + r * a.sin()) as usize;
+        buf[(y * 64 + x) * 4 + 3]
+    };
+    // Outer radius 28, inner 10: spokes every 72° starting straight up,
+    // with the gaps between them halfway round.
+    let spokes = (0..5).filter(|i| alpha_at(*i as f32 * 72.0, 20.0) == 255);
+    assert_eq!(spokes.count(), 5);
+    let gaps = (0..5).filter(|i| alpha_at(*i as f32 * 72.0 + 36.0, 20.0) == 0);
+    assert_eq!(gaps.count(), 5);
+    assert_eq!(alpha_at(0.0, 0.0), 255);
+}
@@ -0,0 +1,32 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+
+/// Number of covered pixels in column `x` of a 16×16 RGBA buffer.
+fn band_height(buf: &[u8], x: usize) -> usize {
+    (0..16).filter(|y| buf[(y * 16 + x) * 4 + 3] > 0).count()
+}
+
+#[test]
+fn animated_stroke_width_thickens() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/stroke_width_anim.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    if let rlottie_core::types::Layer::Shape(shape) = &comp.layers[0] {
+        assert_eq!(shape.stroke_width_at(0.0), 1.0);
+        assert_eq!(shape.stroke_width_at(10.0), 8.0);
+    } else {
+        panic!("expected shape layer");
+    }
+
+    let mut heights = Vec::new();
+    for frame in [0, 5, 10] {
+        let mut buf = vec![0u8; 16 * 16 * 4];
+        comp.render_sync(frame, &mut buf, 16, 16, 16 * 4);
+        heights.push(band_height(&buf, 8));
+    }
+    assert!(heights[0] < heights[1], "{heights:?}");
+    assert!(heights[1] < heights[2], "{heights:?}");
+    assert!(heights[2] >= 7, "{heights:?}");
+}
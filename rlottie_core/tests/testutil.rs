@@ -26,5 +26,6 @@ pub fn transform_strategy() -> impl Strategy<Value = Transform> {
             rotation,
             opacity,
             animators: std::collections::HashMap::new(),
+            motion: Vec::new(),
         })
 }
@@ -3,7 +3,11 @@
 //! Text rendering test
 
 use fontdue::Font;
-use rlottie_core::types::{Color, Composition, Layer, TextLayer, Vec2};
+use rlottie_core::types::{
+    Color, ColorSpace, Composition, GlyphOutlines, GradientStop, Layer, LinearGradient, Paint,
+    TextLayer, Vec2,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[test]
@@ -21,6 +25,7 @@ fn render_simple_text() {
         size: 32.0,
         position: Vec2 { x: 0.0, y: 32.0 },
         font,
+        outlines: None,
     };
     let comp = Composition {
         width: 64,
@@ -28,9 +33,72 @@ fn render_simple_text() {
         start_frame: 0,
         end_frame: 0,
         fps: 60.0,
+        pixel_aspect: 1.0,
+        opacity: 1.0,
         layers: vec![Layer::Text(layer)],
+        color_controls: HashMap::new(),
+        markers: Vec::new(),
     };
     let mut buf = vec![0u8; 64 * 64 * 4];
     comp.render_sync(0, &mut buf, 64, 64, 64 * 4);
     assert!(buf.iter().any(|&b| b != 0));
 }
+
+#[test]
+fn gradient_fills_glyph_outlines() {
+    let font_bytes = std::fs::read("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf").unwrap();
+    let font =
+        Arc::new(Font::from_bytes(font_bytes.clone(), fontdue::FontSettings::default()).unwrap());
+    let stop = |offset, r, b| GradientStop {
+        offset,
+        color: Color { r, g: 0, b, a: 255 },
+    };
+    let fill = Paint::Linear(LinearGradient {
+        start: Vec2 { x: 0.0, y: 0.0 },
+        end: Vec2 { x: 64.0, y: 0.0 },
+        stops: vec![stop(0.0, 255, 0), stop(1.0, 0, 255)],
+        opacity_stops: Vec::new(),
+        space: ColorSpace::Rgb,
+    });
+    let layer = TextLayer {
+        text: "M".to_string(),
+        color: Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 255,
+        },
+        size: 56.0,
+        position: Vec2 { x: 4.0, y: 56.0 },
+        font,
+        outlines: Some(GlyphOutlines {
+            font_data: Arc::new(font_bytes),
+            fill,
+            stroke: None,
+        }),
+    };
+    let comp = Composition {
+        width: 64,
+        height: 64,
+        start_frame: 0,
+        end_frame: 0,
+        fps: 60.0,
+        pixel_aspect: 1.0,
+        opacity: 1.0,
+        layers: vec![Layer::Text(layer)],
+        color_controls: HashMap::new(),
+        markers: Vec::new(),
+    };
+    let mut buf = vec![0u8; 64 * 64 * 4];
+    comp.render_sync(0, &mut buf, 64, 64, 64 * 4);
+
+    // Opaque glyph pixels, left to right: red fades into blue.
+    let covered: Vec<&[u8]> = (0..64)
+        .flat_map(|x| (0..64).map(move |y| (y * 64 + x) * 4))
+        .map(|o| &buf[o..o + 4])
+        .filter(|px| px[3] == 255)
+        .collect();
+    let (left, right) = (covered.first().unwrap(), covered.last().unwrap());
+    assert!(left[0] > left[2], "left {left:?}");
+    assert!(right[2] > right[0], "right {right:?}");
+}
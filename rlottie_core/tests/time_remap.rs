@@ -1,6 +1,8 @@
 // Copyright © SoftOboros Technology, Inc.
 // SPDX-License-Identifier: MIT
 use rlottie_core::loader::json;
+use rlottie_core::timeline::CubicBezier;
+use rlottie_core::types::{Frame, Vec2};
 
 #[test]
 fn frame_looping() {
@@ -8,7 +10,80 @@ fn frame_looping() {
         std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
     let data = std::fs::read(path).unwrap();
     let comp = json::from_slice(&data).unwrap();
-    assert_eq!(comp.frame_at(0), comp.start_frame);
+    assert_eq!(comp.frame_at(0), Frame(comp.start_frame));
     // end_frame is 10 in fixture
-    assert_eq!(comp.frame_at(12), comp.start_frame + 1);
+    assert_eq!(comp.frame_at(12), Frame(comp.start_frame + 1));
+}
+
+#[test]
+fn render_sync_accepts_frame() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    let mut typed = vec![0u8; 16 * 16 * 4];
+    let mut raw = vec![0u8; 16 * 16 * 4];
+    comp.render_sync(Frame(3), &mut typed, 16, 16, 16 * 4);
+    comp.render_sync(3, &mut raw, 16, 16, 16 * 4);
+    assert_eq!(typed, raw);
+}
+
+#[test]
+fn remapped_precomp_follows_remap_curve() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/precomp_remap.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    // Nested square slides 10 px per frame; the remap eases in over 1 s.
+    let ease = CubicBezier::new(Vec2 { x: 0.5, y: 0.0 }, Vec2 { x: 1.0, y: 1.0 });
+    let mut buf = vec![0u8; 320 * 10 * 4];
+    for frame in [5u32, 12, 18, 25] {
+        comp.render_sync(frame, &mut buf, 320, 10, 320 * 4);
+        let left = (0..320).find(|x| buf[(5 * 320 + x) * 4 + 3] != 0).unwrap();
+        let expected = ease.value(frame as f32 / 30.0) * 300.0;
+        assert!(
+            (left as f32 - expected).abs() <= 1.0,
+            "frame {frame}: left edge {left}, expected {expected}"
+        );
+    }
+}
+
+#[test]
+fn remapped_shape_layer_plays_in_reverse() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/shape_remap_reverse.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    // Opacity ramps 0..100 over 1 s, but the remap runs layer time from
+    // 1 s back to 0 s, so the layer fades out instead of in.
+    let alpha_at = |frame: u32| {
+        let mut buf = vec![0u8; 10 * 10 * 4];
+        comp.render_sync(frame, &mut buf, 10, 10, 10 * 4);
+        buf[(5 * 10 + 5) * 4 + 3]
+    };
+    let alphas: Vec<u8> = [0, 3, 6, 9].map(alpha_at).to_vec();
+    assert!(alphas.windows(2).all(|w| w[0] > w[1]), "{alphas:?}");
+    assert_eq!(alphas[0], 255);
+}
+
+#[test]
+fn start_time_delays_shape_and_precomp_layers_alike() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/layer_start_time.json");
+    let data = std::fs::read(path).unwrap();
+    let comp = json::from_slice(&data).unwrap();
+    // Both halves fade in over frames 0..10 of their own time and start
+    // at composition frame 5: the shape layer on the left, a precomp with
+    // the same fade on the right.
+    let alphas_at = |frame: u32| {
+        let mut buf = vec![0u8; 10 * 10 * 4];
+        comp.render_sync(frame, &mut buf, 10, 10, 10 * 4);
+        (buf[(5 * 10 + 2) * 4 + 3], buf[(5 * 10 + 7) * 4 + 3])
+    };
+    assert_eq!(alphas_at(3), (0, 0));
+    assert_eq!(alphas_at(5), (0, 0));
+    let (left, right) = alphas_at(10);
+    assert_eq!(left, right);
+    assert!((120..=135).contains(&left), "{left}");
+    assert_eq!(alphas_at(15), (255, 255));
 }
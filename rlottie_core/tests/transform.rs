@@ -0,0 +1,131 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::loader::json;
+use rlottie_core::renderer::Renderer;
+use rlottie_core::types::{Layer, Vec2, OPACITY, POSITION_X, POSITION_Y, SCALE_X, SCALE_Y};
+
+fn load() -> rlottie_core::types::Composition {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/transformed_rect.json");
+    json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn layer_transform_moves_and_scales_fill() {
+    let comp = load();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.transform.position, Vec2 { x: 20.0, y: 20.0 });
+    assert_eq!(shape.transform.scale, Vec2 { x: 2.0, y: 2.0 });
+
+    let mut buf = vec![0u8; 64 * 64 * 4];
+    comp.render_sync(0, &mut buf, 64, 64, 64 * 4);
+    let alpha = |x: usize, y: usize| buf[(y * 64 + x) * 4 + 3];
+    // The 10x10 square at the origin lands on 20..40.
+    for (x, y) in [(20, 20), (39, 20), (20, 39), (39, 39), (30, 30)] {
+        assert_eq!(alpha(x, y), 255, "pixel {x},{y}");
+    }
+    for (x, y) in [(5, 5), (19, 30), (40, 30), (30, 19), (30, 40)] {
+        assert_eq!(alpha(x, y), 0, "pixel {x},{y}");
+    }
+}
+
+#[test]
+fn layer_opacity_scales_fill_alpha() {
+    let mut comp = load();
+    if let Layer::Shape(shape) = &mut comp.layers[0] {
+        shape.transform.opacity = 0.5;
+    }
+    let mut buf = vec![0u8; 64 * 64 * 4];
+    comp.render_sync(0, &mut buf, 64, 64, 64 * 4);
+    assert_eq!(buf[(30 * 64 + 30) * 4 + 3], 128);
+}
+
+#[test]
+fn animated_opacity_fades_fill_in() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/opacity_anim.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert!(shape.transform.animators.contains_key(OPACITY));
+
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    let alpha_at = |buf: &mut Vec<u8>, frame: u32| {
+        comp.render_sync(frame, buf, 8, 8, 8 * 4);
+        buf[(4 * 8 + 4) * 4 + 3]
+    };
+    assert_eq!(alpha_at(&mut buf, 0), 0);
+    assert_eq!(alpha_at(&mut buf, 15), 128);
+    assert_eq!(alpha_at(&mut buf, 30), 255);
+}
+
+#[test]
+fn animated_anchor_moves_rotation_pivot() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/anchor_anim.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let alpha_at = |frame: u32, x: usize, y: usize| {
+        let mut buf = vec![0u8; 32 * 32 * 4];
+        comp.render_sync(frame, &mut buf, 32, 32, 32 * 4);
+        buf[(y * 32 + x) * 4 + 3]
+    };
+    // Frame 0: unrotated, pivoting on its center, so the square spans 11..21.
+    assert_eq!(alpha_at(0, 19, 19), 255);
+    assert_eq!(alpha_at(0, 8, 8), 0);
+    // Frame 10: half a turn about the corner now pinned at 16,16 spans 6..16.
+    // A static anchor would have spun it in place over 11..21.
+    assert_eq!(alpha_at(10, 8, 8), 255);
+    assert_eq!(alpha_at(10, 19, 19), 0);
+}
+
+#[test]
+fn layer_opacity_fades_fill_and_stroke_together() {
+    let path =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/fill_stroke.json");
+    let mut comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    if let Layer::Shape(shape) = &mut comp.layers[0] {
+        shape.transform.opacity = 0.5;
+    }
+    let mut buf = [255u8; 8 * 8 * 4].to_vec();
+    Renderer::new()
+        .render_over(&comp, 0, &mut buf, 8, 8, 8 * 4)
+        .unwrap();
+    let px = |x: usize, y: usize| &buf[(y * 8 + x) * 4..][..4];
+    // Half of the blue fill over white.
+    assert_eq!(px(4, 4), &[128, 128, 255, 255]);
+    // The stroke covers the fill inside the layer, so none of the blue
+    // shows through where they overlap.
+    assert_eq!(px(1, 4), &[255, 128, 128, 255]);
+}
+
+#[test]
+fn bouncing_square_follows_separate_position_and_scale() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/data/bounce.json");
+    let comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let Layer::Shape(shape) = &comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    for key in [POSITION_X, POSITION_Y, SCALE_X, SCALE_Y] {
+        assert!(shape.transform.animators.contains_key(key), "{key}");
+    }
+
+    let covered = |frame: u32| {
+        let mut buf = vec![0u8; 32 * 32 * 4];
+        comp.render_sync(frame, &mut buf, 32, 32, 32 * 4);
+        let alpha = |x: usize, y: usize| buf[(y * 32 + x) * 4 + 3];
+        let xs: Vec<usize> = (0..32)
+            .filter(|&x| (0..32).any(|y| alpha(x, y) > 0))
+            .collect();
+        let ys: Vec<usize> = (0..32)
+            .filter(|&y| (0..32).any(|x| alpha(x, y) > 0))
+            .collect();
+        (xs[0]..xs[xs.len() - 1] + 1, ys[0]..ys[ys.len() - 1] + 1)
+    };
+    assert_eq!(covered(0), (0..8, 0..8));
+    // Mid-bounce: moved right and down, squashed to half height.
+    assert_eq!(covered(15), (12..20, 24..28));
+    assert_eq!(covered(30), (24..32, 0..8));
+}
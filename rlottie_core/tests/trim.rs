@@ -1,6 +1,8 @@
 // Copyright © SoftOboros Technology, Inc.
 // SPDX-License-Identifier: MIT
 use rlottie_core::loader::json;
+use rlottie_core::renderer::Renderer;
+use rlottie_core::types::{Layer, TrimTarget};
 
 #[test]
 fn parse_trim() {
@@ -13,3 +15,106 @@ fn parse_trim() {
         panic!("expected shape layer");
     }
 }
+
+#[test]
+fn trim_accepts_percent_and_fraction() {
+    let trim_of = |s: &str, e: &str| {
+        let doc = format!(
+            r#"{{"fr":30,"ip":0,"op":10,"w":8,"h":8,"layers":[{{"ty":4,"shapes":[{{"ty":"sh","ks":{{"d":"m 1 4 l 7 4"}}}},{{"ty":"tm","s":{{"k":{s}}},"e":{{"k":{e}}}}}]}}]}}"#
+        );
+        let comp = json::from_slice(doc.as_bytes()).unwrap();
+        let rlottie_core::types::Layer::Shape(shape) = &comp.layers[0] else {
+            panic!("expected shape layer");
+        };
+        shape.trim.unwrap()
+    };
+    assert_eq!(trim_of("50", "100"), (0.5, 1.0));
+    assert_eq!(trim_of("0.5", "1"), (0.5, 1.0));
+    // Whole numbers stay percent even when they are at most 1.
+    assert_eq!(trim_of("0", "1"), (0.0, 0.01));
+}
+
+#[test]
+fn collapsed_trim_draws_nothing() {
+    let doc = |e: u32| {
+        format!(
+            r#"{{"fr":30,"ip":0,"op":10,"w":8,"h":8,"layers":[{{"ty":4,"shapes":[{{"ty":"fl","c":{{"k":[0,0,1,1]}},"o":{{"k":100}}}},{{"ty":"st","c":{{"k":[1,0,0,1]}},"o":{{"k":100}},"w":{{"k":1}}}},{{"ty":"sh","ks":{{"d":"m 1 1 l 7 1 l 7 7 z"}}}},{{"ty":"tm","s":{{"k":50}},"e":{{"k":{e}}}}}]}}]}}"#
+        )
+    };
+    let renderer = Renderer::new();
+    let mut buf = vec![0u8; 8 * 8 * 4];
+
+    let comp = json::from_slice(doc(50).as_bytes()).unwrap();
+    renderer.render(&comp, 0, &mut buf, 8, 8, 8 * 4).unwrap();
+    assert_eq!(renderer.stats().paths_drawn, 0);
+    assert!(buf.iter().all(|&b| b == 0));
+
+    let comp = json::from_slice(doc(100).as_bytes()).unwrap();
+    renderer.render(&comp, 0, &mut buf, 8, 8, 8 * 4).unwrap();
+    assert_eq!(renderer.stats().paths_drawn, 2);
+}
+
+#[test]
+fn trim_after_fill_only_draws_on_the_stroke() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data/trim_stroke_only.json");
+    let mut comp = json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    let Layer::Shape(shape) = &mut comp.layers[0] else {
+        panic!("expected shape layer");
+    };
+    assert_eq!(shape.trim_target, TrimTarget::Stroke);
+
+    let mut buf = vec![0u8; 8 * 8 * 4];
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    let px =
+        |buf: &[u8], x: usize, y: usize| <[u8; 4]>::try_from(&buf[(y * 8 + x) * 4..][..4]).unwrap();
+    // The whole square is filled, including the half the stroke skips.
+    for (x, y) in [(4, 4), (2, 5), (4, 5)] {
+        assert_eq!(px(&buf, x, y), [0, 0, 255, 255], "pixel {x},{y}");
+    }
+    // The stroke covers the top edge but not yet the bottom one.
+    assert_eq!(px(&buf, 4, 0), [255, 0, 0, 255]);
+    assert_eq!(px(&buf, 4, 7), [0, 0, 0, 0]);
+
+    // Trimming both paints leaves the bottom half of the fill out too.
+    let Layer::Shape(shape) = &mut comp.layers[0] else {
+        unreachable!();
+    };
+    shape.trim_target = TrimTarget::FillAndStroke;
+    comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+    assert_eq!(px(&buf, 2, 5), [0, 0, 0, 0]);
+}
+
+#[test]
+fn paints_listed_before_the_trim_stay_whole() {
+    let target_and_pixels = |order: [&str; 3]| {
+        let item = |ty| match ty {
+            "fl" => r#"{"ty":"fl","c":{"k":[0,0,1,1]},"o":{"k":100}}"#,
+            "st" => r#"{"ty":"st","c":{"k":[1,0,0,1]},"o":{"k":100},"w":{"k":2}}"#,
+            _ => r#"{"ty":"tm","s":{"k":0},"e":{"k":50},"o":{"k":0},"m":1}"#,
+        };
+        let items: Vec<&str> = order.iter().map(|ty| item(ty)).collect();
+        let doc = format!(
+            r#"{{"fr":30,"ip":0,"op":10,"w":8,"h":8,"layers":[{{"ty":4,"shapes":[{{"ty":"sh","ks":{{"d":"m 1 1 l 7 1 l 7 7 l 1 7 o"}}}},{}]}}]}}"#,
+            items.join(",")
+        );
+        let comp = json::from_slice(doc.as_bytes()).unwrap();
+        let Layer::Shape(shape) = &comp.layers[0] else {
+            panic!("expected shape layer");
+        };
+        let mut buf = vec![0u8; 8 * 8 * 4];
+        comp.render_sync(0, &mut buf, 8, 8, 8 * 4);
+        let px = |x: usize, y: usize| <[u8; 4]>::try_from(&buf[(y * 8 + x) * 4..][..4]).unwrap();
+        // Inside the half the trim drops, and on its bottom edge.
+        (shape.trim_target, px(2, 5), px(4, 7))
+    };
+    let (target, inside, edge) = target_and_pixels(["fl", "st", "tm"]);
+    assert_eq!(target, TrimTarget::None);
+    assert_eq!(inside, [0, 0, 255, 255]);
+    assert_eq!(edge, [255, 0, 0, 255]);
+
+    let (target, inside, edge) = target_and_pixels(["st", "tm", "fl"]);
+    assert_eq!(target, TrimTarget::Fill);
+    assert_eq!(inside, [0, 0, 0, 0]);
+    assert_eq!(edge, [255, 0, 0, 255]);
+}
@@ -0,0 +1,30 @@
+// Copyright © SoftOboros Technology, Inc.
+// SPDX-License-Identifier: MIT
+use rlottie_core::types::Composition;
+
+fn read(name: &str) -> Vec<u8> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data")
+        .join(name);
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn lists_3d_layers_and_expressions() {
+    let found = Composition::unsupported_features(&read("unsupported.json"));
+    assert_eq!(found, ["3D layers", "expressions"]);
+}
+
+#[test]
+fn supported_files_report_nothing() {
+    // The fill color expression there only links a Color Control effect.
+    assert!(Composition::unsupported_features(&read("color_control.json")).is_empty());
+    assert!(Composition::unsupported_features(&read("fill_stroke.json")).is_empty());
+}
+
+#[test]
+fn invalid_json_is_reported() {
+    let found = Composition::unsupported_features(b"{");
+    assert_eq!(found.len(), 1);
+    assert!(found[0].starts_with("unreadable JSON"));
+}
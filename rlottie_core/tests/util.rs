@@ -1,10 +1,10 @@
 use image::codecs::png::PngEncoder;
-use image::io::Reader as ImageReader;
-use image::{ColorType, ImageEncoder};
+use image::{ColorType, ImageEncoder, ImageReader};
 use rlottie_core::types::Composition;
 use sha2::{Digest, Sha256};
 use std::path::Path;
 
+#[allow(dead_code)]
 pub fn render_hash(anim: &Composition, frame: u32) -> [u8; 32] {
     let png = render_png(anim, frame);
     let digest = Sha256::digest(&png);